@@ -0,0 +1,39 @@
+<?xml version="1.0" encoding="UTF-8"?>
+<tileset version="1.10" tiledversion="1.10.2" name="tileset" tilewidth="16" tileheight="16" tilecount="7" columns="7">
+ <image source="tileset.png" width="112" height="16"/>
+ <tile id="1">
+  <properties>
+   <property name="kind" value="solid"/>
+  </properties>
+ </tile>
+ <tile id="2">
+  <properties>
+   <property name="kind" value="pit"/>
+   <property name="damage" type="float" value="4"/>
+  </properties>
+ </tile>
+ <tile id="3">
+  <properties>
+   <property name="kind" value="shallow_water"/>
+   <property name="speed" type="float" value="0.5"/>
+  </properties>
+ </tile>
+ <tile id="4">
+  <properties>
+   <property name="kind" value="deep_water"/>
+   <property name="damage" type="float" value="4"/>
+  </properties>
+ </tile>
+ <tile id="5">
+  <properties>
+   <property name="kind" value="ledge"/>
+   <property name="facing" value="down"/>
+  </properties>
+ </tile>
+ <tile id="6">
+  <properties>
+   <property name="kind" value="damaging_floor"/>
+   <property name="damage" type="float" value="2"/>
+  </properties>
+ </tile>
+</tileset>
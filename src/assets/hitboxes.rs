@@ -0,0 +1,344 @@
+use crate::attack::Attack;
+use crate::collision::shapes::MAX_POLYGON_POINTS;
+use crate::collision::{Hitbox, HitboxAnimation, HitboxFrame, HitboxFrameString};
+use crate::{Direction, RESOURCES_DIR};
+use ggez::graphics::Rect;
+use ggez::{Context, GameError, GameResult};
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// A [`HitboxFrameString`], and optionally the intervals to play it as a [`HitboxAnimation`], as stored in a JSON file.
+///
+/// ```json
+/// {
+///     "direction": "right",
+///     "intervals": [4, 5],
+///     "frames": [
+///         {
+///             "attack": { "damage": 5.0, "weight": 1.0 },
+///             "hitboxes": [{ "point_size": { "center": [0.0, 80.0], "size": 40.0 } }]
+///         }
+///     ]
+/// }
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HitboxAnimationData {
+    /// Which way the hitboxes in the file are facing.
+    pub direction: Direction,
+    /// The amount of frames between each keyframe. Only needed if the file is loaded as an animation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub intervals: Vec<usize>,
+    /// How far hitboxes move towards the next keyframe as they're lerped, from 0 to 1, see [`Hitbox::twine_lerp`].
+    #[serde(default = "full_twine")]
+    pub twine: f32,
+    pub frames: Vec<HitboxFrameData>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HitboxFrameData {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub attack: Option<Attack>,
+    pub hitboxes: Vec<HitboxData>,
+}
+
+/// One hitbox of a frame, written as the constructor of [`Hitbox`] it's made with.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+pub enum HitboxData {
+    Rect {
+        x: f32,
+        y: f32,
+        w: f32,
+        h: f32,
+    },
+    PointSize {
+        center: Vec2,
+        size: f32,
+    },
+    Circle {
+        center: Vec2,
+        radius: f32,
+    },
+    Capsule {
+        start: Vec2,
+        end: Vec2,
+        radius: f32,
+    },
+    Oriented {
+        center: Vec2,
+        half_extents: Vec2,
+        angle: f32,
+    },
+    Polygon {
+        points: Vec<Vec2>,
+    },
+}
+
+impl HitboxAnimationData {
+    /// Reads and checks a hitbox file from the resources folder.
+    pub fn load(ctx: &Context, path: &str) -> GameResult<Self> {
+        let file = ctx.fs.open(path)?;
+        let data: Self = serde_json::from_reader(file)
+            .map_err(|error| GameError::ResourceLoadError(format!("{path}: {error}")))?;
+        data.validate(path)?;
+        Ok(data)
+    }
+
+    /// Loads the file as a [`HitboxFrameString`], ignoring any intervals.
+    pub fn load_frame_string(ctx: &Context, path: &str) -> GameResult<HitboxFrameString> {
+        Ok(Self::load(ctx, path)?.frame_string())
+    }
+
+    /// Loads the file as a [`HitboxAnimation`]. See [`HitboxAnimationData::animation`].
+    pub fn load_animation(ctx: &Context, path: &str) -> GameResult<HitboxAnimation> {
+        Self::load(ctx, path)?.animation(path)
+    }
+
+    /// Checks the data again and writes it back to the file in the resources folder it was loaded from.
+    ///
+    /// This writes to the source tree rather than the user data folder, since it's only meant for the
+    /// [`HitboxEditor`](crate::editor::HitboxEditor) in debug builds.
+    pub fn save(&self, path: &str) -> GameResult {
+        self.validate(path)?;
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|error| GameError::ResourceLoadError(format!("{path}: {error}")))?;
+        std::fs::write(
+            Path::new(RESOURCES_DIR).join(path.trim_start_matches('/')),
+            json + "\n",
+        )?;
+        Ok(())
+    }
+
+    /// Makes a [`HitboxAnimation`] out of the data, which needs intervals, and the same amount of hitboxes
+    /// in every frame so that they can be lerped between. `path` is only used for errors.
+    pub fn animation(&self, path: &str) -> GameResult<HitboxAnimation> {
+        if self.intervals.is_empty() {
+            return Err(invalid(
+                path,
+                None,
+                None,
+                "animations need at least one interval",
+            ));
+        }
+        if self.intervals.len() > self.frames.len() {
+            return Err(invalid(
+                path,
+                None,
+                None,
+                &format!(
+                    "there are {} intervals, but only {} frames to play them with",
+                    self.intervals.len(),
+                    self.frames.len()
+                ),
+            ));
+        }
+        let expected = self.frames[0].hitboxes.len();
+        for (index, frame) in self.frames.iter().enumerate() {
+            if frame.hitboxes.len() != expected {
+                return Err(invalid(
+                    path,
+                    Some(index),
+                    None,
+                    &format!(
+                        "has {} hitboxes, but frame 0 has {expected}, so they can't be lerped between",
+                        frame.hitboxes.len()
+                    ),
+                ));
+            }
+        }
+
+        let string = self.frame_string();
+        let borrowed = string.borrow();
+        let mut animation = HitboxAnimation::new(borrowed.borrow(), self.intervals.clone());
+        animation.set_twine(self.twine);
+        Ok(animation)
+    }
+
+    pub fn frame_string(&self) -> HitboxFrameString {
+        HitboxFrameString::new(
+            self.frames
+                .iter()
+                .map(|frame| {
+                    let hitboxes: Vec<Hitbox> =
+                        frame.hitboxes.iter().map(HitboxData::hitbox).collect();
+                    let mut hitbox_frame = HitboxFrame::new(&hitboxes, self.direction);
+                    hitbox_frame.set_attack(frame.attack);
+                    hitbox_frame
+                })
+                .collect(),
+        )
+    }
+
+    fn validate(&self, path: &str) -> GameResult {
+        if self.frames.is_empty() {
+            return Err(invalid(path, None, None, "there are no frames"));
+        }
+        if !(0.0..=1.0).contains(&self.twine) {
+            return Err(invalid(
+                path,
+                None,
+                None,
+                "twine has to be a number from 0 to 1",
+            ));
+        }
+        for (frame_index, frame) in self.frames.iter().enumerate() {
+            if let Some(attack) = frame.attack {
+                if !(attack.damage.is_finite() && attack.weight.is_finite()) {
+                    return Err(invalid(
+                        path,
+                        Some(frame_index),
+                        None,
+                        "the attack's damage and weight have to be numbers",
+                    ));
+                }
+            }
+            for (hitbox_index, hitbox) in frame.hitboxes.iter().enumerate() {
+                if let Err(problem) = hitbox.validate() {
+                    return Err(invalid(
+                        path,
+                        Some(frame_index),
+                        Some(hitbox_index),
+                        &problem,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl HitboxData {
+    pub fn hitbox(&self) -> Hitbox {
+        match self {
+            HitboxData::Rect { x, y, w, h } => Hitbox::new(Rect::new(*x, *y, *w, *h)),
+            HitboxData::PointSize { center, size } => Hitbox::point_size(*center, *size),
+            HitboxData::Circle { center, radius } => Hitbox::circle(*center, *radius),
+            HitboxData::Capsule { start, end, radius } => Hitbox::capsule(*start, *end, *radius),
+            HitboxData::Oriented {
+                center,
+                half_extents,
+                angle,
+            } => Hitbox::oriented(*center, *half_extents, *angle),
+            HitboxData::Polygon { points } => Hitbox::polygon(points),
+        }
+    }
+
+    /// Moves the whole hitbox.
+    pub fn translate(&mut self, delta: Vec2) {
+        match self {
+            HitboxData::Rect { x, y, .. } => {
+                *x += delta.x;
+                *y += delta.y;
+            }
+            HitboxData::PointSize { center, .. }
+            | HitboxData::Circle { center, .. }
+            | HitboxData::Oriented { center, .. } => *center += delta,
+            HitboxData::Capsule { start, end, .. } => {
+                *start += delta;
+                *end += delta;
+            }
+            HitboxData::Polygon { points } => points.iter_mut().for_each(|point| *point += delta),
+        }
+    }
+
+    /// Resizes the hitbox so that its edge reaches `point`, keeping where it is.
+    ///
+    /// Rects are resized from their bottom right corner, and polygons move whichever point is closest.
+    pub fn resize_to(&mut self, point: Vec2) {
+        match self {
+            HitboxData::Rect { x, y, w, h } => {
+                *w = (point.x - *x).max(0.0);
+                *h = (point.y - *y).max(0.0);
+            }
+            HitboxData::PointSize { center, size } => {
+                *size = (point - *center).abs().max_element() * 2.0;
+            }
+            HitboxData::Circle { center, radius } => *radius = center.distance(point),
+            HitboxData::Capsule { start, end, radius } => {
+                let along = (point - *start).dot(*end - *start)
+                    / start.distance_squared(*end).max(f32::EPSILON);
+                *radius = point.distance(start.lerp(*end, along.clamp(0.0, 1.0)));
+            }
+            HitboxData::Oriented {
+                center,
+                half_extents,
+                angle,
+            } => *half_extents = Vec2::from_angle(-*angle).rotate(point - *center).abs(),
+            HitboxData::Polygon { points } => {
+                if let Some(closest) = points.iter_mut().min_by(|a, b| {
+                    a.distance_squared(point)
+                        .total_cmp(&b.distance_squared(point))
+                }) {
+                    *closest = point;
+                }
+            }
+        }
+    }
+
+    /// Sizes can be zero, so that animations can shrink away to nothing, but not negative.
+    fn validate(&self) -> Result<(), String> {
+        let (numbers, sizes): (Vec<f32>, Vec<(&str, f32)>) = match self {
+            HitboxData::Rect { x, y, w, h } => (vec![*x, *y], vec![("width", *w), ("height", *h)]),
+            HitboxData::PointSize { center, size } => {
+                (center.to_array().to_vec(), vec![("size", *size)])
+            }
+            HitboxData::Circle { center, radius } => {
+                (center.to_array().to_vec(), vec![("radius", *radius)])
+            }
+            HitboxData::Capsule { start, end, radius } => (
+                [start.to_array(), end.to_array()].concat(),
+                vec![("radius", *radius)],
+            ),
+            HitboxData::Oriented {
+                center,
+                half_extents,
+                angle,
+            } => (
+                [center.to_array(), [*angle, 0.0]].concat(),
+                vec![
+                    ("half width", half_extents.x),
+                    ("half height", half_extents.y),
+                ],
+            ),
+            HitboxData::Polygon { points } => {
+                if !(3..=MAX_POLYGON_POINTS).contains(&points.len()) {
+                    return Err(format!(
+                        "polygons need between 3 and {MAX_POLYGON_POINTS} points, not {}",
+                        points.len()
+                    ));
+                }
+                (
+                    points.iter().flat_map(|point| point.to_array()).collect(),
+                    Vec::new(),
+                )
+            }
+        };
+
+        if numbers.iter().any(|number| !number.is_finite()) {
+            return Err("every position has to be a number".to_string());
+        }
+        for (name, size) in sizes {
+            if !(size.is_finite() && size >= 0.0) {
+                return Err(format!("{name} can't be {size}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn full_twine() -> f32 {
+    1.0
+}
+
+/// An error pointing at the exact frame and hitbox that's wrong, if there is one.
+fn invalid(path: &str, frame: Option<usize>, hitbox: Option<usize>, problem: &str) -> GameError {
+    let location = match (frame, hitbox) {
+        (Some(frame), Some(hitbox)) => format!(", frame {frame}, hitbox {hitbox}"),
+        (Some(frame), None) => format!(", frame {frame}"),
+        _ => String::new(),
+    };
+    GameError::ResourceLoadError(format!("{path}{location}: {problem}"))
+}
@@ -0,0 +1,542 @@
+//! Loads maps made in [LDtk](https://ldtk.io/), saved as a single `.ldtk` project.
+//!
+//! Every level in the project's world becomes a [`Room`] of one map, laid out the way the world places them,
+//! which leads into the levels LDtk lists as its neighbours.
+//! IntGrid layers decide the tiles: each IntGrid value's identifier is read as a [`TileKind`], like `solid`
+//! or `ledge_down`, and drawn with the first tile of that kind in the tileset. Tile and auto layers are only
+//! visual in LDtk, so they're ignored.
+//!
+//! Entities are read by their identifier like any other map object, with their fields set through reflection,
+//! see [`MapData`]. LDtk entities don't have names, so entrances, triggers and doors are named by a `name` field.
+
+use super::map::{invalid, read, Field, MapData};
+use super::tileset::TilesetData;
+use crate::room::Room;
+use crate::tilemap::{TileKind, TILE_SIZE};
+use ggez::graphics::Rect;
+use ggez::{Context, GameResult};
+use glam::Vec2;
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Project {
+    #[serde(default)]
+    external_levels: bool,
+    default_grid_size: f32,
+    world_layout: Option<WorldLayout>,
+    defs: Definitions,
+    #[serde(default)]
+    levels: Vec<Level>,
+    /// Only filled in for projects with multiple worlds, in which case [`Project::levels`] is empty.
+    #[serde(default)]
+    worlds: Vec<World>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct World {
+    world_layout: Option<WorldLayout>,
+    levels: Vec<Level>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum WorldLayout {
+    Free,
+    GridVania,
+    LinearHorizontal,
+    LinearVertical,
+}
+
+#[derive(Debug, Deserialize)]
+struct Definitions {
+    layers: Vec<LayerDefinition>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayerDefinition {
+    uid: u32,
+    #[serde(default)]
+    int_grid_values: Vec<IntGridValue>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IntGridValue {
+    value: u32,
+    identifier: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Level {
+    identifier: String,
+    iid: String,
+    /// Left as -1 in linear layouts, where the levels are placed one after another instead.
+    world_x: f32,
+    world_y: f32,
+    px_wid: f32,
+    px_hei: f32,
+    /// Only `None` for levels saved in their own files.
+    layer_instances: Option<Vec<LayerInstance>>,
+    #[serde(rename = "__neighbours", default)]
+    neighbours: Vec<Neighbour>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Neighbour {
+    level_iid: String,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LayerInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__type")]
+    kind: String,
+    #[serde(rename = "__cWid")]
+    columns: usize,
+    #[serde(rename = "__cHei")]
+    rows: usize,
+    #[serde(rename = "__gridSize")]
+    grid_size: f32,
+    layer_def_uid: u32,
+    #[serde(default)]
+    int_grid_csv: Vec<u32>,
+    #[serde(default)]
+    entity_instances: Vec<Entity>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Entity {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    /// Where the entity's pivot is, in pixels from the top left of its level.
+    px: [f32; 2],
+    #[serde(rename = "__pivot")]
+    pivot: [f32; 2],
+    width: f32,
+    height: f32,
+    #[serde(rename = "fieldInstances")]
+    fields: Vec<FieldInstance>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FieldInstance {
+    #[serde(rename = "__identifier")]
+    identifier: String,
+    #[serde(rename = "__value")]
+    value: Value,
+}
+
+/// Reads every level of a project from the resources folder into one map, with tiles taken from `tileset`.
+pub fn load_map(ctx: &Context, tileset: &TilesetData, path: &str) -> GameResult<MapData> {
+    parse_map(&read(ctx, path)?, tileset, path)
+}
+
+fn parse_map(text: &str, tileset: &TilesetData, path: &str) -> GameResult<MapData> {
+    let project: Project =
+        serde_json::from_str(text).map_err(|error| invalid(path, &error.to_string()))?;
+    if project.external_levels {
+        return Err(invalid(
+            path,
+            "levels saved in separate files aren't supported",
+        ));
+    }
+
+    let mut worlds = project.worlds.into_iter();
+    let (layout, levels) = match (worlds.next(), worlds.next()) {
+        (None, _) => (project.world_layout, project.levels),
+        (Some(world), None) => (world.world_layout, world.levels),
+        (Some(_), Some(_)) => return Err(invalid(path, "projects can only have one world")),
+    };
+    if levels.is_empty() {
+        return Err(invalid(path, "there are no levels"));
+    }
+
+    // Where each level's top left corner is in the world, in LDtk's pixels.
+    let mut next = Vec2::ZERO;
+    let origins: Vec<Vec2> = levels
+        .iter()
+        .map(|level| match layout {
+            Some(WorldLayout::LinearHorizontal) => {
+                let origin = next;
+                next.x += level.px_wid;
+                origin
+            }
+            Some(WorldLayout::LinearVertical) => {
+                let origin = next;
+                next.y += level.px_hei;
+                origin
+            }
+            Some(WorldLayout::Free | WorldLayout::GridVania) | None => {
+                Vec2::new(level.world_x, level.world_y)
+            }
+        })
+        .collect();
+
+    let grid = project.default_grid_size;
+    let min = origins
+        .iter()
+        .copied()
+        .reduce(Vec2::min)
+        .unwrap_or_default();
+    let max = levels
+        .iter()
+        .zip(origins.iter())
+        .map(|(level, origin)| *origin + Vec2::new(level.px_wid, level.px_hei))
+        .reduce(Vec2::max)
+        .unwrap_or_default();
+    let size = ((max - min) / grid).ceil();
+    // Everything is placed in LDtk's pixels, which are scaled up to fit the level's tiles.
+    let scale = TILE_SIZE / grid;
+
+    let mut data = MapData::new(path, min * scale, size.x as usize, size.y as usize);
+    let mut protag_start = None;
+
+    for (level, origin) in levels.iter().zip(origins.iter().copied()) {
+        let name = &level.identifier;
+        let Some(layers) = &level.layer_instances else {
+            return Err(invalid(
+                path,
+                &format!("level `{name}` is saved in its own file, which isn't supported"),
+            ));
+        };
+        let cell = (origin - min) / grid;
+        if cell != cell.floor() {
+            return Err(invalid(
+                path,
+                &format!("level `{name}` isn't lined up with the {grid}px grid"),
+            ));
+        }
+
+        // Layers are listed from the top down, so the top layer's tiles are placed last and win.
+        for layer in layers.iter().rev() {
+            match layer.kind.as_str() {
+                "IntGrid" => {
+                    read_int_grid(&mut data, tileset, &project.defs, layer, grid, cell).map_err(
+                        |problem| {
+                            invalid(
+                                path,
+                                &format!("level `{name}`: layer `{}`: {problem}", layer.identifier),
+                            )
+                        },
+                    )?;
+                }
+                "Entities" => {
+                    for entity in layer.entity_instances.iter() {
+                        read_entity(&mut data, &mut protag_start, entity, origin, scale).map_err(
+                            |problem| {
+                                invalid(
+                                    path,
+                                    &format!(
+                                        "level `{name}`: entity `{}`: {problem}",
+                                        entity.identifier
+                                    ),
+                                )
+                            },
+                        )?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let neighbours = level
+            .neighbours
+            .iter()
+            .map(|neighbour| {
+                levels
+                    .iter()
+                    .find(|level| level.iid == neighbour.level_iid)
+                    .map(|level| level.identifier.clone())
+                    .ok_or_else(|| {
+                        invalid(
+                            path,
+                            &format!(
+                                "level `{name}` has a neighbour `{}` that isn't in the world",
+                                neighbour.level_iid
+                            ),
+                        )
+                    })
+            })
+            .collect::<GameResult<_>>()?;
+        data.rooms.push(Room::new(
+            name.clone(),
+            Rect::new(
+                origin.x * scale,
+                origin.y * scale,
+                level.px_wid * scale,
+                level.px_hei * scale,
+            ),
+            neighbours,
+        ));
+    }
+
+    data.protag_start =
+        protag_start.ok_or_else(|| invalid(path, "there's no ProtagStart entity"))?;
+    data.check().map_err(|problem| invalid(path, &problem))?;
+    Ok(data)
+}
+
+/// Places the tiles of an IntGrid layer whose level's top left corner is at `cell` in the map.
+fn read_int_grid(
+    data: &mut MapData,
+    tileset: &TilesetData,
+    defs: &Definitions,
+    layer: &LayerInstance,
+    grid: f32,
+    cell: Vec2,
+) -> Result<(), String> {
+    if layer.grid_size != grid {
+        return Err(format!(
+            "uses a {}px grid, but the project's is {grid}px",
+            layer.grid_size
+        ));
+    }
+    if layer.int_grid_csv.len() != layer.columns * layer.rows {
+        return Err(format!(
+            "has {} values, but is {}x{}",
+            layer.int_grid_csv.len(),
+            layer.columns,
+            layer.rows
+        ));
+    }
+    let definition = defs
+        .layers
+        .iter()
+        .find(|definition| definition.uid == layer.layer_def_uid)
+        .ok_or("its definition is missing")?;
+
+    for (index, value) in layer.int_grid_csv.iter().enumerate() {
+        if *value == 0 {
+            continue;
+        }
+        let identifier = definition
+            .int_grid_values
+            .iter()
+            .find(|definition| definition.value == *value)
+            .ok_or_else(|| format!("value {value} isn't defined"))?
+            .identifier
+            .as_deref()
+            .ok_or_else(|| format!("value {value} needs an identifier naming its tile kind"))?;
+        let kind = TileKind::from_name(identifier).ok_or_else(|| {
+            format!(
+                "value {value} is named `{identifier}`, which isn't a tile kind, expected floor, solid, pit, \
+                 shallow_water, deep_water, damaging_floor, or ledge_ followed by right, up, left or down"
+            )
+        })?;
+        let id = tileset
+            .tiles
+            .iter()
+            .position(|tile| tile.kind == kind)
+            .ok_or_else(|| {
+                format!(
+                    "value {value} is a {kind:?} tile, but there isn't one in `{}`",
+                    tileset.path
+                )
+            })?;
+
+        let column = cell.x as usize + index % layer.columns;
+        let row = cell.y as usize + index / layer.columns;
+        data.tiles[row * data.width + column] = Some(id);
+    }
+    Ok(())
+}
+
+fn read_entity(
+    data: &mut MapData,
+    protag_start: &mut Option<Vec2>,
+    entity: &Entity,
+    origin: Vec2,
+    scale: f32,
+) -> Result<(), String> {
+    let size = Vec2::new(entity.width, entity.height);
+    let corner = (origin + Vec2::from(entity.px) - Vec2::from(entity.pivot) * size) * scale;
+    let area = Rect::new(corner.x, corner.y, size.x * scale, size.y * scale);
+
+    let mut name = String::new();
+    let mut fields = Vec::new();
+    for field in entity.fields.iter() {
+        match (field.identifier.as_str(), &field.value) {
+            // Fields left empty fall back to whatever the object's default is.
+            (_, Value::Null) => {}
+            ("name", Value::String(value)) => name = value.clone(),
+            ("name", _) => return Err("`name` has to be a string".to_string()),
+            (identifier, value) => fields.push(Field {
+                name: identifier.to_string(),
+                value: value.clone(),
+            }),
+        }
+    }
+
+    data.add_object(protag_start, &entity.identifier, &name, area, &fields)
+        .map_err(|problem| match name.as_str() {
+            "" => problem,
+            name => format!("`{name}`: {problem}"),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tileset::TileData;
+    use super::*;
+    use crate::sprite::SpriteData;
+    use crate::Direction;
+    use ggez::GameError;
+
+    fn tileset() -> TilesetData {
+        TilesetData {
+            path: "/tiles/tileset.tsx".to_string(),
+            atlas: SpriteData::default(),
+            source_size: 16.0,
+            tiles: vec![
+                TileData::new(TileKind::Floor),
+                TileData::new(TileKind::Solid),
+                TileData::new(TileKind::Ledge(Direction::Down)),
+            ],
+        }
+    }
+
+    fn error(result: GameResult<impl std::fmt::Debug>) -> String {
+        match result.unwrap_err() {
+            GameError::ResourceLoadError(message) => message,
+            other => panic!("expected a ResourceLoadError, got {other:?}"),
+        }
+    }
+
+    /// Two 2x1 levels side by side, the first with the protag's start and the second with an enemy.
+    static PROJECT: &str = r#"{
+        "defaultGridSize": 16,
+        "worldLayout": "Free",
+        "defs": { "layers": [
+            { "uid": 1, "intGridValues": [
+                { "value": 1, "identifier": "solid" },
+                { "value": 2, "identifier": "ledge_down" }
+            ] },
+            { "uid": 2 }
+        ] },
+        "levels": [
+            {
+                "identifier": "West", "iid": "west", "worldX": 0, "worldY": 0, "pxWid": 32, "pxHei": 16,
+                "__neighbours": [{ "levelIid": "east", "dir": "e" }],
+                "layerInstances": [
+                    {
+                        "__identifier": "Entities", "__type": "Entities", "__cWid": 2, "__cHei": 1,
+                        "__gridSize": 16, "layerDefUid": 2,
+                        "entityInstances": [{
+                            "__identifier": "ProtagStart", "px": [8, 8], "__pivot": [0.5, 0.5],
+                            "width": 16, "height": 16, "fieldInstances": []
+                        }]
+                    },
+                    {
+                        "__identifier": "Terrain", "__type": "IntGrid", "__cWid": 2, "__cHei": 1,
+                        "__gridSize": 16, "layerDefUid": 1, "intGridCsv": [0, 1]
+                    }
+                ]
+            },
+            {
+                "identifier": "East", "iid": "east", "worldX": 32, "worldY": 0, "pxWid": 32, "pxHei": 16,
+                "__neighbours": [{ "levelIid": "west", "dir": "w" }],
+                "layerInstances": [
+                    {
+                        "__identifier": "Entities", "__type": "Entities", "__cWid": 2, "__cHei": 1,
+                        "__gridSize": 16, "layerDefUid": 2,
+                        "entityInstances": [{
+                            "__identifier": "BasicEnemy", "px": [0, 16], "__pivot": [0, 1],
+                            "width": 16, "height": 16,
+                            "fieldInstances": [
+                                { "__identifier": "speed", "__value": 2.5 },
+                                { "__identifier": "name", "__value": null }
+                            ]
+                        }]
+                    },
+                    {
+                        "__identifier": "Terrain", "__type": "IntGrid", "__cWid": 2, "__cHei": 1,
+                        "__gridSize": 16, "layerDefUid": 1, "intGridCsv": [2, 0]
+                    }
+                ]
+            }
+        ]
+    }"#;
+
+    #[test]
+    fn int_grid_values_pick_tiles_by_kind() {
+        let map = parse_map(PROJECT, &tileset(), "/maps/test.ldtk").unwrap();
+        assert_eq!(map.width, 4);
+        assert_eq!(map.tiles, vec![None, Some(1), Some(2), None]);
+    }
+
+    #[test]
+    fn entities_are_placed_by_their_pivot() {
+        let map = parse_map(PROJECT, &tileset(), "/maps/test.ldtk").unwrap();
+        let scale = TILE_SIZE / 16.0;
+        assert_eq!(map.protag_start, Vec2::splat(8.0 * scale));
+        // The enemy's pivot is its bottom left corner, in the level to the east.
+        assert_eq!(map.spawns[0].position, Vec2::new(40.0, 8.0) * scale);
+        assert_eq!(map.spawns[0].fields.len(), 1);
+        assert_eq!(map.spawns[0].fields[0].number(), Ok(2.5));
+    }
+
+    #[test]
+    fn every_level_is_a_room() {
+        let map = parse_map(PROJECT, &tileset(), "/maps/test.ldtk").unwrap();
+        let scale = TILE_SIZE / 16.0;
+        let rooms: Vec<(&str, Rect)> = map
+            .rooms
+            .iter()
+            .map(|room| (room.name.as_str(), room.area))
+            .collect();
+        assert_eq!(
+            rooms,
+            vec![
+                ("West", Rect::new(0.0, 0.0, 32.0 * scale, 16.0 * scale)),
+                (
+                    "East",
+                    Rect::new(32.0 * scale, 0.0, 32.0 * scale, 16.0 * scale)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn levels_lead_into_their_neighbours() {
+        let map = parse_map(PROJECT, &tileset(), "/maps/test.ldtk").unwrap();
+        assert_eq!(map.rooms[0].neighbours, vec!["East".to_string()]);
+        assert_eq!(map.rooms[1].neighbours, vec!["West".to_string()]);
+
+        let project = PROJECT.replace(r#""levelIid": "west""#, r#""levelIid": "north""#);
+        assert_eq!(
+            error(parse_map(&project, &tileset(), "/maps/test.ldtk")),
+            "/maps/test.ldtk: level `East` has a neighbour `north` that isn't in the world"
+        );
+    }
+
+    #[test]
+    fn unknown_fields_are_errors() {
+        let project = PROJECT.replace(
+            r#""__identifier": "BasicEnemy""#,
+            r#""__identifier": "Rupee""#,
+        );
+        let project = project.replace(r#""__identifier": "speed""#, r#""__identifier": "amuont""#);
+        assert_eq!(
+            error(parse_map(&project, &tileset(), "/maps/test.ldtk")),
+            "/maps/test.ldtk: level `East`: entity `Rupee`: PickupKind has no field `amuont`"
+        );
+    }
+
+    #[test]
+    fn int_grid_values_need_a_tile_kind() {
+        let project = PROJECT.replace(r#""identifier": "ledge_down""#, r#""identifier": "lava""#);
+        let message = error(parse_map(&project, &tileset(), "/maps/test.ldtk"));
+        assert!(
+            message.starts_with(
+                "/maps/test.ldtk: level `East`: layer `Terrain`: value 2 is named `lava`, which isn't a tile kind"
+            ),
+            "{message}"
+        );
+    }
+}
@@ -0,0 +1,506 @@
+use super::{ldtk, tiled, StaticAssets};
+use crate::npc::EnemyContainer;
+use crate::pickup::{Pickup, PickupKind};
+use crate::protag::items::ItemType;
+use crate::room::Room;
+use crate::terrain::Wall;
+use crate::trigger::{Door, Trigger};
+use crate::Direction;
+use bevy_reflect::{PartialReflect, ReflectPath};
+use ggez::graphics::Rect;
+use ggez::{Context, GameError, GameResult};
+use glam::Vec2;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::Read;
+
+static CLASSES: &str = "ProtagStart, Entrance, Wall, Trigger, Door, Room";
+
+/// Everything a [`Level`](crate::level::Level) is built from when the protag enters a map,
+/// see [`Level::enter_map`](crate::level::Level::enter_map).
+///
+/// Positions are in world space, already scaled to the level's [`TILE_SIZE`](crate::tilemap::TILE_SIZE).
+///
+/// Objects in a map, like Tiled objects or LDtk entities, are read by their class:
+///
+/// - `ProtagStart`: where the protag starts, and respawns. Every map needs exactly one.
+/// - `Entrance`: a named point [doors](Door) can lead to.
+/// - `Wall`: a rect of [`Wall`], which can be blown open with a bomb if its `bombable` field is true.
+/// - `Trigger`: a named [`Trigger`] area.
+/// - `Door`: a trigger that leads to the `Entrance` named by its `entrance` field, in the map at its `map` field
+///   (relative to the map file), or the same map if that's left out.
+/// - `Room`: a named screen of the map, see [`Room`]. Its `neighbours` field lists the names of the rooms it leads into,
+///   separated by commas. Tiled maps without any are one big room, and every level of an LDtk world is already a room.
+/// - The type name of any enemy that [`EnemyContainer::spawn`] knows of, like `BasicEnemy`, spawns that enemy.
+/// - The name of any [`PickupKind`], like `Rupee`, places that pickup. `Item` pickups need an `item` field, like `bow`.
+///
+/// The fields of enemies and pickups are set on them by name through reflection, see [`Field::apply`].
+#[derive(Debug, Clone)]
+pub struct MapData {
+    /// Where the map was loaded from in the resources folder.
+    pub path: String,
+    /// Where the top left corner of the tiles is.
+    pub position: Vec2,
+    /// How many tiles wide the map is.
+    pub width: usize,
+    /// Ids into the [`TilesetData`](super::tileset::TilesetData), one row after another. `None` where there's no tile.
+    pub tiles: Vec<Option<usize>>,
+    pub protag_start: Vec2,
+    /// Named points that doors can lead to.
+    pub entrances: HashMap<String, Vec2>,
+    pub spawns: Vec<SpawnData>,
+    pub pickups: Vec<Pickup>,
+    pub walls: Vec<Wall>,
+    pub triggers: Vec<Trigger>,
+    pub rooms: Vec<Room>,
+}
+
+/// An enemy placed in a map, by its type name. See [`EnemyContainer::spawn`].
+#[derive(Debug, Clone)]
+pub struct SpawnData {
+    pub enemy: String,
+    pub position: Vec2,
+    /// Set on the enemy once it's spawned. They're checked against the enemy when the map loads.
+    pub fields: Vec<Field>,
+}
+
+/// A custom value on an object in a map, like a Tiled property or an LDtk field.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Field {
+    pub name: String,
+    #[serde(default)]
+    pub value: Value,
+}
+
+impl MapData {
+    /// Loads a map from the resources folder, picking the format from the file extension.
+    pub fn load(ctx: &Context, assets: &StaticAssets, path: &str) -> GameResult<Self> {
+        let map = match extension(path) {
+            "tmj" | "tmx" => tiled::load_map(ctx, &assets.tileset, path),
+            "ldtk" => ldtk::load_map(ctx, &assets.tileset, path),
+            _ => Err(invalid(
+                path,
+                "not a map file, expected .tmj, .tmx or .ldtk",
+            )),
+        }?;
+        map.check_spawns(assets)?;
+        Ok(map)
+    }
+
+    /// Makes sure everything that leads somewhere else in the map by name, like doors and the neighbours of rooms,
+    /// can find it, once every object has been added.
+    pub(super) fn check(&self) -> Result<(), String> {
+        for trigger in self.triggers.iter() {
+            let Some(door) = trigger.door.as_ref().filter(|door| door.map.is_none()) else {
+                continue;
+            };
+            if !self.entrances.contains_key(&door.entrance) {
+                return Err(format!(
+                    "door `{}` leads to `{}`, but there's no entrance with that name",
+                    trigger.name, door.entrance
+                ));
+            }
+        }
+        for room in self.rooms.iter() {
+            for neighbour in room.neighbours.iter() {
+                if !self.rooms.iter().any(|other| other.name == *neighbour) {
+                    return Err(format!(
+                        "room `{}` leads into `{neighbour}`, but there's no room with that name",
+                        room.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Sets the fields of every spawn on a fresh enemy of its type, so that mistakes in them show up
+    /// when the map loads rather than whenever the protag walks into their room.
+    fn check_spawns(&self, assets: &StaticAssets) -> GameResult {
+        for spawn in self.spawns.iter() {
+            let Some(mut enemy) = EnemyContainer::new_enemy(assets, &spawn.enemy) else {
+                return Err(invalid(
+                    &self.path,
+                    &format!("unknown enemy type `{}`", spawn.enemy),
+                ));
+            };
+            for field in spawn.fields.iter() {
+                field
+                    .apply(enemy.as_partial_reflect_mut())
+                    .map_err(|problem| {
+                        invalid(&self.path, &format!("{}: {problem}", spawn.enemy))
+                    })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// An empty map, for loaders to add objects to.
+    pub(super) fn new(path: &str, position: Vec2, width: usize, height: usize) -> Self {
+        Self {
+            path: path.to_string(),
+            position,
+            width,
+            tiles: vec![None; width * height],
+            protag_start: Vec2::ZERO,
+            entrances: HashMap::new(),
+            spawns: Vec::new(),
+            pickups: Vec::new(),
+            walls: Vec::new(),
+            triggers: Vec::new(),
+            rooms: Vec::new(),
+        }
+    }
+
+    /// Reads an object by its class. `protag_start` is filled in by the map's `ProtagStart`, which there can only be one of.
+    pub(super) fn add_object(
+        &mut self,
+        protag_start: &mut Option<Vec2>,
+        class: &str,
+        name: &str,
+        area: Rect,
+        fields: &[Field],
+    ) -> Result<(), String> {
+        let center = Vec2::new(area.x + area.w / 2.0, area.y + area.h / 2.0);
+        let field = |name: &str| fields.iter().find(|field| field.name == name);
+
+        match class {
+            "ProtagStart" => {
+                only_fields(class, fields, &[])?;
+                if protag_start.replace(center).is_some() {
+                    return Err("there can only be one ProtagStart".to_string());
+                }
+            }
+            "Entrance" => {
+                only_fields(class, fields, &[])?;
+                if name.is_empty() {
+                    return Err("entrances need a name for doors to lead to".to_string());
+                }
+                if self.entrances.insert(name.to_string(), center).is_some() {
+                    return Err("there's already an entrance with this name".to_string());
+                }
+            }
+            "Wall" => {
+                only_fields(class, fields, &["bombable"])?;
+                let bombable = match field("bombable") {
+                    Some(field) => field.boolean()?,
+                    None => false,
+                };
+                self.walls.push(if bombable {
+                    Wall::cracked(area)
+                } else {
+                    Wall::from_rect(area)
+                });
+            }
+            "Trigger" => {
+                only_fields(class, fields, &[])?;
+                self.triggers
+                    .push(Trigger::new(name.to_string(), area, None));
+            }
+            "Room" => {
+                only_fields(class, fields, &["neighbours"])?;
+                if name.is_empty() {
+                    return Err("rooms need a name for their neighbours to lead to".to_string());
+                }
+                let neighbours = match field("neighbours") {
+                    Some(field) => field
+                        .string()?
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|neighbour| !neighbour.is_empty())
+                        .map(str::to_string)
+                        .collect(),
+                    None => Vec::new(),
+                };
+                self.rooms
+                    .push(Room::new(name.to_string(), area, neighbours));
+            }
+            "Door" => {
+                only_fields(class, fields, &["map", "entrance"])?;
+                let entrance = field("entrance")
+                    .ok_or("doors need an `entrance` field")?
+                    .string()?
+                    .to_string();
+                let map = match field("map") {
+                    Some(field) => Some(resolve(&self.path, field.string()?)),
+                    None => None,
+                };
+                self.triggers.push(Trigger::new(
+                    name.to_string(),
+                    area,
+                    Some(Door { map, entrance }),
+                ));
+            }
+            "" => {
+                return Err(format!(
+                    "objects need a class, one of {CLASSES}, an enemy type, or a pickup"
+                ))
+            }
+            class if EnemyContainer::enemy_types().any(|enemy| enemy == class) => {
+                self.spawns.push(SpawnData {
+                    enemy: class.to_string(),
+                    position: center,
+                    fields: fields.to_vec(),
+                });
+            }
+            class => {
+                let Some(mut kind) = PickupKind::from_name(class) else {
+                    return Err(format!(
+                        "unknown class `{class}`, expected one of {CLASSES}, an enemy type: {}, or a pickup: {}",
+                        EnemyContainer::enemy_types().collect::<Vec<_>>().join(", "),
+                        PickupKind::names().collect::<Vec<_>>().join(", "),
+                    ));
+                };
+                for field in fields {
+                    field.apply(&mut kind)?;
+                }
+                if kind
+                    == (PickupKind::Item {
+                        item: ItemType::None,
+                    })
+                {
+                    return Err("items need an `item` field, like bow".to_string());
+                }
+                self.pickups.push(Pickup::new(kind, center));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Field {
+    pub fn string(&self) -> Result<&str, String> {
+        self.value
+            .as_str()
+            .ok_or_else(|| format!("`{}` has to be a string", self.name))
+    }
+
+    pub fn number(&self) -> Result<f32, String> {
+        self.value
+            .as_f64()
+            .map(|number| number as f32)
+            .ok_or_else(|| format!("`{}` has to be a number", self.name))
+    }
+
+    pub fn boolean(&self) -> Result<bool, String> {
+        self.value
+            .as_bool()
+            .ok_or_else(|| format!("`{}` has to be a bool", self.name))
+    }
+
+    pub fn direction(&self) -> Result<Direction, String> {
+        Direction::deserialize(&self.value)
+            .map_err(|_| format!("`{}` has to be one of right, up, left or down", self.name))
+    }
+
+    /// Sets the field of the same name on a reflected struct, or on the current variant of a reflected enum.
+    /// Nested fields can be reached with a path, like `stats.health`.
+    ///
+    /// Numbers, bools, strings, directions and item types can be set.
+    pub fn apply(&self, target: &mut dyn PartialReflect) -> Result<(), String> {
+        let type_path = target.reflect_short_type_path().to_string();
+        let field = self
+            .name
+            .as_str()
+            .reflect_element_mut(target)
+            .map_err(|_| format!("{type_path} has no field `{}`", self.name))?;
+
+        let field_type = field.reflect_type_path().to_string();
+        let invalid = || {
+            format!(
+                "`{}` can't be set to {}, since it's a {field_type}",
+                self.name, self.value
+            )
+        };
+        let value = &self.value;
+        let applied: Option<Box<dyn PartialReflect>> = if field.represents::<f32>() {
+            value.as_f64().map(|number| Box::new(number as f32) as _)
+        } else if field.represents::<i32>() {
+            value
+                .as_i64()
+                .and_then(|number| i32::try_from(number).ok())
+                .map(|number| Box::new(number) as _)
+        } else if field.represents::<u32>() {
+            value
+                .as_u64()
+                .and_then(|number| u32::try_from(number).ok())
+                .map(|number| Box::new(number) as _)
+        } else if field.represents::<usize>() {
+            value
+                .as_u64()
+                .and_then(|number| usize::try_from(number).ok())
+                .map(|number| Box::new(number) as _)
+        } else if field.represents::<bool>() {
+            value.as_bool().map(|boolean| Box::new(boolean) as _)
+        } else if field.represents::<String>() {
+            value
+                .as_str()
+                .map(|string| Box::new(string.to_string()) as _)
+        } else if field.represents::<Direction>() {
+            self.direction()
+                .ok()
+                .map(|direction| Box::new(direction) as _)
+        } else if field.represents::<ItemType>() {
+            ItemType::deserialize(value)
+                .ok()
+                .map(|item| Box::new(item) as _)
+        } else {
+            None
+        };
+
+        let applied = applied.ok_or_else(invalid)?;
+        field.try_apply(applied.as_ref()).map_err(|_| invalid())
+    }
+}
+
+fn only_fields(class: &str, fields: &[Field], allowed: &[&str]) -> Result<(), String> {
+    match fields
+        .iter()
+        .find(|field| !allowed.contains(&field.name.as_str()))
+    {
+        None => Ok(()),
+        Some(field) if allowed.is_empty() => Err(format!(
+            "unknown field `{}`, {class} objects don't have any",
+            field.name
+        )),
+        Some(field) => Err(format!(
+            "unknown field `{}`, expected {}",
+            field.name,
+            allowed.join(" or ")
+        )),
+    }
+}
+
+pub(super) fn read(ctx: &Context, path: &str) -> GameResult<String> {
+    let mut text = String::new();
+    ctx.fs.open(path)?.read_to_string(&mut text)?;
+    Ok(text)
+}
+
+pub(super) fn extension(path: &str) -> &str {
+    path.rsplit_once('.').map_or("", |(_, extension)| extension)
+}
+
+/// Turns a path relative to the file at `from` into a path in the resources folder.
+pub(super) fn resolve(from: &str, relative: &str) -> String {
+    let mut parts: Vec<&str> = if relative.starts_with('/') {
+        Vec::new()
+    } else {
+        from.split('/').filter(|part| !part.is_empty()).collect()
+    };
+    if !relative.starts_with('/') {
+        parts.pop();
+    }
+    for part in relative.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            part => parts.push(part),
+        }
+    }
+    format!("/{}", parts.join("/"))
+}
+
+pub(super) fn invalid(path: &str, problem: &str) -> GameError {
+    GameError::ResourceLoadError(format!("{path}: {problem}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_reflect::Reflect;
+    use serde_json::json;
+
+    #[derive(Debug, Reflect)]
+    struct Target {
+        speed: f32,
+        health: i32,
+        amount: u32,
+        name: String,
+        flying: bool,
+        facing: Direction,
+        stats: Stats,
+    }
+
+    #[derive(Debug, Reflect)]
+    struct Stats {
+        damage: u32,
+    }
+
+    fn target() -> Target {
+        Target {
+            speed: 1.0,
+            health: 10,
+            amount: 1,
+            name: String::new(),
+            flying: false,
+            facing: Direction::Down,
+            stats: Stats { damage: 1 },
+        }
+    }
+
+    fn field(name: &str, value: Value) -> Field {
+        Field {
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn applies_fields_by_type() {
+        let mut target = target();
+        for (name, value) in [
+            ("speed", json!(2.5)),
+            ("health", json!(-3)),
+            ("amount", json!(7)),
+            ("name", json!("slime")),
+            ("flying", json!(true)),
+            ("facing", json!("left")),
+            ("stats.damage", json!(4)),
+        ] {
+            field(name, value).apply(&mut target).unwrap();
+        }
+        assert_eq!(target.speed, 2.5);
+        assert_eq!(target.health, -3);
+        assert_eq!(target.amount, 7);
+        assert_eq!(target.name, "slime");
+        assert!(target.flying);
+        assert_eq!(target.facing, Direction::Left);
+        assert_eq!(target.stats.damage, 4);
+    }
+
+    #[test]
+    fn applies_fields_to_enum_variants() {
+        let mut kind = PickupKind::from_name("Arrows").unwrap();
+        field("amount", json!(5)).apply(&mut kind).unwrap();
+        assert_eq!(kind, PickupKind::Arrows { amount: 5 });
+    }
+
+    #[test]
+    fn mismatched_fields_are_errors() {
+        let mut target = target();
+        assert_eq!(
+            field("sped", json!(2.5)).apply(&mut target),
+            Err("Target has no field `sped`".to_string())
+        );
+        // Whole number fields don't take fractions, and unsigned ones don't take negatives.
+        assert_eq!(
+            field("health", json!(1.5)).apply(&mut target),
+            Err("`health` can't be set to 1.5, since it's a i32".to_string())
+        );
+        assert_eq!(
+            field("amount", json!(-1)).apply(&mut target),
+            Err("`amount` can't be set to -1, since it's a u32".to_string())
+        );
+        assert_eq!(
+            field("facing", json!("sideways")).apply(&mut target),
+            Err(
+                "`facing` can't be set to \"sideways\", since it's a linklike::Direction"
+                    .to_string()
+            )
+        );
+    }
+}
@@ -1,8 +1,16 @@
+use ggez::{Context, GameResult};
 use player::ProtagData;
+use tileset::TilesetData;
 
 use crate::npc::advanced_enemy::AdvancedEnemyData;
+use crate::npc::basic_enemy::BasicEnemyData;
 
+pub mod hitboxes;
+pub mod ldtk;
+pub mod map;
 pub mod player;
+pub mod tiled;
+pub mod tileset;
 
 /// Storage container for runtime generated data that lasts for the entirety of the program.
 ///
@@ -20,14 +28,20 @@ pub mod player;
 #[derive(Debug)]
 pub struct StaticAssets {
     pub protag: ProtagData,
+    pub basic_enemy: BasicEnemyData,
     pub advanced_enemy: AdvancedEnemyData,
+    pub tileset: TilesetData,
 }
 
 impl StaticAssets {
-    pub(crate) fn new() -> Self {
-        Self {
-            protag: ProtagData::new(),
-            advanced_enemy: AdvancedEnemyData::new(),
-        }
+    /// Hitbox animations are loaded from `resources/hitboxes`, see [`HitboxAnimationData`](hitboxes::HitboxAnimationData),
+    /// and the tileset from `resources/tiles`.
+    pub(crate) fn new(ctx: &Context) -> GameResult<Self> {
+        Ok(Self {
+            protag: ProtagData::new(ctx)?,
+            basic_enemy: BasicEnemyData::default(),
+            advanced_enemy: AdvancedEnemyData::new(ctx)?,
+            tileset: tiled::load_tileset(ctx, "/tiles/tileset.tsx")?,
+        })
     }
 }
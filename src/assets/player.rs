@@ -1,5 +1,13 @@
-use crate::collision::{Hitbox, HitboxFrameRef, HitboxFrameString, HitboxFrameStringRef};
+use super::hitboxes::HitboxAnimationData;
+use crate::attack::{Attack, Element};
+use crate::collision::{
+    CollisionFilter, Hitbox, HitboxAnimation, HitboxFrameRef, HitboxFrameString,
+    HitboxFrameStringRef,
+};
+use crate::sprite::SpriteData;
 use crate::Direction;
+use ggez::graphics::DrawParam;
+use ggez::{Context, GameResult};
 use glam::Vec2;
 use std::borrow::Borrow;
 use std::convert::AsRef;
@@ -7,43 +15,151 @@ use std::convert::AsRef;
 #[derive(Debug, Clone)]
 pub struct ProtagData {
     pub inventory: InventoryData,
-    pub start_pos: glam::Vec2,
+    pub max_health: i32,
+    /// How many frames the protag is invulnerable for after taking a hit.
+    pub iframes: usize,
+    /// Resistance to knockback, see [`Knockback`](crate::knockback::Knockback).
+    pub mass: f32,
 }
 
 #[derive(Debug, Clone)]
 pub struct InventoryData {
     pub sword: SwordData,
+    pub boomerang: BoomerangData,
+    pub bow: BowData,
+    pub bomb: BombData,
 }
 
 #[derive(Debug, Clone)]
 pub struct SwordData {
     pub swing: [HitboxFrameString; 4],
+    /// Shown in the item select subscreen and the HUD.
+    pub icon: SpriteData,
+}
+
+#[derive(Debug, Clone)]
+pub struct BoomerangData {
+    /// Distance travelled per frame, both on the way out and on the way back.
+    pub speed: f32,
+    /// How many frames the boomerang flies outwards before turning back.
+    pub range: usize,
+    /// How many frames the protag stands still for while throwing.
+    pub throw_frames: usize,
+    /// How many frames enemies are stunned for when hit.
+    pub stun: usize,
+    pub weight: f32,
+    pub size: f32,
+    /// Shown in the item select subscreen and the HUD.
+    pub icon: SpriteData,
+}
+
+#[derive(Debug, Clone)]
+pub struct BowData {
+    /// How many frames the fire key has to be held before the arrow can be let loose.
+    pub draw_frames: usize,
+    pub arrow_speed: f32,
+    /// How many frames an arrow flies for before disappearing.
+    pub arrow_range: usize,
+    /// How many frames an arrow stays stuck in a wall before disappearing.
+    pub stuck_frames: usize,
+    pub damage: f32,
+    pub weight: f32,
+    pub start_arrows: u32,
+    pub max_arrows: u32,
+    /// Shown in the item select subscreen and the HUD.
+    pub icon: SpriteData,
+}
+
+#[derive(Debug, Clone)]
+pub struct BombData {
+    /// How many frames after being placed the bomb explodes.
+    pub fuse_frames: usize,
+    /// The explosion, which grows outwards from the center of the bomb. Each frame carries the blast's [`Attack`].
+    pub blast: HitboxAnimation,
+    pub throw_speed: f32,
+    /// How many frames a thrown bomb flies for before landing.
+    pub throw_frames: usize,
+    pub size: f32,
+    pub start_bombs: u32,
+    pub max_bombs: u32,
+    /// Shown in the item select subscreen and the HUD.
+    pub icon: SpriteData,
 }
 
 impl ProtagData {
-    pub fn new() -> ProtagData {
-        static SWING_HITBOXES: HitboxFrameStringRef = HitboxFrameStringRef::new(&[
-            HitboxFrameRef::right(&[Hitbox::point_size(Vec2::new(00.0, 80.0), 40.0)]),
-            HitboxFrameRef::right(&[Hitbox::point_size(Vec2::new(45.0, 60.0), 40.0)]),
-            HitboxFrameRef::right(&[Hitbox::point_size(Vec2::new(65.0, 40.0), 40.0)]),
-            HitboxFrameRef::right(&[Hitbox::point_size(Vec2::new(80.0, 20.0), 40.0)]),
-            HitboxFrameRef::right(&[Hitbox::point_size(Vec2::new(80.0, 00.0), 40.0)]),
-            HitboxFrameRef::right(&[Hitbox::point_size(Vec2::new(80.0, -20.0), 40.0)]),
-            HitboxFrameRef::right(&[Hitbox::point_size(Vec2::new(0.0, 0.0), 40.0)]),
+    pub fn new(ctx: &Context) -> GameResult<ProtagData> {
+        static BLAST: Attack = Attack::new(16.0, 6.0).with_element(Element::Fire);
+        static BLAST_HITBOXES: HitboxFrameStringRef = HitboxFrameStringRef::new(&[
+            HitboxFrameRef::down(&[Hitbox::circle(Vec2::ZERO, 10.0)]).with_attack(BLAST),
+            HitboxFrameRef::down(&[Hitbox::circle(Vec2::ZERO, 60.0)]).with_attack(BLAST),
+            HitboxFrameRef::down(&[Hitbox::circle(Vec2::ZERO, 90.0)]).with_attack(BLAST),
+            HitboxFrameRef::down(&[Hitbox::circle(Vec2::ZERO, 100.0)]).with_attack(BLAST),
+            HitboxFrameRef::down(&[Hitbox::circle(Vec2::ZERO, 0.0)]).with_attack(BLAST),
         ]);
 
-        let swing = [
-            SWING_HITBOXES.to_direction(Direction::Right),
-            SWING_HITBOXES.to_direction(Direction::Up),
-            SWING_HITBOXES.to_direction(Direction::Left),
-            SWING_HITBOXES.to_direction(Direction::Down),
+        let mut blast = HitboxAnimation::new(BLAST_HITBOXES, vec![2, 4, 6, 8]);
+        blast.loop_animations(false);
+        blast.set_filter(CollisionFilter::ATTACK);
+
+        let swing_file = HitboxAnimationData::load_frame_string(ctx, "/hitboxes/sword_swing.json")?;
+        let swing_hitboxes = swing_file.borrow();
+        let mut swing = [
+            swing_hitboxes.borrow().to_direction(Direction::Right),
+            swing_hitboxes.borrow().to_direction(Direction::Up),
+            swing_hitboxes.borrow().to_direction(Direction::Left),
+            swing_hitboxes.borrow().to_direction(Direction::Down),
         ];
+        for string in swing.iter_mut() {
+            string.set_filter(CollisionFilter::ATTACK);
+        }
+
+        // Item icons are drawn at 16x16, so they're scaled up to fill their slot.
+        let icon = |path: &str| SpriteData {
+            texture_path: path.to_string(),
+            draw_param: DrawParam::new().scale(Vec2::splat(3.0)),
+            ..Default::default()
+        };
 
-        ProtagData {
+        Ok(ProtagData {
             inventory: InventoryData {
-                sword: SwordData { swing },
+                sword: SwordData {
+                    swing,
+                    icon: icon("/items/sword.png"),
+                },
+                boomerang: BoomerangData {
+                    speed: 12.0,
+                    range: 30,
+                    throw_frames: 8,
+                    stun: 120,
+                    weight: 0.5,
+                    size: 24.0,
+                    icon: icon("/items/boomerang.png"),
+                },
+                bow: BowData {
+                    draw_frames: 20,
+                    arrow_speed: 16.0,
+                    arrow_range: 60,
+                    stuck_frames: 45,
+                    damage: 8.0,
+                    weight: 1.5,
+                    start_arrows: 10,
+                    max_arrows: 30,
+                    icon: icon("/items/bow.png"),
+                },
+                bomb: BombData {
+                    fuse_frames: 150,
+                    blast,
+                    throw_speed: 10.0,
+                    throw_frames: 15,
+                    size: 30.0,
+                    start_bombs: 5,
+                    max_bombs: 10,
+                    icon: icon("/items/bomb.png"),
+                },
             },
-            start_pos: Vec2::ONE * 500.0,
-        }
+            max_health: 40,
+            iframes: 60,
+            mass: 2.0,
+        })
     }
 }
@@ -0,0 +1,792 @@
+//! Loads maps and tilesets made in [Tiled](https://www.mapeditor.org/), saved as either JSON (`.tmj`, `.tsj`) or XML (`.tmx`, `.tsx`).
+//!
+//! Tile layers are stacked into the level's [`Tilemap`](crate::tilemap::Tilemap), and objects are read by their class,
+//! with their properties as fields, see [`MapData`]. Maps without any `Room` objects are one room.
+//!
+//! Tilesets have to be saved as their own file, and tile layers have to be saved as CSV rather than compressed.
+
+use super::map::{extension, invalid, read, resolve, Field, MapData};
+use super::tileset::{TileData, TilesetData};
+use crate::room::Room;
+use crate::sprite::SpriteData;
+use crate::tilemap::{TileKind, TILE_SIZE};
+use ggez::graphics::Rect;
+use ggez::{Context, GameResult};
+use glam::Vec2;
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// The top bits of a tile's gid mark whether it's flipped, which tiles aren't drawn with.
+static FLIP_FLAGS: u32 = 0xF000_0000;
+
+#[derive(Debug, Deserialize)]
+struct Map {
+    #[serde(default)]
+    orientation: String,
+    #[serde(default)]
+    infinite: bool,
+    width: usize,
+    height: usize,
+    tilewidth: f32,
+    tileheight: f32,
+    #[serde(default)]
+    tilesets: Vec<TilesetRef>,
+    #[serde(default)]
+    layers: Vec<Layer>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TilesetRef {
+    firstgid: u32,
+    /// Only `None` for tilesets embedded into the map.
+    source: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Layer {
+    #[serde(rename = "tilelayer")]
+    Tiles {
+        name: String,
+        #[serde(default)]
+        encoding: Option<String>,
+        data: LayerData,
+    },
+    #[serde(rename = "objectgroup")]
+    Objects {
+        objects: Vec<Object>,
+    },
+    Group {
+        layers: Vec<Layer>,
+    },
+    #[serde(rename = "imagelayer")]
+    Image,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum LayerData {
+    Gids(Vec<u32>),
+    /// Base64, which may also be compressed.
+    Encoded(IgnoredAny),
+}
+
+#[derive(Debug, Deserialize)]
+struct Object {
+    id: u32,
+    #[serde(default)]
+    name: String,
+    /// Saved as `type` in JSON, and as `class` in XML since Tiled 1.9.
+    #[serde(default, rename = "type", alias = "class")]
+    class: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+    #[serde(default)]
+    properties: Vec<Field>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tileset {
+    tilewidth: f32,
+    tileheight: f32,
+    tilecount: usize,
+    image: Option<String>,
+    #[serde(default)]
+    tiles: Vec<Tile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Tile {
+    id: usize,
+    #[serde(default)]
+    properties: Vec<Field>,
+}
+
+/// Reads a map from the resources folder, with tiles taken from `tileset`, which has to be the only tileset the map uses.
+pub fn load_map(ctx: &Context, tileset: &TilesetData, path: &str) -> GameResult<MapData> {
+    parse_map(&read(ctx, path)?, tileset, path)
+}
+
+fn parse_map(text: &str, tileset: &TilesetData, path: &str) -> GameResult<MapData> {
+    let map: Map = match extension(path) {
+        "tmj" | "json" => serde_json::from_str(text).map_err(|error| error.to_string()),
+        "tmx" => xml::map(text),
+        other => Err(format!(
+            "`.{other}` isn't a Tiled map, expected .tmj or .tmx"
+        )),
+    }
+    .map_err(|problem| invalid(path, &problem))?;
+
+    if !(map.orientation.is_empty() || map.orientation == "orthogonal") {
+        return Err(invalid(path, "only orthogonal maps are supported"));
+    }
+    if map.infinite {
+        return Err(invalid(path, "infinite maps aren't supported"));
+    }
+    if map.tilewidth != map.tileheight {
+        return Err(invalid(path, "tiles have to be square"));
+    }
+    // Objects are placed in the map's pixels, which are scaled up to fit the level's tiles.
+    let scale = TILE_SIZE / map.tilewidth;
+
+    let firstgid = match map.tilesets.as_slice() {
+        [] => 1,
+        [TilesetRef {
+            source: None,
+            firstgid: _,
+        }] => {
+            return Err(invalid(
+                path,
+                "embedded tilesets aren't supported, export the tileset to its own file",
+            ))
+        }
+        [TilesetRef {
+            source: Some(source),
+            firstgid,
+        }] => {
+            let source = resolve(path, source);
+            if source != tileset.path {
+                return Err(invalid(
+                    path,
+                    &format!(
+                        "uses the tileset `{source}`, but only `{}` is loaded",
+                        tileset.path
+                    ),
+                ));
+            }
+            *firstgid
+        }
+        _ => return Err(invalid(path, "maps can only use one tileset")),
+    };
+
+    let mut data = MapData::new(path, Vec2::ZERO, map.width, map.height);
+
+    let mut protag_start = None;
+    let mut layers: Vec<&Layer> = map.layers.iter().collect();
+    layers.reverse();
+    while let Some(layer) = layers.pop() {
+        match layer {
+            Layer::Tiles {
+                name,
+                encoding,
+                data: layer_data,
+            } => {
+                let LayerData::Gids(gids) = layer_data else {
+                    return Err(invalid(
+                        path,
+                        &format!(
+                            "layer `{name}` is saved as {}, but only CSV is supported",
+                            encoding.as_deref().unwrap_or("compressed data")
+                        ),
+                    ));
+                };
+                if gids.len() != data.tiles.len() {
+                    return Err(invalid(
+                        path,
+                        &format!(
+                            "layer `{name}` has {} tiles, but the map is {}x{}",
+                            gids.len(),
+                            map.width,
+                            map.height
+                        ),
+                    ));
+                }
+                // Later layers are drawn on top, so they replace any tiles below them.
+                for (tile, gid) in data.tiles.iter_mut().zip(gids) {
+                    let gid = gid & !FLIP_FLAGS;
+                    if gid == 0 {
+                        continue;
+                    }
+                    let id = gid.checked_sub(firstgid).map(|id| id as usize);
+                    match id.filter(|id| *id < tileset.tiles.len()) {
+                        Some(id) => *tile = Some(id),
+                        None => {
+                            return Err(invalid(
+                                path,
+                                &format!(
+                                    "layer `{name}` has tile {gid}, which isn't in the tileset"
+                                ),
+                            ))
+                        }
+                    }
+                }
+            }
+            Layer::Objects { objects } => {
+                for object in objects {
+                    let area = Rect::new(
+                        object.x * scale,
+                        object.y * scale,
+                        object.width * scale,
+                        object.height * scale,
+                    );
+                    data.add_object(
+                        &mut protag_start,
+                        &object.class,
+                        &object.name,
+                        area,
+                        &object.properties,
+                    )
+                    .map_err(|problem| match object.name.as_str() {
+                        "" => invalid(path, &format!("object {}: {problem}", object.id)),
+                        name => invalid(path, &format!("object {} `{name}`: {problem}", object.id)),
+                    })?;
+                }
+            }
+            // Layers in groups are read in the same order as if they weren't grouped.
+            Layer::Group { layers: group } => layers.extend(group.iter().rev()),
+            Layer::Image => {}
+        }
+    }
+
+    data.protag_start =
+        protag_start.ok_or_else(|| invalid(path, "there's no ProtagStart object"))?;
+    if data.rooms.is_empty() {
+        data.rooms.push(Room::new(
+            path.to_string(),
+            Rect::new(
+                0.0,
+                0.0,
+                map.width as f32 * TILE_SIZE,
+                map.height as f32 * TILE_SIZE,
+            ),
+            Vec::new(),
+        ));
+    }
+    data.check().map_err(|problem| invalid(path, &problem))?;
+    Ok(data)
+}
+
+/// Reads a tileset from the resources folder. Each tile's `kind`, `facing`, `damage` and `speed` properties
+/// are read into its [`TileData`], and tiles without properties are plain floor.
+pub fn load_tileset(ctx: &Context, path: &str) -> GameResult<TilesetData> {
+    parse_tileset(&read(ctx, path)?, path)
+}
+
+fn parse_tileset(text: &str, path: &str) -> GameResult<TilesetData> {
+    let tileset: Tileset = match extension(path) {
+        "tsj" | "json" => serde_json::from_str(text).map_err(|error| error.to_string()),
+        "tsx" => xml::tileset(text),
+        other => Err(format!(
+            "`.{other}` isn't a Tiled tileset, expected .tsj or .tsx"
+        )),
+    }
+    .map_err(|problem| invalid(path, &problem))?;
+
+    if tileset.tilewidth != tileset.tileheight {
+        return Err(invalid(path, "tiles have to be square"));
+    }
+    let Some(image) = tileset.image else {
+        return Err(invalid(
+            path,
+            "tilesets made of separate images aren't supported",
+        ));
+    };
+
+    let mut tiles = vec![TileData::new(TileKind::Floor); tileset.tilecount];
+    for tile in tileset.tiles.iter() {
+        let data = tiles
+            .get_mut(tile.id)
+            .ok_or_else(|| invalid(path, &format!("tile {} is past the end", tile.id)))?;
+        *data = read_tile(tile)
+            .map_err(|problem| invalid(path, &format!("tile {}: {problem}", tile.id)))?;
+    }
+
+    Ok(TilesetData {
+        path: path.to_string(),
+        atlas: SpriteData {
+            texture_path: resolve(path, &image),
+            ..Default::default()
+        },
+        source_size: tileset.tilewidth,
+        tiles,
+    })
+}
+
+fn read_tile(tile: &Tile) -> Result<TileData, String> {
+    let mut data = TileData::new(TileKind::Floor);
+    let mut kind = "floor";
+    let mut facing = None;
+    for property in tile.properties.iter() {
+        match property.name.as_str() {
+            "kind" => kind = property.string()?,
+            "facing" => facing = Some(property.direction()?),
+            "damage" => data.damage = property.number()?,
+            "speed" => data.speed = property.number()?,
+            other => {
+                return Err(format!(
+                    "unknown property `{other}`, expected kind, facing, damage or speed"
+                ))
+            }
+        }
+    }
+
+    data.kind = match kind {
+        "ledge" => TileKind::Ledge(facing.ok_or("ledges need a `facing` property")?),
+        other => TileKind::from_name(other).ok_or_else(|| format!(
+            "unknown kind `{other}`, expected floor, solid, pit, shallow_water, deep_water, ledge or damaging_floor"
+        ))?,
+    };
+    Ok(data)
+}
+
+/// Reads the XML formats into the same structs as the JSON ones.
+mod xml {
+    use super::*;
+    use roxmltree::{Document, Node};
+    use serde_json::Value;
+
+    pub(super) fn map(text: &str) -> Result<Map, String> {
+        let document = Document::parse(text).map_err(|error| error.to_string())?;
+        let root = root(&document, "map")?;
+        Ok(Map {
+            orientation: root
+                .attribute("orientation")
+                .unwrap_or_default()
+                .to_string(),
+            infinite: root.attribute("infinite") == Some("1"),
+            width: attribute(root, "width")?,
+            height: attribute(root, "height")?,
+            tilewidth: attribute(root, "tilewidth")?,
+            tileheight: attribute(root, "tileheight")?,
+            tilesets: children(root, "tileset")
+                .map(|node| {
+                    Ok(TilesetRef {
+                        firstgid: attribute(node, "firstgid")?,
+                        source: node.attribute("source").map(str::to_string),
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+            layers: layers(root)?,
+        })
+    }
+
+    pub(super) fn tileset(text: &str) -> Result<Tileset, String> {
+        let document = Document::parse(text).map_err(|error| error.to_string())?;
+        let root = root(&document, "tileset")?;
+        let image = children(root, "image")
+            .next()
+            .and_then(|image| image.attribute("source"))
+            .map(str::to_string);
+        Ok(Tileset {
+            tilewidth: attribute(root, "tilewidth")?,
+            tileheight: attribute(root, "tileheight")?,
+            tilecount: attribute(root, "tilecount")?,
+            image,
+            tiles: children(root, "tile")
+                .map(|node| {
+                    Ok(Tile {
+                        id: attribute(node, "id")?,
+                        properties: properties(node)?,
+                    })
+                })
+                .collect::<Result<_, String>>()?,
+        })
+    }
+
+    fn layers(parent: Node) -> Result<Vec<Layer>, String> {
+        let mut found = Vec::new();
+        for node in parent.children().filter(Node::is_element) {
+            found.push(match node.tag_name().name() {
+                "layer" => tile_layer(node)?,
+                "objectgroup" => Layer::Objects {
+                    objects: children(node, "object")
+                        .map(object)
+                        .collect::<Result<_, String>>()?,
+                },
+                "group" => Layer::Group {
+                    layers: layers(node)?,
+                },
+                "imagelayer" => Layer::Image,
+                _ => continue,
+            });
+        }
+        Ok(found)
+    }
+
+    fn tile_layer(node: Node) -> Result<Layer, String> {
+        let name = node.attribute("name").unwrap_or_default().to_string();
+        let data = children(node, "data")
+            .next()
+            .ok_or_else(|| format!("layer `{name}` has no data"))?;
+        let encoding = data.attribute("encoding").map(str::to_string);
+        let text = data.text().unwrap_or_default();
+
+        let data = match encoding.as_deref() {
+            Some("csv") => LayerData::Gids(
+                text.split(',')
+                    .map(|gid| gid.trim().parse())
+                    .collect::<Result<_, _>>()
+                    .map_err(|_| format!("layer `{name}` has a tile that isn't a number"))?,
+            ),
+            // Very old maps list every tile as its own element.
+            None => LayerData::Gids(
+                children(data, "tile")
+                    .map(|tile| attribute(tile, "gid").or(Ok(0)))
+                    .collect::<Result<_, String>>()?,
+            ),
+            Some(_) => LayerData::Encoded(IgnoredAny),
+        };
+        Ok(Layer::Tiles {
+            name,
+            encoding,
+            data,
+        })
+    }
+
+    fn object(node: Node) -> Result<Object, String> {
+        Ok(Object {
+            id: attribute(node, "id")?,
+            name: node.attribute("name").unwrap_or_default().to_string(),
+            class: node
+                .attribute("class")
+                .or(node.attribute("type"))
+                .unwrap_or_default()
+                .to_string(),
+            x: attribute(node, "x")?,
+            y: attribute(node, "y")?,
+            width: attribute(node, "width").unwrap_or(0.0),
+            height: attribute(node, "height").unwrap_or(0.0),
+            properties: properties(node)?,
+        })
+    }
+
+    /// Properties are typed by their `type` attribute, and multi-line strings are written as text instead of a `value`.
+    fn properties(node: Node) -> Result<Vec<Field>, String> {
+        let Some(properties) = children(node, "properties").next() else {
+            return Ok(Vec::new());
+        };
+        children(properties, "property")
+            .map(|property| {
+                let name = attribute::<String>(property, "name")?;
+                let text = property
+                    .attribute("value")
+                    .or(property.text())
+                    .unwrap_or_default();
+                let value = match property.attribute("type") {
+                    Some("int") => text
+                        .parse::<i64>()
+                        .map(Value::from)
+                        .map_err(|_| format!("property `{name}` isn't a whole number"))?,
+                    Some("float") => text
+                        .parse::<f64>()
+                        .map(Value::from)
+                        .map_err(|_| format!("property `{name}` isn't a number"))?,
+                    Some("bool") => Value::Bool(text == "true"),
+                    _ => Value::String(text.to_string()),
+                };
+                Ok(Field { name, value })
+            })
+            .collect()
+    }
+
+    fn root<'a, 'input>(
+        document: &'a Document<'input>,
+        tag: &str,
+    ) -> Result<Node<'a, 'input>, String> {
+        let root = document.root_element();
+        if root.tag_name().name() != tag {
+            return Err(format!(
+                "expected a <{tag}>, not a <{}>",
+                root.tag_name().name()
+            ));
+        }
+        Ok(root)
+    }
+
+    fn children<'a, 'input: 'a>(
+        node: Node<'a, 'input>,
+        tag: &'a str,
+    ) -> impl Iterator<Item = Node<'a, 'input>> {
+        node.children()
+            .filter(move |child| child.tag_name().name() == tag)
+    }
+
+    fn attribute<T: FromStr>(node: Node, name: &str) -> Result<T, String> {
+        let tag = node.tag_name().name();
+        node.attribute(name)
+            .ok_or_else(|| format!("<{tag}> is missing `{name}`"))?
+            .parse()
+            .map_err(|_| format!("`{name}` of <{tag}> isn't valid"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pickup::PickupKind;
+    use crate::protag::items::ItemType;
+    use crate::trigger::Door;
+    use ggez::GameError;
+
+    fn tileset() -> TilesetData {
+        TilesetData {
+            path: "/tiles/tileset.tsx".to_string(),
+            atlas: SpriteData::default(),
+            source_size: 16.0,
+            tiles: vec![
+                TileData::new(TileKind::Floor),
+                TileData::new(TileKind::Solid),
+                TileData::new(TileKind::Pit),
+            ],
+        }
+    }
+
+    fn error(result: GameResult<impl std::fmt::Debug>) -> String {
+        match result.unwrap_err() {
+            GameError::ResourceLoadError(message) => message,
+            other => panic!("expected a ResourceLoadError, got {other:?}"),
+        }
+    }
+
+    static TMX: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<map orientation="orthogonal" width="2" height="2" tilewidth="16" tileheight="16" infinite="0">
+ <tileset firstgid="1" source="../tiles/tileset.tsx"/>
+ <layer id="1" name="terrain" width="2" height="2">
+  <data encoding="csv">1,2,2147483650,0</data>
+ </layer>
+ <objectgroup id="2" name="objects">
+  <object id="1" class="ProtagStart" x="8" y="8"/>
+  <object id="2" class="Rupee" x="16" y="16">
+   <properties>
+    <property name="amount" type="int" value="5"/>
+   </properties>
+  </object>
+  <object id="3" class="BasicEnemy" x="24" y="8">
+   <properties>
+    <property name="speed" type="float" value="2.5"/>
+   </properties>
+  </object>
+  <object id="4" name="cave_door" class="Door" x="0" y="16" width="16" height="16">
+   <properties>
+    <property name="entrance" value="from_field"/>
+    <property name="map" value="cave.tmx"/>
+   </properties>
+  </object>
+ </objectgroup>
+</map>"#;
+
+    #[test]
+    fn reads_tmx_maps() {
+        let map = parse_map(TMX, &tileset(), "/maps/test.tmx").unwrap();
+        assert_eq!(map.width, 2);
+        // The flipped tile in the third cell still counts as tile 1.
+        assert_eq!(map.tiles, vec![Some(0), Some(1), Some(1), None]);
+        assert_eq!(map.protag_start, Vec2::splat(8.0 * TILE_SIZE / 16.0));
+        assert_eq!(map.pickups[0].position, Vec2::splat(TILE_SIZE));
+        assert_eq!(map.spawns[0].enemy, "BasicEnemy");
+        assert_eq!(map.spawns[0].fields[0].number(), Ok(2.5));
+        assert_eq!(
+            map.triggers[0].door,
+            Some(Door {
+                map: Some("/maps/cave.tmx".to_string()),
+                entrance: "from_field".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn int_properties_set_int_fields() {
+        let map = parse_map(TMX, &tileset(), "/maps/test.tmx").unwrap();
+        assert_eq!(map.pickups[0].kind, PickupKind::Rupee { amount: 5 });
+
+        let tmx = TMX.replace(r#"type="int" value="5""#, r#"type="int" value="5.5""#);
+        assert_eq!(
+            error(parse_map(&tmx, &tileset(), "/maps/test.tmx")),
+            "/maps/test.tmx: property `amount` isn't a whole number"
+        );
+    }
+
+    #[test]
+    fn item_pickups_read_their_item() {
+        let tmx = TMX.replace(
+            r#"class="Rupee" x="16" y="16">
+   <properties>
+    <property name="amount" type="int" value="5"/>"#,
+            r#"class="Item" x="16" y="16">
+   <properties>
+    <property name="item" value="bow"/>"#,
+        );
+        let map = parse_map(&tmx, &tileset(), "/maps/test.tmx").unwrap();
+        assert_eq!(
+            map.pickups[0].kind,
+            PickupKind::Item {
+                item: ItemType::Bow
+            }
+        );
+
+        let tmx = tmx.replace(r#"<property name="item" value="bow"/>"#, "");
+        assert_eq!(
+            error(parse_map(&tmx, &tileset(), "/maps/test.tmx")),
+            "/maps/test.tmx: object 2: items need an `item` field, like bow"
+        );
+    }
+
+    #[test]
+    fn reads_group_layers_in_order() {
+        let json = r#"{
+            "orientation": "orthogonal", "width": 2, "height": 1, "tilewidth": 16, "tileheight": 16,
+            "tilesets": [{ "firstgid": 1, "source": "../tiles/tileset.tsx" }],
+            "layers": [
+                { "type": "tilelayer", "name": "ground", "data": [1, 1] },
+                { "type": "group", "layers": [
+                    { "type": "tilelayer", "name": "walls", "data": [0, 2] },
+                    { "type": "objectgroup", "objects": [
+                        { "id": 1, "type": "ProtagStart", "x": 0, "y": 0 }
+                    ] }
+                ] },
+                { "type": "tilelayer", "name": "holes", "data": [3, 0] }
+            ]
+        }"#;
+        let map = parse_map(json, &tileset(), "/maps/test.tmj").unwrap();
+        assert_eq!(map.tiles, vec![Some(2), Some(1)]);
+        assert_eq!(map.protag_start, Vec2::ZERO);
+    }
+
+    #[test]
+    fn tiles_outside_the_tileset_are_errors() {
+        let tmx = TMX.replace("1,2,2147483650,0", "1,2,4,0");
+        assert_eq!(
+            error(parse_map(&tmx, &tileset(), "/maps/test.tmx")),
+            "/maps/test.tmx: layer `terrain` has tile 4, which isn't in the tileset"
+        );
+    }
+
+    #[test]
+    fn unknown_object_classes_are_errors() {
+        let tmx = TMX.replace(r#"class="BasicEnemy""#, r#"class="Dragon""#);
+        let message = error(parse_map(&tmx, &tileset(), "/maps/test.tmx"));
+        assert!(
+            message.starts_with("/maps/test.tmx: object 3: unknown class `Dragon`"),
+            "{message}"
+        );
+        assert!(message.contains("BasicEnemy"), "{message}");
+    }
+
+    #[test]
+    fn unknown_object_fields_are_errors() {
+        let tmx = TMX.replace(r#"name="amount""#, r#"name="amuont""#);
+        assert_eq!(
+            error(parse_map(&tmx, &tileset(), "/maps/test.tmx")),
+            "/maps/test.tmx: object 2: PickupKind has no field `amuont`"
+        );
+
+        let tmx = TMX.replace(r#"name="map""#, r#"name="mpa""#);
+        assert_eq!(
+            error(parse_map(&tmx, &tileset(), "/maps/test.tmx")),
+            "/maps/test.tmx: object 4 `cave_door`: unknown field `mpa`, expected map or entrance"
+        );
+    }
+
+    #[test]
+    fn unknown_tile_properties_are_errors() {
+        let tsx = r#"<tileset tilewidth="16" tileheight="16" tilecount="2">
+ <image source="tileset.png" width="32" height="16"/>
+ <tile id="1">
+  <properties>
+   <property name="colour" value="red"/>
+  </properties>
+ </tile>
+</tileset>"#;
+        assert_eq!(
+            error(parse_tileset(tsx, "/tiles/test.tsx")),
+            "/tiles/test.tsx: tile 1: unknown property `colour`, expected kind, facing, damage or speed"
+        );
+
+        let tsx = tsx.replace(
+            r#"name="colour" value="red""#,
+            r#"name="kind" value="lava""#,
+        );
+        let message = error(parse_tileset(&tsx, "/tiles/test.tsx"));
+        assert!(
+            message.starts_with("/tiles/test.tsx: tile 1: unknown kind `lava`"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn maps_without_rooms_are_one_room() {
+        let map = parse_map(TMX, &tileset(), "/maps/test.tmx").unwrap();
+        assert_eq!(map.rooms.len(), 1);
+        assert_eq!(
+            map.rooms[0].area,
+            Rect::new(0.0, 0.0, 2.0 * TILE_SIZE, 2.0 * TILE_SIZE)
+        );
+    }
+
+    #[test]
+    fn room_objects_split_the_map() {
+        let tmx = TMX.replace(
+            " </objectgroup>",
+            r#"  <object id="5" name="north" class="Room" x="0" y="0" width="32" height="16">
+   <properties>
+    <property name="neighbours" value="south"/>
+   </properties>
+  </object>
+  <object id="6" name="south" class="Room" x="0" y="16" width="32" height="16"/>
+ </objectgroup>"#,
+        );
+        let map = parse_map(&tmx, &tileset(), "/maps/test.tmx").unwrap();
+        let rooms: Vec<(&str, Rect, Vec<String>)> = map
+            .rooms
+            .iter()
+            .map(|room| (room.name.as_str(), room.area, room.neighbours.clone()))
+            .collect();
+        assert_eq!(
+            rooms,
+            vec![
+                (
+                    "north",
+                    Rect::new(0.0, 0.0, 2.0 * TILE_SIZE, TILE_SIZE),
+                    vec!["south".to_string()]
+                ),
+                (
+                    "south",
+                    Rect::new(0.0, TILE_SIZE, 2.0 * TILE_SIZE, TILE_SIZE),
+                    Vec::new()
+                ),
+            ]
+        );
+
+        let tmx = tmx.replace(r#"value="south""#, r#"value="south, east""#);
+        assert_eq!(
+            error(parse_map(&tmx, &tileset(), "/maps/test.tmx")),
+            "/maps/test.tmx: room `north` leads into `east`, but there's no room with that name"
+        );
+    }
+
+    #[test]
+    fn doors_within_the_map_need_their_entrance() {
+        let tmx = TMX.replace(
+            r#"    <property name="map" value="cave.tmx"/>
+"#,
+            "",
+        );
+        assert_eq!(
+            error(parse_map(&tmx, &tileset(), "/maps/test.tmx")),
+            "/maps/test.tmx: door `cave_door` leads to `from_field`, but there's no entrance with that name"
+        );
+
+        // The entrance can come after the door.
+        let tmx = tmx.replace(
+            " </objectgroup>",
+            r#"  <object id="5" name="from_field" class="Entrance" x="16" y="16"/>
+ </objectgroup>"#,
+        );
+        let map = parse_map(&tmx, &tileset(), "/maps/test.tmx").unwrap();
+        assert_eq!(map.triggers[0].door.as_ref().unwrap().map, None);
+    }
+
+    #[test]
+    fn maps_need_a_protag_start() {
+        let tmx = TMX.replace(r#"class="ProtagStart""#, r#"class="Entrance" name="start""#);
+        assert_eq!(
+            error(parse_map(&tmx, &tileset(), "/maps/test.tmx")),
+            "/maps/test.tmx: there's no ProtagStart object"
+        );
+    }
+}
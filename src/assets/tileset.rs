@@ -0,0 +1,38 @@
+use crate::sprite::SpriteData;
+use crate::tilemap::TileKind;
+use bevy_reflect::Reflect;
+
+/// The tiles a [`Tilemap`](crate::tilemap::Tilemap) is made of, and the atlas they're drawn from.
+///
+/// Loaded from a Tiled tileset, see [`tiled::load_tileset`](super::tiled::load_tileset).
+#[derive(Debug)]
+pub struct TilesetData {
+    /// Where the tileset was loaded from in the resources folder, which maps refer to it by.
+    pub path: String,
+    /// Every tile's image, laid out left to right, then top to bottom, in the same order as [`TilesetData::tiles`].
+    pub atlas: SpriteData,
+    /// The size of one tile in the atlas image, in pixels.
+    pub source_size: f32,
+    /// Indexed by tile id.
+    pub tiles: Vec<TileData>,
+}
+
+/// What a tile does to whatever is on it.
+#[derive(Debug, Clone, Copy, Reflect, PartialEq)]
+pub struct TileData {
+    pub kind: TileKind,
+    /// Dealt to the protag when they fall into a hazard, or every time their iframes run out on a damaging floor.
+    pub damage: f32,
+    /// Multiplies how fast the protag walks across the tile.
+    pub speed: f32,
+}
+
+impl TileData {
+    pub const fn new(kind: TileKind) -> Self {
+        Self {
+            kind,
+            damage: 0.0,
+            speed: 1.0,
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::collision::{Contact, HitboxFrameRef, HitboxType};
+use crate::knockback::HitOrigin;
+use crate::level::Collider;
+use crate::npc::DamageTransfer;
+use bevy_reflect::Reflect;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// What kind of damage an attack deals, passed along to the victim in [`DamageTransfer::element`].
+#[derive(Debug, Default, Clone, Copy, Reflect, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Element {
+    #[default]
+    Physical,
+    Fire,
+    Ice,
+    Lightning,
+}
+
+/// What happens to whatever a [`HitboxFrame`](crate::collision::HitboxFrame) hits.
+///
+/// Frames without an attack are just geometry, and never deal damage through [`resolve_hit`].
+///
+/// In hitbox files, only `damage` and `weight` have to be given.
+#[derive(Debug, Clone, Copy, Reflect, Serialize, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Attack {
+    pub damage: f32,
+    /// for knockback, see [`Knockback`](crate::knockback::Knockback).
+    pub weight: f32,
+    /// How many frames the victim is stunned for, unable to move or attack.
+    #[serde(default)]
+    pub hitstun: usize,
+    #[serde(default)]
+    pub element: Element,
+    /// Inactive frames are still part of the attack, like a wind up or follow through, but can't hit anything.
+    #[serde(default = "active_by_default")]
+    pub active: bool,
+    /// Each target is only hit once by frames of the same group, until the attacker's [`HitHistory`] is cleared.
+    ///
+    /// Giving later frames of a swing a different group lets it hit the same target again.
+    #[serde(default)]
+    pub hit_group: u32,
+}
+
+fn active_by_default() -> bool {
+    true
+}
+
+impl Attack {
+    pub const fn new(damage: f32, weight: f32) -> Self {
+        Self {
+            damage,
+            weight,
+            hitstun: 0,
+            element: Element::Physical,
+            active: true,
+            hit_group: 0,
+        }
+    }
+
+    pub const fn with_element(mut self, element: Element) -> Self {
+        self.element = element;
+        self
+    }
+
+    /// The damage dealt to a victim, knocked back according to `origin`.
+    pub fn transfer(&self, origin: HitOrigin) -> DamageTransfer {
+        DamageTransfer {
+            damage: self.damage,
+            weight: self.weight,
+            origin,
+            stun: self.hitstun,
+            element: self.element,
+        }
+    }
+}
+
+/// Which targets an attacker has already hit, and with which [`Attack::hit_group`].
+///
+/// Attackers keep one of these for as long as one use of the attack lasts, like a single swing of the sword,
+/// and clear it before the next one.
+#[derive(Debug, Default, Clone, Reflect, PartialEq)]
+pub struct HitHistory(Vec<(u32, Collider)>);
+
+impl HitHistory {
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// A hit that landed, from [`resolve_hit`].
+#[derive(Debug, Clone)]
+pub struct Hit {
+    /// Knocks the victim away from the attack, along the deepest contact.
+    pub damage: DamageTransfer,
+    /// Every pair of hitboxes that touched, so the attacker can tell which of its hitboxes landed.
+    pub contacts: Vec<Contact>,
+}
+
+/// Checks whether an attacking frame hits a target, shared by anything that attacks with [`Attack`] frames.
+///
+/// The hit is skipped if the frame has no attack, the attack is inactive, or `history` shows the target
+/// was already hit by the same hit group. Otherwise, if the hitboxes touch, the target is added to the history
+/// and the damage it should take is returned. The target still decides whether the hit actually hurts, like when it has iframes.
+pub fn resolve_hit(
+    frame: HitboxFrameRef,
+    offset: Vec2,
+    target: Collider,
+    hurtbox: HitboxType,
+    target_offset: Vec2,
+    history: &mut HitHistory,
+) -> Option<Hit> {
+    let attack = frame.attack().filter(|attack| attack.active)?;
+    let key = (attack.hit_group, target);
+    if history.0.contains(&key) {
+        return None;
+    }
+
+    let contacts = frame.contacts(hurtbox, offset, target_offset);
+    let deepest = contacts
+        .iter()
+        .copied()
+        .map(Some)
+        .fold(None, Contact::deepest)?;
+    history.0.push(key);
+
+    // The contact normal points from the target towards the attack, so the target is pushed the other way.
+    Some(Hit {
+        damage: attack.transfer(HitOrigin::Normal(-deepest.normal)),
+        contacts,
+    })
+}
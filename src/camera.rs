@@ -0,0 +1,127 @@
+use crate::level::Level;
+use bevy_reflect::Reflect;
+use ggez::graphics::Rect;
+use ggez::Context;
+use glam::Vec2;
+
+/// How much of the world is on screen at once, in world pixels. Bigger windows zoom in to show the same area.
+pub(crate) static VIEW_SIZE: Vec2 = Vec2::new(1280.0, 720.0);
+
+/// How far the protag can get from the middle of the screen before the camera follows them, in world pixels.
+pub(crate) static DEADZONE: Vec2 = Vec2::new(120.0, 80.0);
+
+/// How far the screen moves at most while shaking, in world pixels.
+pub(crate) static MAX_SHAKE: f32 = 16.0;
+
+/// How much trauma wears off every frame.
+pub(crate) static TRAUMA_DECAY: f32 = 0.02;
+
+/// Added to the camera's trauma whenever the protag gets hurt.
+pub(crate) static HURT_TRAUMA: f32 = 0.5;
+
+/// Decides which part of the level is on screen, owned by the [`Level`].
+///
+/// Anything in world space is drawn through the camera, see [`Camera::world_to_screen`].
+/// The HUD and menus are drawn through [`Camera::SCREEN`] instead, which leaves positions as they are.
+#[derive(Debug, Clone, Reflect)]
+pub struct Camera {
+    /// The point in world space at the middle of the screen, not counting shake.
+    #[reflect(ignore)]
+    pub position: Vec2,
+    /// How many pixels on screen one pixel of the world takes up.
+    pub zoom: f32,
+    /// How hard the screen is shaking, from 0 to 1. See [`Camera::add_trauma`].
+    pub trauma: f32,
+    /// How far the screen is shaken away from [`Camera::position`] this frame.
+    #[reflect(ignore)]
+    pub shake: Vec2,
+    /// The size of the window, in pixels.
+    #[reflect(ignore)]
+    pub screen: Vec2,
+    /// Counts up every update, to move the shake along.
+    pub time: usize,
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::SCREEN
+    }
+}
+
+impl Camera {
+    /// Draws in screen space, for the HUD and menus.
+    pub const SCREEN: Camera = Camera {
+        position: Vec2::ZERO,
+        zoom: 1.0,
+        trauma: 0.0,
+        shake: Vec2::ZERO,
+        screen: Vec2::ZERO,
+        time: 0,
+    };
+
+    pub fn world_to_screen(&self, point: Vec2) -> Vec2 {
+        (point - self.position - self.shake) * self.zoom + self.screen / 2.0
+    }
+
+    /// How much of the world fits on screen, in world pixels.
+    pub fn view_size(&self) -> Vec2 {
+        self.screen / self.zoom
+    }
+
+    /// Zooms to fit [`VIEW_SIZE`] into a window of the given size.
+    pub fn resize(&mut self, screen: Vec2) {
+        self.screen = screen;
+        self.zoom = (screen / VIEW_SIZE).min_element();
+    }
+
+    /// The closest position to `position` that keeps the view inside `bounds`.
+    ///
+    /// Bounds smaller than the view are centered on screen.
+    pub fn clamp(&self, position: Vec2, bounds: Rect) -> Vec2 {
+        let half = self.view_size() / 2.0;
+        let min = Vec2::new(bounds.left(), bounds.top()) + half;
+        let max = Vec2::new(bounds.right(), bounds.bottom()) - half;
+        let center = Vec2::new(bounds.x + bounds.w / 2.0, bounds.y + bounds.h / 2.0);
+        Vec2::select(min.cmpgt(max), center, position.clamp(min, max.max(min)))
+    }
+
+    /// Moves just far enough to keep `target` inside the deadzone, without leaving `bounds`.
+    pub fn follow(&mut self, target: Vec2, bounds: Rect) {
+        let offset = (target - self.position).clamp(-DEADZONE, DEADZONE);
+        self.position = self.clamp(target - offset, bounds);
+    }
+
+    /// Moves straight to `target`, without leaving `bounds`.
+    pub fn snap(&mut self, target: Vec2, bounds: Rect) {
+        self.position = self.clamp(target, bounds);
+    }
+
+    /// Shakes the screen. Trauma adds up to at most 1, and the shake grows with its square,
+    /// so small hits barely move the screen while big ones throw it around.
+    pub fn add_trauma(&mut self, amount: f32) {
+        self.trauma = (self.trauma + amount).min(1.0);
+    }
+
+    /// Fits the view to the window, follows the protag around the room they're in, and shakes.
+    ///
+    /// Isn't run while the screen scrolls between rooms, since [`Room::update`](crate::room::Room::update)
+    /// moves the camera then.
+    pub fn update(level: &mut Level, ctx: &mut Context) {
+        let camera = &mut level.camera;
+        let (width, height) = ctx.gfx.drawable_size();
+        camera.resize(Vec2::new(width, height));
+
+        if let Some(room) = level.rooms.get(level.room) {
+            camera.follow(level.protag.position, room.area);
+        }
+
+        camera.time += 1;
+        let time = camera.time as f32;
+        let noise = Vec2::new(
+            ((time * 0.9).sin() + (time * 2.3 + 1.0).sin()) / 2.0,
+            ((time * 1.1 + 4.0).sin() + (time * 2.9 + 2.0).sin()) / 2.0,
+        );
+        camera.shake = noise * MAX_SHAKE * camera.trauma.powi(2);
+        camera.trauma = (camera.trauma - TRAUMA_DECAY).max(0.0);
+    }
+}
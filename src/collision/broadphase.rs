@@ -0,0 +1,209 @@
+use super::{CollisionFilter, HitboxFrameRef, HitboxType};
+use ggez::graphics::Rect;
+use glam::Vec2;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// Width and height of a single cell in world space. Roughly the size of an enemy with its attacks out.
+pub(crate) static CELL_SIZE: f32 = 128.0;
+
+/// A uniform grid that sorts bounding boxes into cells, so that collision checks
+/// only need to run between things that are near each other.
+///
+/// It's meant to be rebuilt every frame: [`clear`](SpatialHash::clear) it, [`insert`](SpatialHash::insert) everything
+/// that can collide, then ask for candidates with [`query`](SpatialHash::query) or [`pairs`](SpatialHash::pairs).
+/// Candidates only have overlapping bounding boxes, so the actual hitboxes still need to be checked afterwards.
+/// Entries are skipped if their [`CollisionFilter`]s don't interact.
+///
+/// The same key can be inserted more than once, like an enemy's hurtbox and each of its attacks,
+/// and will still only be returned once per query.
+#[derive(Debug, Clone)]
+pub struct SpatialHash<K> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+    /// Every inserted key along with its bounding box in world space. Cells store indices into this.
+    entries: Vec<(K, Rect, CollisionFilter)>,
+}
+
+impl<K: Copy + Eq + Hash> SpatialHash<K> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    /// Removes every entry, keeping the allocated cells around for the next frame.
+    pub fn clear(&mut self) {
+        self.cells.values_mut().for_each(Vec::clear);
+        self.entries.clear();
+    }
+
+    /// Adds a bounding box, in world space.
+    pub fn insert(&mut self, key: K, rect: Rect, filter: CollisionFilter) {
+        let index = self.entries.len();
+        self.entries.push((key, rect, filter));
+        for cell in self.cells_of(rect) {
+            self.cells.entry(cell).or_default().push(index);
+        }
+    }
+
+    pub fn insert_hitbox(&mut self, key: K, hitbox: HitboxType, offset: Vec2) {
+        self.insert(
+            key,
+            offset_rect(hitbox.bounding_box(), offset),
+            hitbox.filter(),
+        );
+    }
+
+    /// Every key with a bounding box overlapping the rect and a filter that interacts with `filter`,
+    /// in the order they were inserted.
+    pub fn query(&self, rect: Rect, filter: CollisionFilter) -> Vec<K> {
+        let mut indices: Vec<usize> = self
+            .cells_of(rect)
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .filter(|&index| {
+                let (_, entry_rect, entry_filter) = &self.entries[index];
+                entry_rect.overlaps(&rect) && entry_filter.interacts(&filter)
+            })
+            .collect();
+        indices.sort_unstable();
+        indices.dedup();
+
+        let mut seen: HashSet<K> = HashSet::with_capacity(indices.len());
+        indices
+            .into_iter()
+            .map(|index| self.entries[index].0)
+            .filter(|key| seen.insert(*key))
+            .collect()
+    }
+
+    /// Uses the bounding box the frame already has cached, moved to `offset`.
+    pub fn query_frame(&self, frame: HitboxFrameRef, offset: Vec2) -> Vec<K> {
+        self.query(offset_rect(frame.bounding_box(), offset), frame.filter())
+    }
+
+    pub fn query_hitbox(&self, hitbox: HitboxType, offset: Vec2) -> Vec<K> {
+        self.query(offset_rect(hitbox.bounding_box(), offset), hitbox.filter())
+    }
+
+    /// Every pair of different keys with overlapping bounding boxes. Each pair is only returned once,
+    /// with the key that was inserted first on the left.
+    pub fn pairs(&self) -> Vec<(K, K)> {
+        let mut pairs: Vec<(usize, usize)> = Vec::new();
+        for indices in self.cells.values() {
+            for (i, &a) in indices.iter().enumerate() {
+                for &b in &indices[i + 1..] {
+                    let (a, b) = (a.min(b), a.max(b));
+                    let (a_key, a_rect, a_filter) = &self.entries[a];
+                    let (b_key, b_rect, b_filter) = &self.entries[b];
+                    if a_key != b_key && a_filter.interacts(b_filter) && a_rect.overlaps(b_rect) {
+                        pairs.push((a, b));
+                    }
+                }
+            }
+        }
+        pairs.sort_unstable();
+
+        // Keys inserted more than once can meet in either order, so both orders count as the same pair.
+        let mut seen: HashSet<(K, K)> = HashSet::with_capacity(pairs.len());
+        pairs
+            .into_iter()
+            .map(|(a, b)| (self.entries[a].0, self.entries[b].0))
+            .filter(|&(a, b)| !seen.contains(&(b, a)) && seen.insert((a, b)))
+            .collect()
+    }
+
+    /// The cells the rect touches.
+    fn cells_of(&self, rect: Rect) -> impl Iterator<Item = (i32, i32)> {
+        let cell = |value: f32| (value / self.cell_size).floor() as i32;
+        let (min_x, min_y) = (cell(rect.x), cell(rect.y));
+        let (max_x, max_y) = (cell(rect.x + rect.w), cell(rect.y + rect.h));
+        (min_x..=max_x).flat_map(move |x| (min_y..=max_y).map(move |y| (x, y)))
+    }
+}
+
+impl<K: Copy + Eq + Hash> Default for SpatialHash<K> {
+    fn default() -> Self {
+        Self::new(CELL_SIZE)
+    }
+}
+
+/// Moves a rect relative to its owner into world space, like a [`Hitbox::bounding_box`](super::Hitbox::bounding_box).
+pub(crate) fn offset_rect(rect: Rect, offset: Vec2) -> Rect {
+    Rect::new(rect.x + offset.x, rect.y + offset.y, rect.w, rect.h)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Rect {
+        Rect::new(x, y, size, size)
+    }
+
+    #[test]
+    fn queries_find_overlapping_keys_once_in_order() {
+        let mut hash = SpatialHash::new(10.0);
+        // Spans four cells, and is inserted again as if it were an attack.
+        hash.insert(1, square(5.0, 5.0, 10.0), CollisionFilter::ALL);
+        hash.insert(2, square(50.0, 50.0, 5.0), CollisionFilter::ALL);
+        hash.insert(3, square(12.0, 12.0, 2.0), CollisionFilter::ALL);
+        hash.insert(1, square(0.0, 0.0, 2.0), CollisionFilter::ALL);
+
+        assert_eq!(
+            hash.query(square(0.0, 0.0, 20.0), CollisionFilter::ALL),
+            vec![1, 3]
+        );
+        assert_eq!(
+            hash.query(square(30.0, 30.0, 5.0), CollisionFilter::ALL),
+            Vec::<i32>::new()
+        );
+        // Touching the edge is enough.
+        assert_eq!(
+            hash.query(square(45.0, 50.0, 5.0), CollisionFilter::ALL),
+            vec![2]
+        );
+    }
+
+    #[test]
+    fn pairs_are_returned_once() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert(1, square(0.0, 0.0, 30.0), CollisionFilter::ALL);
+        hash.insert(2, square(5.0, 5.0, 20.0), CollisionFilter::ALL);
+        hash.insert(1, square(8.0, 8.0, 2.0), CollisionFilter::ALL);
+        hash.insert(3, square(100.0, 100.0, 2.0), CollisionFilter::ALL);
+        assert_eq!(hash.pairs(), vec![(1, 2)]);
+    }
+
+    #[test]
+    fn clearing_forgets_every_entry() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert(1, square(0.0, 0.0, 5.0), CollisionFilter::ALL);
+        hash.insert(2, square(0.0, 0.0, 5.0), CollisionFilter::ALL);
+        hash.clear();
+        assert!(hash.pairs().is_empty());
+        assert!(hash
+            .query(square(0.0, 0.0, 5.0), CollisionFilter::ALL)
+            .is_empty());
+    }
+
+    #[test]
+    fn negative_and_empty_rects_are_still_found() {
+        let mut hash = SpatialHash::new(10.0);
+        hash.insert(1, square(-15.0, -15.0, 10.0), CollisionFilter::ALL);
+        hash.insert(2, square(-6.0, -6.0, 0.0), CollisionFilter::ALL);
+        assert_eq!(
+            hash.query(square(-1.0, -1.0, 0.0), CollisionFilter::ALL),
+            Vec::<i32>::new()
+        );
+        assert_eq!(
+            hash.query(square(-8.0, -8.0, 2.0), CollisionFilter::ALL),
+            vec![1, 2]
+        );
+        assert_eq!(hash.pairs(), vec![(1, 2)]);
+    }
+}
@@ -0,0 +1,261 @@
+use super::oriented::project;
+use super::shapes::{closest_points, separating_axes};
+use super::{Hitbox, HitboxShape};
+use bevy_reflect::Reflect;
+use glam::Vec2;
+
+/// Details about where two hitboxes touched, from the point of view of the first hitbox.
+///
+/// Returned by the `contact` family of functions, which mirror the `colliding` family but give more than a `bool`.
+#[derive(Debug, Default, Clone, Copy, Reflect, PartialEq)]
+pub struct Contact {
+    /// Which hitbox of the first frame touched. Always 0 for singular hitboxes.
+    pub index: usize,
+    /// Which hitbox of the other frame touched. Always 0 for singular hitboxes.
+    pub other_index: usize,
+    /// Moving the first hitbox by this much is the shortest way to separate the two.
+    #[reflect(ignore)]
+    pub penetration: Vec2,
+    /// Unit vector pointing from the other hitbox towards the first.
+    #[reflect(ignore)]
+    pub normal: Vec2,
+    /// Roughly where the hitboxes touched, in world space.
+    #[reflect(ignore)]
+    pub point: Vec2,
+}
+
+impl Contact {
+    /// How far the hitboxes are overlapping.
+    pub fn depth(&self) -> f32 {
+        self.penetration.length()
+    }
+
+    /// The same contact, but from the point of view of the other hitbox.
+    pub fn flipped(self) -> Self {
+        Self {
+            index: self.other_index,
+            other_index: self.index,
+            penetration: -self.penetration,
+            normal: -self.normal,
+            point: self.point,
+        }
+    }
+
+    pub(crate) fn with_indices(self, index: usize, other_index: usize) -> Self {
+        Self {
+            index,
+            other_index,
+            ..self
+        }
+    }
+
+    /// Picks whichever contact is deeper, for when a frame touches in several places.
+    pub(crate) fn deepest(a: Option<Contact>, b: Option<Contact>) -> Option<Contact> {
+        match (a, b) {
+            (Some(a), Some(b)) if b.depth() > a.depth() => Some(b),
+            (Some(a), _) => Some(a),
+            (None, b) => b,
+        }
+    }
+}
+
+/// Finds the contact between two hitboxes at the given offsets, if they're touching.
+pub(crate) fn hitbox_contact(
+    hitbox: &Hitbox,
+    other: &Hitbox,
+    offset: Vec2,
+    other_offset: Vec2,
+) -> Option<Contact> {
+    if !hitbox.colliding_single(other, offset, other_offset) {
+        return None;
+    }
+
+    if let (HitboxShape::Rect, HitboxShape::Rect) = (&hitbox.shape, &other.shape) {
+        return Some(rect_contact(hitbox, other, offset, other_offset));
+    }
+
+    let (core, radius) = hitbox.core();
+    let (other_core, other_radius) = other.core();
+    let core: Vec<Vec2> = core.into_iter().map(|point| point + offset).collect();
+    let other_core: Vec<Vec2> = other_core
+        .into_iter()
+        .map(|point| point + other_offset)
+        .collect();
+
+    // If the cores aren't touching, then only the radii are overlapping, and the closest points give the normal directly.
+    if let Some((closest, other_closest)) = closest_points(&core, &other_core) {
+        let distance = closest.distance(other_closest);
+        if let Some(normal) = (closest - other_closest).try_normalize() {
+            let surface = closest - normal * radius;
+            let other_surface = other_closest + normal * other_radius;
+            return Some(Contact {
+                penetration: normal * (radius + other_radius - distance),
+                normal,
+                point: (surface + other_surface) / 2.0,
+                ..Default::default()
+            });
+        }
+    }
+
+    Some(overlapping_core_contact(
+        &core,
+        radius,
+        &other_core,
+        other_radius,
+    ))
+}
+
+/// Axis aligned boxes only ever need to be pushed out along x or y.
+fn rect_contact(hitbox: &Hitbox, other: &Hitbox, offset: Vec2, other_offset: Vec2) -> Contact {
+    let min = Vec2::new(hitbox.rect.x, hitbox.rect.y) + offset;
+    let max = min + Vec2::new(hitbox.rect.w, hitbox.rect.h);
+    let other_min = Vec2::new(other.rect.x, other.rect.y) + other_offset;
+    let other_max = other_min + Vec2::new(other.rect.w, other.rect.h);
+
+    let overlap_min = min.max(other_min);
+    let overlap_max = max.min(other_max);
+    let overlap = overlap_max - overlap_min;
+    let center = (min + max) / 2.0;
+    let other_center = (other_min + other_max) / 2.0;
+
+    let normal = if overlap.x < overlap.y {
+        Vec2::new(if center.x < other_center.x { -1.0 } else { 1.0 }, 0.0)
+    } else {
+        Vec2::new(0.0, if center.y < other_center.y { -1.0 } else { 1.0 })
+    };
+
+    Contact {
+        penetration: normal * overlap.x.min(overlap.y),
+        normal,
+        point: (overlap_min + overlap_max) / 2.0,
+        ..Default::default()
+    }
+}
+
+/// Finds the axis the shapes overlap the least on, which is the shortest way to push them apart.
+fn overlapping_core_contact(
+    core: &[Vec2],
+    radius: f32,
+    other_core: &[Vec2],
+    other_radius: f32,
+) -> Contact {
+    let mut best: Option<(f32, Vec2)> = None;
+    for axis in separating_axes(core)
+        .into_iter()
+        .chain(separating_axes(other_core))
+    {
+        let (min, max) = project(core, axis);
+        let (other_min, other_max) = project(other_core, axis);
+        // The shape can be pushed out either way along the axis, so go whichever way is shorter
+        let backwards = max - other_min;
+        let forwards = other_max - min;
+        let (overlap, axis) = if backwards < forwards {
+            (backwards, -axis)
+        } else {
+            (forwards, axis)
+        };
+        let overlap = overlap + radius + other_radius;
+        if best.is_none_or(|(best_overlap, _)| overlap < best_overlap) {
+            best = Some((overlap, axis));
+        }
+    }
+
+    // Two circles sitting exactly on top of each other have no axis to go by
+    let (depth, normal) = best.unwrap_or((radius + other_radius, Vec2::NEG_Y));
+
+    // The deepest point of the first shape inside the other, moved halfway back out.
+    let deepest = core
+        .iter()
+        .copied()
+        .min_by(|a, b| a.dot(normal).total_cmp(&b.dot(normal)))
+        .unwrap_or_default()
+        - normal * radius;
+
+    Contact {
+        penetration: normal * depth,
+        normal,
+        point: deepest + normal * depth / 2.0,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ggez::graphics::Rect;
+
+    #[test]
+    fn boxes_are_pushed_out_the_shallow_way() {
+        let left = Hitbox::new(Rect::new(0.0, 0.0, 10.0, 10.0));
+        let right = Hitbox::new(Rect::new(8.0, 0.0, 10.0, 10.0));
+        let contact = left.contact_single(&right, Vec2::ZERO, Vec2::ZERO).unwrap();
+        assert_eq!(contact.normal, Vec2::NEG_X);
+        assert_eq!(contact.penetration, Vec2::new(-2.0, 0.0));
+        assert_eq!(contact.point, Vec2::new(9.0, 5.0));
+
+        let flipped = right.contact_single(&left, Vec2::ZERO, Vec2::ZERO).unwrap();
+        assert_eq!(flipped.normal, contact.flipped().normal);
+        assert_eq!(flipped.penetration, contact.flipped().penetration);
+    }
+
+    #[test]
+    fn touching_edges_have_no_depth() {
+        let left = Hitbox::new(Rect::new(0.0, 0.0, 10.0, 10.0));
+        let right = Hitbox::new(Rect::new(10.0, 0.0, 10.0, 10.0));
+        let contact = left.contact_single(&right, Vec2::ZERO, Vec2::ZERO).unwrap();
+        assert_eq!(contact.normal, Vec2::NEG_X);
+        assert_eq!(contact.depth(), 0.0);
+        assert!(left
+            .contact_single(&right, Vec2::new(-0.1, 0.0), Vec2::ZERO)
+            .is_none());
+    }
+
+    #[test]
+    fn circles_are_pushed_apart_along_their_centers() {
+        let circle = Hitbox::circle(Vec2::ZERO, 5.0);
+        let contact = circle
+            .contact_single(&circle, Vec2::ZERO, Vec2::new(8.0, 0.0))
+            .unwrap();
+        assert_eq!(contact.normal, Vec2::NEG_X);
+        assert_eq!(contact.penetration, Vec2::new(-2.0, 0.0));
+        assert_eq!(contact.point, Vec2::new(4.0, 0.0));
+    }
+
+    #[test]
+    fn cores_inside_each_other_use_the_shallowest_axis() {
+        let square = Hitbox::new(Rect::new(0.0, 0.0, 10.0, 10.0));
+        // Inside the box, nearest its right edge.
+        let circle = Hitbox::circle(Vec2::new(8.0, 5.0), 1.0);
+        let contact = circle
+            .contact_single(&square, Vec2::ZERO, Vec2::ZERO)
+            .unwrap();
+        assert_eq!(contact.normal, Vec2::X);
+        assert_eq!(contact.depth(), 3.0);
+    }
+
+    #[test]
+    fn circles_on_top_of_each_other_still_separate() {
+        let circle = Hitbox::circle(Vec2::ZERO, 5.0);
+        let contact = circle
+            .contact_single(&circle, Vec2::ZERO, Vec2::ZERO)
+            .unwrap();
+        assert_eq!(contact.normal.length(), 1.0);
+        assert_eq!(contact.depth(), 10.0);
+    }
+
+    #[test]
+    fn deepest_contacts_win() {
+        let shallow = Contact {
+            penetration: Vec2::new(1.0, 0.0),
+            ..Default::default()
+        };
+        let deep = Contact {
+            penetration: Vec2::new(0.0, -2.0),
+            ..Default::default()
+        };
+        assert_eq!(Contact::deepest(Some(shallow), Some(deep)), Some(deep));
+        assert_eq!(Contact::deepest(Some(deep), Some(shallow)), Some(deep));
+        assert_eq!(Contact::deepest(None, Some(shallow)), Some(shallow));
+        assert_eq!(Contact::deepest(None, None), None);
+    }
+}
@@ -0,0 +1,155 @@
+use bevy_reflect::Reflect;
+use std::ops::BitOr;
+
+/// A set of collision layers, one per bit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct Layers(pub u8);
+
+impl Layers {
+    pub const NONE: Self = Self(0);
+    /// Bodies that can be hurt, like the protag and enemies.
+    pub const HURTBOX: Self = Self(1 << 0);
+    /// Attacks that deal damage, like sword swings, bomb blasts and enemy attacks.
+    pub const HITBOX: Self = Self(1 << 1);
+    /// Level geometry that blocks movement and projectiles.
+    pub const SOLID: Self = Self(1 << 2);
+    /// Areas that do something when walked into, without blocking anything.
+    pub const TRIGGER: Self = Self(1 << 3);
+    pub const PROJECTILE: Self = Self(1 << 4);
+    /// Things lying on the floor that can be collected or picked up.
+    pub const PICKUP: Self = Self(1 << 5);
+    pub const ALL: Self = Self(u8::MAX);
+
+    pub const fn union(self, other: Layers) -> Self {
+        Self(self.0 | other.0)
+    }
+
+    /// True if any layer is in both sets.
+    pub const fn intersects(self, other: Layers) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl BitOr for Layers {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        self.union(other)
+    }
+}
+
+/// Which layers a hitbox is on, and which layers it collides with.
+///
+/// Two hitboxes only collide if each of their masks includes a layer the other is on,
+/// so either side can opt out of a collision. Hitboxes start out on every layer and colliding with every layer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct CollisionFilter {
+    pub layers: Layers,
+    pub mask: Layers,
+}
+
+impl CollisionFilter {
+    pub const ALL: Self = Self::new(Layers::ALL, Layers::ALL);
+    /// The protag's and enemies' bodies. Bodies touch each other so that enemies can deal contact damage.
+    pub const BODY: Self = Self::new(Layers::HURTBOX, Layers::ALL);
+    pub const ATTACK: Self = Self::new(Layers::HITBOX, Layers::HURTBOX.union(Layers::SOLID));
+    pub const SOLID: Self = Self::new(Layers::SOLID, Layers::ALL);
+    pub const TRIGGER: Self = Self::new(Layers::TRIGGER, Layers::HURTBOX);
+    pub const PROJECTILE: Self = Self::new(
+        Layers::PROJECTILE,
+        Layers::HURTBOX.union(Layers::SOLID).union(Layers::PICKUP),
+    );
+    /// Pickups also collide with solids, so that carried things like bombs can land against walls.
+    pub const PICKUP: Self = Self::new(
+        Layers::PICKUP,
+        Layers::HURTBOX
+            .union(Layers::PROJECTILE)
+            .union(Layers::SOLID),
+    );
+
+    pub const fn new(layers: Layers, mask: Layers) -> Self {
+        Self { layers, mask }
+    }
+
+    /// True if the two filters let their hitboxes collide.
+    pub const fn interacts(&self, other: &CollisionFilter) -> bool {
+        self.mask.intersects(other.layers) && other.mask.intersects(self.layers)
+    }
+
+    /// A filter that collides with anything either filter would, for checking groups of hitboxes at once.
+    pub const fn union(self, other: CollisionFilter) -> Self {
+        Self::new(self.layers.union(other.layers), self.mask.union(other.mask))
+    }
+}
+
+impl Default for CollisionFilter {
+    fn default() -> Self {
+        Self::ALL
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{Hitbox, Ray, SpatialHash};
+    use super::*;
+    use ggez::graphics::Rect;
+    use glam::Vec2;
+
+    #[test]
+    fn both_sides_have_to_collide_with_each_other() {
+        assert!(CollisionFilter::ATTACK.interacts(&CollisionFilter::BODY));
+        assert!(CollisionFilter::BODY.interacts(&CollisionFilter::BODY));
+        assert!(CollisionFilter::TRIGGER.interacts(&CollisionFilter::BODY));
+        // Attacks go right through each other and triggers, and triggers don't notice anything but bodies.
+        assert!(!CollisionFilter::ATTACK.interacts(&CollisionFilter::ATTACK));
+        assert!(!CollisionFilter::ATTACK.interacts(&CollisionFilter::TRIGGER));
+        assert!(!CollisionFilter::TRIGGER.interacts(&CollisionFilter::SOLID));
+        // The solid wants to collide with everything, but the trigger opts out.
+        assert!(!CollisionFilter::SOLID.interacts(&CollisionFilter::TRIGGER));
+        assert!(!CollisionFilter::new(Layers::NONE, Layers::ALL).interacts(&CollisionFilter::ALL));
+    }
+
+    #[test]
+    fn hitboxes_on_ignored_layers_never_touch() {
+        let square = Hitbox::new(Rect::new(0.0, 0.0, 10.0, 10.0));
+        let attack = square.clone().with_filter(CollisionFilter::ATTACK);
+        let trigger = square.clone().with_filter(CollisionFilter::TRIGGER);
+        assert!(!attack.colliding_single(&trigger, Vec2::ZERO, Vec2::ZERO));
+        assert!(attack
+            .contact_single(&trigger, Vec2::ZERO, Vec2::ZERO)
+            .is_none());
+        assert!(attack
+            .sweep_single(&trigger, Vec2::new(-20.0, 0.0), Vec2::ZERO, Vec2::ZERO)
+            .is_none());
+        assert!(attack.colliding_single(&square, Vec2::ZERO, Vec2::ZERO));
+    }
+
+    #[test]
+    fn rays_only_hit_layers_in_their_mask() {
+        let wall =
+            Hitbox::new(Rect::new(10.0, -5.0, 10.0, 10.0)).with_filter(CollisionFilter::SOLID);
+        let ray = Ray::new(Vec2::ZERO, Vec2::X, 50.0);
+        assert!(wall.raycast(&ray, Vec2::ZERO).is_some());
+        assert!(wall
+            .raycast(&ray.with_mask(Layers::SOLID), Vec2::ZERO)
+            .is_some());
+        assert!(wall
+            .raycast(
+                &ray.with_mask(Layers::HURTBOX | Layers::TRIGGER),
+                Vec2::ZERO
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn broadphase_skips_filtered_entries() {
+        let mut hash = SpatialHash::new(10.0);
+        let area = Rect::new(0.0, 0.0, 5.0, 5.0);
+        hash.insert(1, area, CollisionFilter::BODY);
+        hash.insert(2, area, CollisionFilter::TRIGGER);
+        hash.insert(3, area, CollisionFilter::ATTACK);
+        assert_eq!(hash.query(area, CollisionFilter::ATTACK), vec![1]);
+        assert_eq!(hash.query(area, CollisionFilter::BODY), vec![1, 2, 3]);
+        assert_eq!(hash.pairs(), vec![(1, 2), (1, 3)]);
+    }
+}
@@ -0,0 +1,1541 @@
+use crate::attack::Attack;
+use crate::camera::Camera;
+use crate::Direction;
+use bevy_reflect::Reflect;
+use ggez::graphics::{Canvas, Color, DrawParam, GraphicsContext, Mesh, Rect};
+use ggez::{mint, GameResult};
+use glam::Vec2;
+use lerp::Lerp;
+use std::convert::AsRef;
+use std::iter::Iterator;
+
+pub use broadphase::SpatialHash;
+pub use contact::Contact;
+pub use layers::{CollisionFilter, Layers};
+pub use oriented::OrientedBox;
+pub use ray::{Ray, RayHit};
+pub use shapes::{Capsule, Circle, ConvexPolygon};
+pub use sweep::Sweep;
+
+pub mod broadphase;
+pub mod contact;
+pub mod layers;
+pub mod oriented;
+pub mod ray;
+pub mod shapes;
+pub mod sweep;
+
+pub enum OwnedHitboxType {
+    /// A singular hitbox.
+    Singular(Hitbox),
+    /// A compound of hitboxes, consisting of at least one (but typically more) hitboxes.
+    Compound(HitboxFrame),
+    /// A list of compound hitboxes, to be iterated upon frame by frame.
+    ///
+    /// The second item (usize) is the current frame that should be active.
+    String(HitboxFrameString, usize),
+}
+
+impl OwnedHitboxType {
+    pub fn as_ref(&self) -> HitboxType {
+        match self {
+            OwnedHitboxType::Singular(singular) => HitboxType::Singular(singular),
+            OwnedHitboxType::Compound(compound) => HitboxType::Compound(compound.borrow()),
+            OwnedHitboxType::String(string, us) => HitboxType::BorrowedString(string.borrow(), *us),
+        }
+    }
+}
+
+pub enum HitboxType<'string, 'frame, 'hitbox> {
+    /// A singular hitbox.
+    Singular(&'hitbox Hitbox),
+    /// A compound of hitboxes, consisting of at least one (but typically more) hitboxes.
+    Compound(HitboxFrameRef<'frame>),
+    /// A list of compound hitboxes, to be iterated upon frame by frame.
+    ///
+    /// The second item (usize) is the current frame that should be active.
+    String(HitboxFrameStringRef<'string, 'frame>, usize),
+    BorrowedString(BorrowedHitboxFrameString<'frame>, usize),
+}
+
+// Hitboxes can be rotated around an origin
+#[derive(Debug, Default, Clone, Reflect, PartialEq)]
+pub struct Hitbox {
+    /// The axis aligned bounds of the hitbox. For [`HitboxShape::Rect`], this is the hitbox itself.
+    #[reflect(ignore)]
+    rect: Rect,
+    shape: HitboxShape,
+    /// Decides which other hitboxes this one can collide with. See [`CollisionFilter`].
+    filter: CollisionFilter,
+}
+
+#[derive(Debug, Default, Clone, Reflect, PartialEq)]
+pub enum HitboxShape {
+    /// Axis aligned, and exactly the hitbox's bounding rect.
+    #[default]
+    Rect,
+    /// Rotated to an arbitrary angle. The hitbox's rect is kept as the bounding box of it.
+    Oriented(OrientedBox),
+    Circle(Circle),
+    Capsule(Capsule),
+    Polygon(ConvexPolygon),
+}
+
+impl Hitbox {
+    pub const fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            shape: HitboxShape::Rect,
+            filter: CollisionFilter::ALL,
+        }
+    }
+
+    /// Creates a hitbox rotated to the given angle, centered at (0,0) + center.
+    pub fn oriented(center: Vec2, half_extents: Vec2, angle: f32) -> Self {
+        Self::from_oriented(OrientedBox::new(center, half_extents, angle))
+    }
+
+    pub fn from_oriented(oriented: OrientedBox) -> Self {
+        Self {
+            rect: oriented.bounding_box(),
+            shape: HitboxShape::Oriented(oriented),
+            filter: CollisionFilter::ALL,
+        }
+    }
+
+    pub const fn circle(center: Vec2, radius: f32) -> Self {
+        let circle = Circle::new(center, radius);
+        Self {
+            rect: circle.bounding_box(),
+            shape: HitboxShape::Circle(circle),
+            filter: CollisionFilter::ALL,
+        }
+    }
+
+    /// Creates a capsule hitbox, covering everything within the radius of the line from start to end.
+    pub const fn capsule(start: Vec2, end: Vec2, radius: f32) -> Self {
+        let capsule = Capsule::new(start, end, radius);
+        Self {
+            rect: capsule.bounding_box(),
+            shape: HitboxShape::Capsule(capsule),
+            filter: CollisionFilter::ALL,
+        }
+    }
+
+    /// Creates a convex polygon hitbox. See [`ConvexPolygon`] for the requirements on the points.
+    pub fn polygon(points: &[Vec2]) -> Self {
+        let polygon = ConvexPolygon::new(points);
+        Self {
+            rect: polygon.bounding_box(),
+            shape: HitboxShape::Polygon(polygon),
+            filter: CollisionFilter::ALL,
+        }
+    }
+
+    /// Recalculates the bounding box after the shape's been changed, keeping the filter.
+    fn with_shape(&self, shape: HitboxShape) -> Self {
+        let rect = self.rect;
+        let rect = match &shape {
+            HitboxShape::Rect => rect,
+            HitboxShape::Oriented(oriented) => oriented.bounding_box(),
+            HitboxShape::Circle(circle) => circle.bounding_box(),
+            HitboxShape::Capsule(capsule) => capsule.bounding_box(),
+            HitboxShape::Polygon(polygon) => polygon.bounding_box(),
+        };
+        Self {
+            rect,
+            shape,
+            filter: self.filter,
+        }
+    }
+
+    pub fn shape(&self) -> &HitboxShape {
+        &self.shape
+    }
+
+    pub const fn filter(&self) -> CollisionFilter {
+        self.filter
+    }
+
+    pub fn set_filter(&mut self, filter: CollisionFilter) {
+        self.filter = filter;
+    }
+
+    /// Puts the hitbox on the filter's layers. Usable in statics, unlike [`Hitbox::set_filter`].
+    pub const fn with_filter(mut self, filter: CollisionFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// The smallest axis aligned rect containing the hitbox, relative to its owner.
+    pub const fn bounding_box(&self) -> Rect {
+        self.rect
+    }
+
+    /// Returns the hitbox as an [`OrientedBox`], converting axis aligned hitboxes into unrotated ones.
+    ///
+    /// Returns `None` for round and polygon hitboxes.
+    pub fn to_oriented(&self) -> Option<OrientedBox> {
+        match &self.shape {
+            HitboxShape::Rect => Some(OrientedBox::from_rect(self.rect)),
+            HitboxShape::Oriented(oriented) => Some(*oriented),
+            _ => None,
+        }
+    }
+
+    /// The hitbox as the outline of a convex core, plus a radius around that core.
+    ///
+    /// Circles are a single point and capsules are a line segment, while boxes and polygons have no radius.
+    /// See [`shapes::convex_distance`] for how this is used.
+    pub fn core(&self) -> (Vec<Vec2>, f32) {
+        match &self.shape {
+            HitboxShape::Rect => (OrientedBox::from_rect(self.rect).corners().to_vec(), 0.0),
+            HitboxShape::Oriented(oriented) => (oriented.corners().to_vec(), 0.0),
+            HitboxShape::Circle(circle) => (vec![circle.center], circle.radius),
+            HitboxShape::Capsule(capsule) => (vec![capsule.start, capsule.end], capsule.radius),
+            HitboxShape::Polygon(polygon) => (polygon.points().to_vec(), 0.0),
+        }
+    }
+
+    /// Rotates the hitbox around the origin by any angle, unlike [`Hitbox::rotate_clockwise`] which only turns by 90°.
+    ///
+    /// Boxes always become oriented hitboxes, even if the angle is a multiple of 90°.
+    pub fn rotated(&self, angle: f32) -> Hitbox {
+        let rotation = Vec2::from_angle(angle);
+        let shape = match &self.shape {
+            HitboxShape::Rect => {
+                HitboxShape::Oriented(OrientedBox::from_rect(self.rect).rotated(angle))
+            }
+            HitboxShape::Oriented(oriented) => HitboxShape::Oriented(oriented.rotated(angle)),
+            HitboxShape::Circle(circle) => {
+                HitboxShape::Circle(Circle::new(rotation.rotate(circle.center), circle.radius))
+            }
+            HitboxShape::Capsule(capsule) => HitboxShape::Capsule(Capsule::new(
+                rotation.rotate(capsule.start),
+                rotation.rotate(capsule.end),
+                capsule.radius,
+            )),
+            HitboxShape::Polygon(polygon) => {
+                let mut polygon = *polygon;
+                for point in polygon.points_mut() {
+                    *point = rotation.rotate(*point);
+                }
+                HitboxShape::Polygon(polygon)
+            }
+        };
+        self.with_shape(shape)
+    }
+
+    /// Creates a new hitbox, centered at (0,0) + point, with the width and height both equalling the size divided by two.
+    ///
+    /// Exists as a helper function to make hitbox creation more intuitive
+    pub const fn point_size(point: Vec2, size: f32) -> Self {
+        Self::new(Rect {
+            x: point.x - (size / 2.0),
+            y: point.y - (size / 2.0),
+            w: size,
+            h: size,
+        })
+    }
+
+    /// Rotates the hitbox so that it points in the given direction.
+    ///
+    /// Does nothing if the hitbox is already pointing in that direction.
+    pub const fn as_direction(
+        mut self,
+        direction: Direction,
+        mut clockwise_rotations: i32,
+    ) -> Self {
+        // self.rect
+        //     .rotate(direction.to_angle() - self.direction.to_angle());
+
+        while clockwise_rotations > 0 {
+            self.rotate_clockwise();
+            clockwise_rotations -= 1;
+        }
+
+        self
+    }
+
+    /// Rotates the hitbox so that it points in the given direction.
+    ///
+    /// Does nothing if the hitbox is already pointing in that direction.
+    pub const fn as_direction_centered(
+        mut self,
+        direction: Direction,
+        mut clockwise_rotations: i32,
+    ) -> Self {
+        // self.rect
+        //     .rotate(direction.to_angle() - self.direction.to_angle());
+
+        while clockwise_rotations > 0 {
+            self.rotate_clockwise();
+            clockwise_rotations -= 1;
+        }
+
+        self
+    }
+
+    #[inline]
+    pub const fn rotate_clockwise(&mut self) {
+        const fn rotate(point: Vec2) -> Vec2 {
+            Vec2::new(point.y, -point.x)
+        }
+
+        match &mut self.shape {
+            HitboxShape::Rect => {}
+            HitboxShape::Oriented(oriented) => {
+                oriented.center = rotate(oriented.center);
+                oriented.angle -= std::f32::consts::FRAC_PI_2;
+            }
+            HitboxShape::Circle(circle) => circle.center = rotate(circle.center),
+            HitboxShape::Capsule(capsule) => {
+                capsule.start = rotate(capsule.start);
+                capsule.end = rotate(capsule.end);
+            }
+            HitboxShape::Polygon(polygon) => {
+                let points = polygon.points_mut();
+                let mut i = 0;
+                while i < points.len() {
+                    points[i] = rotate(points[i]);
+                    i += 1;
+                }
+            }
+        }
+
+        let mut points: [[f32; 2]; 4] = [
+            [self.rect.x, self.rect.y],
+            [self.rect.x, self.rect.y + self.rect.h],
+            [self.rect.x + self.rect.w, self.rect.y],
+            [self.rect.x + self.rect.w, self.rect.y + self.rect.h],
+        ];
+
+        points[0] = [points[0][1], -points[0][0]];
+        points[1] = [points[1][1], -points[1][0]];
+        points[2] = [points[2][1], -points[2][0]];
+        points[3] = [points[3][1], -points[3][0]];
+
+        self.rect = Self::rect_from_points(points);
+    }
+
+    #[inline]
+    pub const fn rect_from_points(points: [[f32; 2]; 4]) -> Rect {
+        let min_x = min(
+            min(points[0][0], points[1][0]),
+            min(points[2][0], points[3][0]),
+        );
+        let min_y = min(
+            min(points[0][1], points[1][1]),
+            min(points[2][1], points[3][1]),
+        );
+        let max_x = max(
+            max(points[0][0], points[1][0]),
+            max(points[2][0], points[3][0]),
+        );
+        let max_y = max(
+            max(points[0][1], points[1][1]),
+            max(points[2][1], points[3][1]),
+        );
+        Rect {
+            x: min_x,
+            y: min_y,
+            w: max_x - min_x,
+            h: max_y - min_y,
+        }
+    }
+
+    /// A hitbox covering everything this one passed over since it was `previous`, for catching hits
+    /// that happen between frames of an animation.
+    ///
+    /// Only circles and boxes can be swept. Anything else is returned as it is now.
+    pub fn swept_from(&self, previous: &Hitbox) -> Hitbox {
+        let shape = match (&previous.shape, &self.shape) {
+            (HitboxShape::Circle(from), HitboxShape::Circle(to)) => HitboxShape::Capsule(
+                Capsule::new(from.center, to.center, from.radius.max(to.radius)),
+            ),
+            _ => match (previous.to_oriented(), self.to_oriented()) {
+                (Some(from), Some(to)) => {
+                    let corners: Vec<Vec2> =
+                        from.corners().into_iter().chain(to.corners()).collect();
+                    HitboxShape::Polygon(ConvexPolygon::new(&shapes::convex_hull(&corners)))
+                }
+                _ => return self.clone(),
+            },
+        };
+        self.with_shape(shape)
+    }
+
+    pub fn lerp(&self, other: &Hitbox, t: f32) -> Hitbox {
+        if self.shape != HitboxShape::Rect || other.shape != HitboxShape::Rect {
+            return self.twine_lerp(other, t, 1.0);
+        }
+        Hitbox::new(Rect {
+            x: self.rect.x.lerp(other.rect.x, t),
+            y: self.rect.y.lerp(other.rect.y, t),
+            w: self.rect.w.lerp(other.rect.w, t),
+            h: self.rect.h.lerp(other.rect.h, t),
+        })
+        .with_filter(self.filter)
+    }
+
+    /// Lerps the size of the hitbox by `t`, but only moves it by `twine` of the way there.
+    ///
+    /// Hitboxes of different shapes can't be blended, so they snap from one to the other halfway through,
+    /// except for boxes, which can always be lerped as oriented boxes.
+    pub fn twine_lerp(&self, target: &Hitbox, t: f32, twine: f32) -> Hitbox {
+        let twined = |from: Vec2, to: Vec2| from + (from.lerp(to, t) - from) * twine;
+        let shape = match (&self.shape, &target.shape) {
+            (HitboxShape::Rect, HitboxShape::Rect) => {
+                let target_x = self.rect.x.lerp(target.rect.x, t);
+                let target_y = self.rect.y.lerp(target.rect.y, t);
+                return Hitbox::new(Rect {
+                    x: self.rect.x + ((target_x - self.rect.x) * twine),
+                    y: self.rect.y + ((target_y - self.rect.y) * twine),
+                    w: self.rect.w.lerp(target.rect.w, t),
+                    h: self.rect.h.lerp(target.rect.h, t),
+                })
+                .with_filter(self.filter);
+            }
+            (HitboxShape::Circle(from), HitboxShape::Circle(to)) => {
+                HitboxShape::Circle(Circle::new(
+                    twined(from.center, to.center),
+                    from.radius.lerp(to.radius, t),
+                ))
+            }
+            (HitboxShape::Capsule(from), HitboxShape::Capsule(to)) => {
+                HitboxShape::Capsule(Capsule::new(
+                    twined(from.start, to.start),
+                    twined(from.end, to.end),
+                    from.radius.lerp(to.radius, t),
+                ))
+            }
+            (HitboxShape::Polygon(from), HitboxShape::Polygon(to))
+                if from.points().len() == to.points().len() =>
+            {
+                let mut polygon = *from;
+                for (point, &to) in polygon.points_mut().iter_mut().zip(to.points()) {
+                    *point = twined(*point, to);
+                }
+                HitboxShape::Polygon(polygon)
+            }
+            _ => match (self.to_oriented(), target.to_oriented()) {
+                (Some(from), Some(to)) => {
+                    let mut lerped = from.lerp(&to, t);
+                    lerped.center = twined(from.center, to.center);
+                    HitboxShape::Oriented(lerped)
+                }
+                _ if t < 0.5 => return self.clone(),
+                _ => return target.clone(),
+            },
+        };
+        self.with_shape(shape)
+    }
+
+    pub fn colliding(&self, other: HitboxType, offset: Vec2, other_offset: Vec2) -> bool {
+        match other {
+            HitboxType::Singular(single) => self.colliding_single(&single, offset, other_offset),
+            HitboxType::Compound(compound) => self.colliding_frame(compound, offset, other_offset),
+            HitboxType::String(string, index) => {
+                self.colliding_frame(string.0[index], offset, other_offset)
+            }
+            HitboxType::BorrowedString(borrowed, index) => {
+                self.colliding_frame(borrowed.0[index], offset, other_offset)
+            }
+        }
+    }
+
+    pub fn colliding_single(&self, other: &Hitbox, offset: Vec2, other_offset: Vec2) -> bool {
+        if !self.filter.interacts(&other.filter) {
+            return false;
+        }
+        match (&self.shape, &other.shape) {
+            (HitboxShape::Rect, HitboxShape::Rect) => {}
+            (
+                HitboxShape::Rect | HitboxShape::Oriented(_),
+                HitboxShape::Rect | HitboxShape::Oriented(_),
+            ) => return self.colliding_oriented(other, offset, other_offset),
+            _ => return self.colliding_shapes(other, offset, other_offset),
+        }
+        Rect::from([
+            self.rect.x + offset.x,
+            self.rect.y + offset.y,
+            self.rect.w,
+            self.rect.h,
+        ])
+        .overlaps(&Rect::from([
+            other.rect.x + other_offset.x,
+            other.rect.y + other_offset.y,
+            other.rect.w,
+            other.rect.h,
+        ]))
+    }
+
+    /// Separating axis test, for when either hitbox is rotated.
+    fn colliding_oriented(&self, other: &Hitbox, offset: Vec2, other_offset: Vec2) -> bool {
+        // Cheap check first, since most hitboxes are nowhere near each other
+        let offset_box = |b: Rect, o: Vec2| Rect::new(b.x + o.x, b.y + o.y, b.w, b.h);
+        if !offset_box(self.rect, offset).overlaps(&offset_box(other.rect, other_offset)) {
+            return false;
+        }
+        match (self.to_oriented(), other.to_oriented()) {
+            (Some(oriented), Some(other_oriented)) => {
+                oriented.overlaps(&other_oriented, offset, other_offset)
+            }
+            _ => self.colliding_shapes(other, offset, other_offset),
+        }
+    }
+
+    /// Works for every pair of shapes, by checking the distance between their cores. See [`Hitbox::core`].
+    fn colliding_shapes(&self, other: &Hitbox, offset: Vec2, other_offset: Vec2) -> bool {
+        let offset_box = |b: Rect, o: Vec2| Rect::new(b.x + o.x, b.y + o.y, b.w, b.h);
+        if !offset_box(self.rect, offset).overlaps(&offset_box(other.rect, other_offset)) {
+            return false;
+        }
+        let (core, radius) = self.core();
+        let (other_core, other_radius) = other.core();
+        let core: Vec<Vec2> = core.into_iter().map(|point| point + offset).collect();
+        let other_core: Vec<Vec2> = other_core
+            .into_iter()
+            .map(|point| point + other_offset)
+            .collect();
+
+        shapes::convex_distance(&core, &other_core) <= radius + other_radius
+    }
+
+    pub fn colliding_frame(&self, other: HitboxFrameRef, offset: Vec2, other_offset: Vec2) -> bool {
+        let does_this_self_hitbox_overlap_with_other =
+            |a: bool, other: &Hitbox| a || self.colliding_single(other, offset, other_offset);
+
+        other
+            .0
+            .into_iter()
+            .fold(false, does_this_self_hitbox_overlap_with_other)
+    }
+
+    /// Like [`Hitbox::colliding`], but returns where the hitboxes touched. See [`Contact`].
+    ///
+    /// If several of the other hitboxes are touching, the deepest contact is returned.
+    pub fn contact(&self, other: HitboxType, offset: Vec2, other_offset: Vec2) -> Option<Contact> {
+        match other {
+            HitboxType::Singular(single) => self.contact_single(single, offset, other_offset),
+            HitboxType::Compound(compound) => self.contact_frame(compound, offset, other_offset),
+            HitboxType::String(string, index) => {
+                self.contact_frame(string.0[index], offset, other_offset)
+            }
+            HitboxType::BorrowedString(borrowed, index) => {
+                self.contact_frame(borrowed.0[index], offset, other_offset)
+            }
+        }
+    }
+
+    pub fn contact_single(
+        &self,
+        other: &Hitbox,
+        offset: Vec2,
+        other_offset: Vec2,
+    ) -> Option<Contact> {
+        if !self.filter.interacts(&other.filter) {
+            return None;
+        }
+        contact::hitbox_contact(self, other, offset, other_offset)
+    }
+
+    pub fn contact_frame(
+        &self,
+        other: HitboxFrameRef,
+        offset: Vec2,
+        other_offset: Vec2,
+    ) -> Option<Contact> {
+        other
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, hitbox)| {
+                self.contact_single(hitbox, offset, other_offset)
+                    .map(|contact| contact.with_indices(0, index))
+            })
+            .fold(None, Contact::deepest)
+    }
+
+    /// Like [`Hitbox::colliding`], but moves the hitbox from `from` to `to` and finds the first time it touches
+    /// any of the other hitboxes. See [`Sweep`].
+    pub fn sweep(
+        &self,
+        other: HitboxType,
+        from: Vec2,
+        to: Vec2,
+        other_offset: Vec2,
+    ) -> Option<Sweep> {
+        other
+            .hitboxes()
+            .iter()
+            .enumerate()
+            .map(|(index, hitbox)| {
+                self.sweep_single(hitbox, from, to, other_offset)
+                    .map(|sweep| sweep.with_indices(0, index))
+            })
+            .fold(None, Sweep::earliest)
+    }
+
+    pub fn sweep_single(
+        &self,
+        other: &Hitbox,
+        from: Vec2,
+        to: Vec2,
+        other_offset: Vec2,
+    ) -> Option<Sweep> {
+        if !self.filter.interacts(&other.filter) {
+            return None;
+        }
+        sweep::hitbox_sweep(self, other, from, to, other_offset)
+    }
+
+    /// Where the ray first hits this hitbox at the given offset, if the hitbox is on one of the ray's layers.
+    pub fn raycast(&self, ray: &Ray, offset: Vec2) -> Option<RayHit> {
+        let offset_box = Rect::new(
+            self.rect.x + offset.x,
+            self.rect.y + offset.y,
+            self.rect.w,
+            self.rect.h,
+        );
+        if !ray.mask.intersects(self.filter.layers) || !ray.bounding_box().overlaps(&offset_box) {
+            return None;
+        }
+        ray::hitbox_raycast(self, ray, offset)
+    }
+
+    /// Moves this hitbox along the ray, and finds where it first runs into any of the other hitboxes.
+    ///
+    /// Uses this hitbox's filter rather than the ray's mask. The hit's point is where this hitbox's offset was when it hit,
+    /// and its index is which of the other hitboxes was hit.
+    pub fn shape_cast(&self, ray: &Ray, other: HitboxType, other_offset: Vec2) -> Option<RayHit> {
+        self.sweep(other, ray.origin, ray.end(), other_offset)
+            .map(|sweep| {
+                let distance = sweep.time * ray.length;
+                RayHit {
+                    index: sweep.other_index,
+                    distance,
+                    normal: sweep.normal,
+                    point: ray.at(distance),
+                }
+            })
+    }
+
+    /// Draws the outline of the hitbox, through the camera.
+    pub fn draw(
+        &self,
+        gfx: &mut GraphicsContext,
+        canvas: &mut Canvas,
+        camera: &Camera,
+        world_offset: Vec2,
+        color: Option<Color>,
+    ) -> GameResult {
+        let vertices: Vec<Vec2> = match &self.shape {
+            HitboxShape::Rect => vec![
+                Vec2::new(self.rect.left(), self.rect.top()),
+                Vec2::new(self.rect.right(), self.rect.top()),
+                Vec2::new(self.rect.right(), self.rect.bottom()),
+                Vec2::new(self.rect.left(), self.rect.bottom()),
+            ],
+            HitboxShape::Oriented(oriented) => oriented.corners().to_vec(),
+            HitboxShape::Circle(circle) => circle.outline(),
+            HitboxShape::Capsule(capsule) => capsule.outline(),
+            HitboxShape::Polygon(polygon) => polygon.points().to_vec(),
+        };
+        if vertices.len() < 2 {
+            return Ok(());
+        }
+
+        // Loop back around to the first corner to close the outline
+        let vertices: Vec<Vec2> = vertices
+            .iter()
+            .chain(vertices.first())
+            .map(|&value| camera.world_to_screen(value + world_offset))
+            .collect();
+
+        canvas.draw(
+            &Mesh::new_line(gfx, &vertices, 4.0 * camera.zoom, Color::WHITE)?,
+            DrawParam::new().color(color.unwrap_or(Color::WHITE)).z(999),
+        );
+        Ok(())
+    }
+}
+
+/// The third item is what the frame does to anything it hits, if it's part of an attack. See [`Attack`].
+#[derive(Debug, Reflect, Clone, PartialEq)]
+pub struct HitboxFrame(Vec<Hitbox>, Direction, Option<Attack>);
+
+impl HitboxFrame {
+    pub fn new(value: &[Hitbox], direction: Direction) -> Self {
+        Self(value.to_vec(), direction, None)
+    }
+
+    /// Returns a list of hitboxes, rotated as the given direction from the given frame.
+    ///
+    /// [`HitboxFrame`] stores a reference to the hitboxes, and does not store them itself.
+    /// So something else needs to take ownership of the hitboxes. Thus, this function returns
+    /// an iterator which you should collect into somewhere yourself. Then, you can use
+    /// [`HitboxFrame::new`] with a slice of the collected hitboxes to get the new [`HitboxFrame`]
+    pub fn as_direction(&self, direction: Direction) -> Self {
+        let clockwise_rotations = (direction as i32 - self.1 as i32).rem_euclid(4);
+
+        let collect = self
+            .0
+            .iter()
+            .map(|value| {
+                value
+                    .clone()
+                    .as_direction_centered(direction, clockwise_rotations)
+            })
+            .collect();
+        Self(collect, direction, self.2)
+    }
+
+    /// Rotates every hitbox in the frame around the origin by any angle.
+    ///
+    /// The frame's direction becomes whichever of the four directions is closest to where it now points.
+    pub fn rotated(&self, angle: f32) -> Self {
+        Self(
+            self.0.iter().map(|hitbox| hitbox.rotated(angle)).collect(),
+            Direction::from(Vec2::from_angle(self.1.to_angle() + angle)),
+            self.2,
+        )
+    }
+
+    pub fn borrow(&self) -> HitboxFrameRef {
+        let frame = HitboxFrameRef::from_hitboxes(self.1, &self.0);
+        match self.2 {
+            Some(attack) => frame.with_attack(attack),
+            None => frame,
+        }
+    }
+
+    pub fn attack(&self) -> Option<Attack> {
+        self.2
+    }
+
+    pub fn set_attack(&mut self, attack: Option<Attack>) {
+        self.2 = attack;
+    }
+
+    /// Puts every hitbox in the frame on the filter's layers.
+    pub fn set_filter(&mut self, filter: CollisionFilter) {
+        for hitbox in self.0.iter_mut() {
+            hitbox.set_filter(filter);
+        }
+    }
+
+    /// Sweeps each hitbox from where it was in the previous frame. See [`Hitbox::swept_from`].
+    ///
+    /// Frames with a different amount of hitboxes can't be matched up, so they're returned unswept.
+    pub fn swept_from(&self, previous: &HitboxFrame) -> Self {
+        if self.0.len() != previous.0.len() {
+            return self.clone();
+        }
+        Self(
+            self.0
+                .iter()
+                .zip(previous.0.iter())
+                .map(|(hitbox, previous)| hitbox.swept_from(previous))
+                .collect(),
+            self.1,
+            self.2,
+        )
+    }
+}
+
+#[derive(Debug, Default, Clone, Reflect, PartialEq)]
+pub struct HitboxFrameString(pub Vec<HitboxFrame>);
+
+impl HitboxFrameString {
+    pub fn new(frames: Vec<HitboxFrame>) -> Self {
+        Self(frames)
+    }
+
+    pub fn from_frames(zero: &[HitboxFrame]) -> Self {
+        Self(
+            zero.iter()
+                .map(|value| value.clone())
+                .collect::<Vec<HitboxFrame>>(),
+        )
+    }
+
+    pub fn from_hitboxes(
+        zero: impl IntoIterator<Item = impl AsRef<[Hitbox]>>,
+        direction: Direction,
+    ) -> Self {
+        let zero: Vec<HitboxFrame> = zero
+            .into_iter()
+            .map(|value| HitboxFrame::new(value.as_ref(), direction))
+            .collect();
+
+        Self(zero)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn as_direction(
+        hitbox_frame_string: HitboxFrameStringRef<'_, '_>,
+        direction: Direction,
+    ) -> Self
+// where
+    //     B: for<'a> FromIterator<I>,
+    //     I: FromIterator<Hitbox> + AsRef<[Hitbox]>,
+    {
+        Self(
+            hitbox_frame_string
+                .0
+                .into_iter()
+                .map(|value: &HitboxFrameRef<'_>| value.to_owned().as_direction(direction))
+                .collect(),
+        )
+    }
+
+    pub fn colliding(
+        &self,
+        frame: usize,
+        other: HitboxType,
+        offset: Vec2,
+        other_offset: Vec2,
+    ) -> bool {
+        let binding = self.borrow();
+        HitboxType::String(binding.borrow(), frame).is_colliding(other, offset, other_offset)
+    }
+
+    pub fn contact(
+        &self,
+        frame: usize,
+        other: HitboxType,
+        offset: Vec2,
+        other_offset: Vec2,
+    ) -> Option<Contact> {
+        self.0
+            .get(frame)?
+            .borrow()
+            .contact(other, offset, other_offset)
+    }
+
+    /// Every pair of hitboxes that's touching on the given frame. See [`HitboxFrameRef::contacts`].
+    pub fn contacts(
+        &self,
+        frame: usize,
+        other: HitboxType,
+        offset: Vec2,
+        other_offset: Vec2,
+    ) -> Vec<Contact> {
+        self.0.get(frame).map_or(Vec::new(), |frame| {
+            frame.borrow().contacts(other, offset, other_offset)
+        })
+    }
+
+    pub fn draw(
+        &self,
+        gfx: &mut GraphicsContext,
+        canvas: &mut Canvas,
+        camera: &Camera,
+        index: usize,
+        offset: Vec2,
+        color: Color,
+    ) -> GameResult {
+        if let Some(frame) = self.0.get(index) {
+            frame.borrow().draw(gfx, canvas, camera, offset, color)?;
+        }
+        Ok(())
+    }
+
+    /// Rotates every frame around the origin by any angle. See [`HitboxFrame::rotated`].
+    pub fn rotated(&self, angle: f32) -> Self {
+        Self(self.0.iter().map(|frame| frame.rotated(angle)).collect())
+    }
+
+    /// Puts every hitbox in every frame on the filter's layers.
+    pub fn set_filter(&mut self, filter: CollisionFilter) {
+        for frame in self.0.iter_mut() {
+            frame.set_filter(filter);
+        }
+    }
+
+    pub fn borrow(&self) -> BorrowedHitboxFrameString {
+        BorrowedHitboxFrameString::new(self)
+    }
+}
+
+/// A set of [`Hitbox`]es, made to operate together as one more complex hitbox.
+///
+/// The third item ([`Rect`]) is the bounding box, cached with the frame to avoid recalculating it every collision.
+/// The fourth is the frame's [`Attack`], if it has one.
+///
+/// See [`Hitbox`] for details on each individual hitbox.
+///
+/// Also see [`HitboxFrameString`] for a set of [`HitboxFrame`]'s that can be interchanged frame-by-frame.
+#[derive(Debug, Reflect, Clone, Copy, PartialEq)]
+pub struct HitboxFrameRef<'hitbox>(
+    pub &'hitbox [Hitbox],
+    Direction,
+    #[reflect(ignore)] Rect,
+    Option<Attack>,
+);
+
+#[inline]
+pub(crate) const fn min(a: f32, b: f32) -> f32 {
+    match a > b {
+        true => b,
+        false => a,
+    }
+}
+
+#[inline]
+pub(crate) const fn max(a: f32, b: f32) -> f32 {
+    match a > b {
+        true => a,
+        false => b,
+    }
+}
+
+impl<'hitbox> HitboxFrameRef<'hitbox> {
+    pub const fn right(hitboxes: &'hitbox [Hitbox]) -> Self {
+        Self(
+            hitboxes,
+            Direction::Right,
+            HitboxFrameRef::calculate_bounding_box(hitboxes),
+            None,
+        )
+    }
+    pub const fn up(hitboxes: &'hitbox [Hitbox]) -> Self {
+        Self(
+            hitboxes,
+            Direction::Up,
+            HitboxFrameRef::calculate_bounding_box(hitboxes),
+            None,
+        )
+    }
+    pub const fn left(hitboxes: &'hitbox [Hitbox]) -> Self {
+        Self(
+            hitboxes,
+            Direction::Left,
+            HitboxFrameRef::calculate_bounding_box(hitboxes),
+            None,
+        )
+    }
+    pub const fn down(hitboxes: &'hitbox [Hitbox]) -> Self {
+        Self(
+            hitboxes,
+            Direction::Down,
+            HitboxFrameRef::calculate_bounding_box(hitboxes),
+            None,
+        )
+    }
+
+    pub const fn from_hitboxes(direction: Direction, hitboxes: &'hitbox [Hitbox]) -> Self {
+        Self(
+            hitboxes,
+            direction,
+            HitboxFrameRef::calculate_bounding_box(hitboxes),
+            None,
+        )
+    }
+
+    /// Calculate one bounding box that encompasses all of the given hitboxes
+    pub const fn calculate_bounding_box(hitboxes: &'hitbox [Hitbox]) -> Rect {
+        if hitboxes.is_empty() {
+            return Rect::zero();
+        }
+        let mut bounding_box: Rect = hitboxes[0].rect;
+        let mut i = 1;
+        while i < hitboxes.len() {
+            bounding_box = {
+                let other = hitboxes[i].rect;
+                let x = min(bounding_box.x, other.x);
+                let y = min(bounding_box.y, other.y);
+                let w = max(
+                    (&bounding_box).x + (&bounding_box).w,
+                    (&other).x + (&other).w,
+                ) - x;
+                let h = max(
+                    (&bounding_box).y + (&bounding_box).h,
+                    (&other).y + (&other).h,
+                ) - y;
+                Rect { x, y, w, h }
+            };
+            i += 1;
+        }
+        bounding_box
+    }
+
+    /// One box around every hitbox in the frame, cached when the frame was made.
+    pub const fn bounding_box(&self) -> Rect {
+        self.2
+    }
+
+    /// Makes the frame part of an attack, dealing damage to whatever it hits.
+    pub const fn with_attack(mut self, attack: Attack) -> Self {
+        self.3 = Some(attack);
+        self
+    }
+
+    pub const fn attack(&self) -> Option<Attack> {
+        self.3
+    }
+
+    /// Every layer any hitbox in the frame is on, and every layer any of them collide with.
+    pub fn filter(&self) -> CollisionFilter {
+        self.0
+            .iter()
+            .map(Hitbox::filter)
+            .reduce(CollisionFilter::union)
+            .unwrap_or(CollisionFilter::new(Layers::NONE, Layers::NONE))
+    }
+
+    pub fn colliding(&self, other: HitboxType, offset: Vec2, other_offset: Vec2) -> bool {
+        match other {
+            HitboxType::Singular(single) => self.colliding_single(single, offset, other_offset),
+            HitboxType::Compound(compound) => self.colliding_frame(compound, offset, other_offset),
+            HitboxType::String(string, index) => {
+                self.colliding_frame(string.0[index], offset, other_offset)
+            }
+            HitboxType::BorrowedString(borrowed, index) => {
+                self.colliding_frame(borrowed.0[index], offset, other_offset)
+            }
+        }
+    }
+
+    pub fn colliding_single(&self, other: &Hitbox, offset: Vec2, other_offset: Vec2) -> bool {
+        for rect in self.0.iter() {
+            if rect.colliding_single(&other, offset, other_offset) {
+                return true;
+            }
+        }
+        false
+    }
+
+    pub fn colliding_frame(
+        &self,
+        compound: HitboxFrameRef,
+        offset: Vec2,
+        other_offset: Vec2,
+    ) -> bool {
+        // wouldve named offset_bounding_box, but this more turse way is much nicer with formatting
+        let offset_box = |b: Rect, o: Vec2| Rect::new(b.x + o.x, b.y + o.y, b.w, b.h);
+        let self_bounding_box = offset_box(self.2.clone(), offset);
+        let other_bounding_box = offset_box(compound.2.clone(), other_offset);
+
+        if !self_bounding_box.overlaps(&other_bounding_box) {
+            return false;
+        }
+
+        self.0
+            .into_iter()
+            .filter(|hitbox: &&Hitbox| hitbox.rect.overlaps(&other_bounding_box)) // optimization filter, check for bounding box to save compound checks
+            .filter(|hitbox: &&Hitbox| hitbox.colliding_frame(compound, offset, other_offset)) // returns any hitboxes that are overlapping with the other compound hitbox
+            .next() // if any overlaps exist, then some
+            .is_some()
+    }
+
+    /// The first hitbox in the frame the ray hits, along with where.
+    pub fn raycast(&self, ray: &Ray, offset: Vec2) -> Option<RayHit> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, hitbox)| hitbox.raycast(ray, offset).map(|hit| hit.with_index(index)))
+            .fold(None, RayHit::nearest)
+    }
+
+    /// Like [`Hitbox::sweep`], but moves the whole frame and returns the earliest hit out of every hitbox.
+    pub fn sweep(
+        &self,
+        other: HitboxType,
+        from: Vec2,
+        to: Vec2,
+        other_offset: Vec2,
+    ) -> Option<Sweep> {
+        let others = other.hitboxes();
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(index, hitbox)| {
+                others.iter().enumerate().map(move |(other_index, other)| {
+                    hitbox
+                        .sweep_single(other, from, to, other_offset)
+                        .map(|sweep| sweep.with_indices(index, other_index))
+                })
+            })
+            .fold(None, Sweep::earliest)
+    }
+
+    /// Like [`HitboxFrameRef::colliding`], but returns the deepest contact out of every pair of hitboxes that touched.
+    pub fn contact(&self, other: HitboxType, offset: Vec2, other_offset: Vec2) -> Option<Contact> {
+        self.contacts(other, offset, other_offset)
+            .into_iter()
+            .map(Some)
+            .fold(None, Contact::deepest)
+    }
+
+    /// Every pair of hitboxes that's touching, with [`Contact::index`] being the index of the hitbox in this frame.
+    pub fn contacts(&self, other: HitboxType, offset: Vec2, other_offset: Vec2) -> Vec<Contact> {
+        let others = other.hitboxes();
+        self.0
+            .iter()
+            .enumerate()
+            .flat_map(|(index, hitbox)| {
+                others
+                    .iter()
+                    .enumerate()
+                    .filter_map(move |(other_index, other)| {
+                        hitbox
+                            .contact_single(other, offset, other_offset)
+                            .map(|contact| contact.with_indices(index, other_index))
+                    })
+            })
+            .collect()
+    }
+
+    pub fn draw(
+        &self,
+        gfx: &mut GraphicsContext,
+        canvas: &mut Canvas,
+        camera: &Camera,
+        offset: Vec2,
+        color: Color,
+    ) -> GameResult {
+        for hitbox in self.0.iter() {
+            hitbox.draw(gfx, canvas, camera, offset, Some(color))?;
+        }
+        Ok(())
+    }
+}
+
+impl HitboxFrameRef<'_> {
+    pub fn to_owned(&self) -> HitboxFrame {
+        HitboxFrame(self.0.to_vec(), self.1, self.3)
+    }
+}
+
+impl HitboxType<'_, '_, '_> {
+    pub fn is_colliding(&self, other: HitboxType, offset: Vec2, other_offset: Vec2) -> bool {
+        match self {
+            HitboxType::Singular(singular) => singular.colliding(other, offset, other_offset),
+            HitboxType::Compound(compound) => compound.colliding(other, offset, other_offset),
+            HitboxType::String(string, index) => {
+                string.0[*index].colliding(other, offset, other_offset)
+            }
+            HitboxType::BorrowedString(borrowed, index) => {
+                borrowed.0[*index].colliding(other, offset, other_offset)
+            }
+        }
+    }
+
+    pub fn contact(&self, other: HitboxType, offset: Vec2, other_offset: Vec2) -> Option<Contact> {
+        self.frame().contact(other, offset, other_offset)
+    }
+
+    pub fn contacts(&self, other: HitboxType, offset: Vec2, other_offset: Vec2) -> Vec<Contact> {
+        self.frame().contacts(other, offset, other_offset)
+    }
+
+    pub fn raycast(&self, ray: &Ray, offset: Vec2) -> Option<RayHit> {
+        self.frame().raycast(ray, offset)
+    }
+
+    /// See [`HitboxFrameRef::filter`].
+    pub fn filter(&self) -> CollisionFilter {
+        self.frame().filter()
+    }
+
+    /// One box around every active hitbox.
+    pub fn bounding_box(&self) -> Rect {
+        match self {
+            HitboxType::Singular(singular) => singular.rect,
+            _ => self.frame().bounding_box(),
+        }
+    }
+
+    /// The hitboxes that are currently active.
+    pub fn hitboxes(&self) -> &[Hitbox] {
+        match self {
+            HitboxType::Singular(singular) => std::slice::from_ref(*singular),
+            HitboxType::Compound(compound) => compound.0,
+            HitboxType::String(string, index) => string.0[*index].0,
+            HitboxType::BorrowedString(borrowed, index) => borrowed.0[*index].0,
+        }
+    }
+
+    fn frame(&self) -> HitboxFrameRef<'_> {
+        match self {
+            HitboxType::Singular(singular) => {
+                HitboxFrameRef::from_hitboxes(Direction::Right, std::slice::from_ref(*singular))
+            }
+            HitboxType::Compound(compound) => *compound,
+            HitboxType::String(string, index) => string.0[*index],
+            HitboxType::BorrowedString(borrowed, index) => borrowed.0[*index],
+        }
+    }
+}
+
+/// A set of hitbox sets, to be iterated through frame by frame.
+///
+/// An example of this would be a fighting game attack, with the "frame data".
+/// Each frame has a set of hitboxes, which detail a shape that can be used for complex attacks or collision.
+/// Each string has a set of frames, which can be used to "animate" a shape that changes form across time, or by other metrics.
+///
+/// Since both this and [`HitboxFrame`] operate off of borrowed data, it's easy to reuse hitbox data wherever needed.
+///
+#[derive(Debug, Default, Reflect, Clone, Copy, PartialEq)]
+pub struct HitboxFrameStringRef<'string, 'hitbox>(pub &'string [HitboxFrameRef<'hitbox>]);
+
+pub type StaticHitboxFrameString = HitboxFrameStringRef<'static, 'static>;
+
+impl<'string, 'hitbox> HitboxFrameStringRef<'string, 'hitbox> {
+    pub fn new_with_direction(zero: &'string [HitboxFrameRef<'hitbox>]) -> Self {
+        Self(zero)
+    }
+
+    pub const fn new(zero: &'string [HitboxFrameRef<'hitbox>]) -> Self {
+        Self(zero)
+    }
+
+    pub const fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn to_direction(self, direction: Direction) -> HitboxFrameString {
+        HitboxFrameString::new(
+            self.0
+                .iter()
+                .map(|value: &HitboxFrameRef<'_>| value.to_owned().as_direction(direction))
+                .collect(),
+        )
+    }
+
+    pub fn colliding(
+        &self,
+        frame: usize,
+        other: HitboxType,
+        offset: Vec2,
+        other_offset: Vec2,
+    ) -> bool {
+        HitboxType::String(*self, frame).is_colliding(other, offset, other_offset)
+    }
+
+    pub fn contact(
+        &self,
+        frame: usize,
+        other: HitboxType,
+        offset: Vec2,
+        other_offset: Vec2,
+    ) -> Option<Contact> {
+        self.0.get(frame)?.contact(other, offset, other_offset)
+    }
+
+    pub fn draw(
+        &self,
+        gfx: &mut GraphicsContext,
+        canvas: &mut Canvas,
+        camera: &Camera,
+        index: usize,
+        offset: Vec2,
+        color: Color,
+    ) -> GameResult {
+        if let Some(frame) = self.0.get(index) {
+            frame.draw(gfx, canvas, camera, offset, color)?;
+        }
+        Ok(())
+    }
+}
+
+pub struct BorrowedHitboxFrameString<'a>(Vec<HitboxFrameRef<'a>>);
+
+impl<'a> BorrowedHitboxFrameString<'a> {
+    pub fn new(borrowing: &'a HitboxFrameString) -> Self {
+        let collect = borrowing
+            .0
+            .iter()
+            .map(|value| value.borrow())
+            .collect::<Vec<HitboxFrameRef>>();
+        Self(collect)
+    }
+
+    pub fn borrow(&self) -> HitboxFrameStringRef {
+        HitboxFrameStringRef::new(&self.0)
+    }
+}
+
+/// Machiene that turns hitboxes into lerped hitboxes
+#[derive(Debug, Reflect, Clone)]
+pub struct HitboxAnimation {
+    pub lerped_hitboxes: HitboxFrame,
+    pub hitboxes: [HitboxFrameString; 4],
+    /// The amount of frames between each keyframe.
+    intervals: Vec<usize>,
+
+    frame_of_current_interval: usize,
+    current_interval: usize,
+    loops: bool,
+    active: bool,
+    twine: f32,
+    direction: Direction,
+    /// If set, the animation points at this angle instead of being snapped to [`HitboxAnimation::direction`].
+    angle: Option<f32>,
+    /// If true, [`HitboxAnimation::lerped_hitboxes`] covers everything the hitboxes passed over since the last frame,
+    /// so that fast animations can't skip over anything between frames.
+    sweep: bool,
+}
+
+impl HitboxAnimation {
+    pub fn new(hitboxes: HitboxFrameStringRef, intervals: Vec<usize>) -> Self {
+        Self {
+            lerped_hitboxes: HitboxFrame::new(&[], Direction::Down),
+            hitboxes: [
+                hitboxes.to_direction(Direction::Right),
+                hitboxes.to_direction(Direction::Up),
+                hitboxes.to_direction(Direction::Left),
+                hitboxes.to_direction(Direction::Down),
+            ],
+            intervals,
+            frame_of_current_interval: 0,
+            current_interval: 0,
+            loops: true,
+            active: true,
+            twine: 1.0,
+            direction: Direction::Down,
+            angle: None,
+            sweep: false,
+        }
+    }
+
+    /// Puts every hitbox in every frame of the animation on the filter's layers.
+    pub fn set_filter(&mut self, filter: CollisionFilter) {
+        for string in self.hitboxes.iter_mut() {
+            string.set_filter(filter);
+        }
+        self.lerped_hitboxes.set_filter(filter);
+    }
+
+    pub fn set_twine(&mut self, twine: f32) {
+        self.twine = twine;
+    }
+
+    pub fn sweep_between_frames(&mut self, value: bool) {
+        self.sweep = value;
+    }
+
+    pub fn loop_animations(&mut self, value: bool) {
+        self.loops = value;
+    }
+
+    pub fn set_active(&mut self, value: bool) {
+        self.active = value;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn set_direction(&mut self, direction: Direction) {
+        self.direction = direction;
+    }
+
+    pub fn angle(&self) -> Option<f32> {
+        self.angle
+    }
+
+    /// Points the animation at any angle, rotating the right facing hitboxes to match.
+    /// Setting it back to `None` snaps the animation back to its direction.
+    pub fn set_angle(&mut self, angle: Option<f32>) {
+        self.angle = angle;
+    }
+
+    pub fn reset(&mut self) {
+        self.active = true;
+        self.current_interval = 0;
+        self.frame_of_current_interval = 0;
+    }
+
+    pub fn reset_current_interval(&mut self) {
+        self.frame_of_current_interval = 0;
+    }
+
+    pub fn update_animation(&mut self) -> bool {
+        if !self.active {
+            return false;
+        }
+
+        let updated = if self.frame_of_current_interval == 0 {
+            if self.current_interval >= self.intervals.len() - 1 {
+                if self.loops {
+                    self.current_interval = 0;
+                } else {
+                    self.active = false;
+                }
+                true
+            } else {
+                self.current_interval += 1;
+                self.frame_of_current_interval = self.intervals[self.current_interval];
+                false
+            }
+        } else {
+            self.frame_of_current_interval -= 1;
+            false
+        };
+
+        let lerped = self.lerped_hitboxes();
+        self.lerped_hitboxes = match self.previous_step() {
+            // Looping back around jumps straight to the first frame, so there's nothing in between to sweep over.
+            Some((interval, frame)) if self.sweep && !updated => {
+                lerped.swept_from(&self.lerped_hitboxes_at(interval, frame))
+            }
+            _ => lerped,
+        };
+
+        updated
+    }
+
+    pub fn current_frame(&self, direction: Direction) -> HitboxFrameRef {
+        self.hitboxes[direction as usize].0[self.current_interval].borrow()
+    }
+
+    pub fn next_frame(&self, direction: Direction) -> Option<HitboxFrameRef> {
+        let string = &self.hitboxes[direction as usize];
+
+        if self.current_interval + 1 >= string.0.len() {
+            return None;
+        }
+
+        Some(string.0[self.current_interval + 1].borrow())
+    }
+
+    pub fn lerped_hitboxes(&self) -> HitboxFrame {
+        self.lerped_hitboxes_at(self.current_interval, self.frame_of_current_interval)
+    }
+
+    /// The lerped hitboxes at any point of the animation, pointing the way the animation currently does.
+    fn lerped_hitboxes_at(&self, interval: usize, frame: usize) -> HitboxFrame {
+        if let Some(angle) = self.angle {
+            return self
+                .lerped_frame(Direction::Right, interval, frame)
+                .rotated(angle);
+        }
+        self.lerped_frame(self.direction, interval, frame)
+    }
+
+    /// Where the animation was on the previous frame, or `None` if it's on the first frame.
+    fn previous_step(&self) -> Option<(usize, usize)> {
+        if self.frame_of_current_interval < self.intervals[self.current_interval] {
+            Some((self.current_interval, self.frame_of_current_interval + 1))
+        } else {
+            self.current_interval
+                .checked_sub(1)
+                .map(|interval| (interval, 0))
+        }
+    }
+
+    fn lerped_frame(&self, direction: Direction, interval: usize, frame: usize) -> HitboxFrame {
+        let string = &self.hitboxes[direction as usize];
+        let current_frame = string.0[interval].borrow();
+
+        let t = (self.intervals[interval] - frame) as f32 / (self.intervals[interval] as f32 + 1.0);
+
+        let new_frame: HitboxFrame =
+            if let Some(next_frame) = string.0.get(interval + 1).map(HitboxFrame::borrow) {
+                // The attack doesn't lerp, it stays as the keyframe's until the next keyframe is reached.
+                let mut lerped = HitboxFrame::new(
+                    &current_frame
+                        .0
+                        .iter()
+                        .zip(next_frame.0.iter())
+                        .map(|(current, next)| current.twine_lerp(next, t, self.twine))
+                        .collect::<Vec<Hitbox>>(),
+                    direction,
+                );
+                lerped.set_attack(current_frame.attack());
+                lerped
+            } else {
+                current_frame.to_owned()
+            };
+
+        new_frame
+    }
+
+    pub fn draw(
+        &self,
+        gfx: &mut GraphicsContext,
+        canvas: &mut Canvas,
+        camera: &Camera,
+        offset: Vec2,
+    ) {
+        self.lerped_hitboxes
+            .borrow()
+            .draw(gfx, canvas, camera, offset, Color::GREEN)
+            .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::{FRAC_PI_2, FRAC_PI_4};
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 0.001
+    }
+
+    #[test]
+    fn rotated_boxes_only_collide_where_they_overlap() {
+        // A diamond, whose bounding box reaches well past its sides.
+        let diamond = Hitbox::oriented(Vec2::ZERO, Vec2::splat(10.0), FRAC_PI_4);
+        let corner = Hitbox::new(Rect::new(10.0, 10.0, 5.0, 5.0));
+        assert!(diamond.bounding_box().overlaps(&corner.bounding_box()));
+        assert!(!diamond.colliding_single(&corner, Vec2::ZERO, Vec2::ZERO));
+        assert!(!corner.colliding_single(&diamond, Vec2::ZERO, Vec2::ZERO));
+
+        assert!(diamond.colliding_single(&corner, Vec2::ZERO, Vec2::splat(-5.0)));
+        assert!(corner.colliding_single(&diamond, Vec2::splat(-5.0), Vec2::ZERO));
+    }
+
+    #[test]
+    fn touching_edges_collide() {
+        let left = Hitbox::new(Rect::new(0.0, 0.0, 10.0, 10.0));
+        let right = Hitbox::new(Rect::new(10.0, 0.0, 10.0, 10.0));
+        assert!(left.colliding_single(&right, Vec2::ZERO, Vec2::ZERO));
+        assert!(!left.colliding_single(&right, Vec2::ZERO, Vec2::new(0.1, 0.0)));
+
+        // The same, but through the separating axis test.
+        let (left, right) = (left.rotated(0.0), right.rotated(0.0));
+        assert!(left.colliding_single(&right, Vec2::ZERO, Vec2::ZERO));
+        assert!(!left.colliding_single(&right, Vec2::ZERO, Vec2::new(0.1, 0.0)));
+    }
+
+    #[test]
+    fn rotating_turns_boxes_around_the_origin() {
+        let hitbox = Hitbox::new(Rect::new(0.0, 0.0, 20.0, 10.0)).rotated(FRAC_PI_2);
+        let bounds = hitbox.bounding_box();
+        assert!(
+            close(Vec2::new(bounds.x, bounds.y), Vec2::new(-10.0, 0.0)),
+            "{bounds:?}"
+        );
+        assert!(
+            close(Vec2::new(bounds.w, bounds.h), Vec2::new(10.0, 20.0)),
+            "{bounds:?}"
+        );
+
+        // Turned clockwise on screen, so the box now hangs down from the origin instead of reaching right.
+        let below = Hitbox::point_size(Vec2::new(-5.0, 15.0), 2.0);
+        let right = Hitbox::point_size(Vec2::new(15.0, 5.0), 2.0);
+        assert!(hitbox.colliding_single(&below, Vec2::ZERO, Vec2::ZERO));
+        assert!(!hitbox.colliding_single(&right, Vec2::ZERO, Vec2::ZERO));
+    }
+
+    #[test]
+    fn boxes_without_size_still_collide() {
+        let point = Hitbox::oriented(Vec2::ZERO, Vec2::ZERO, 0.3);
+        let square = Hitbox::new(Rect::new(0.0, 0.0, 10.0, 10.0));
+        assert!(point.colliding_single(&square, Vec2::splat(5.0), Vec2::ZERO));
+        assert!(point.colliding_single(&square, Vec2::ZERO, Vec2::ZERO));
+        assert!(!point.colliding_single(&square, Vec2::splat(-1.0), Vec2::ZERO));
+    }
+}
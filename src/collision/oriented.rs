@@ -0,0 +1,104 @@
+use bevy_reflect::Reflect;
+use ggez::graphics::Rect;
+use glam::Vec2;
+
+/// A rectangle that can be rotated to any angle, rather than only being axis aligned.
+///
+/// Overlaps are found with the separating axis theorem: two convex shapes don't overlap
+/// if and only if there's an axis that their projections don't overlap on,
+/// and for boxes the only axes worth checking are the normals of their edges.
+#[derive(Debug, Default, Clone, Copy, Reflect, PartialEq)]
+pub struct OrientedBox {
+    #[reflect(ignore)]
+    pub center: Vec2,
+    /// Half of the width and height of the box, before it's rotated.
+    #[reflect(ignore)]
+    pub half_extents: Vec2,
+    /// Rotation around the center in radians. Positive angles turn clockwise on screen, to match [`Direction::to_angle`](crate::Direction::to_angle).
+    pub angle: f32,
+}
+
+impl OrientedBox {
+    pub const fn new(center: Vec2, half_extents: Vec2, angle: f32) -> Self {
+        Self {
+            center,
+            half_extents,
+            angle,
+        }
+    }
+
+    /// An unrotated box covering the rect.
+    pub fn from_rect(rect: Rect) -> Self {
+        let half_extents = Vec2::new(rect.w, rect.h) / 2.0;
+        Self::new(Vec2::new(rect.x, rect.y) + half_extents, half_extents, 0.0)
+    }
+
+    /// The local x and y axes of the box, which are also the normals of its edges.
+    pub fn axes(&self) -> [Vec2; 2] {
+        let x = Vec2::from_angle(self.angle);
+        [x, x.perp()]
+    }
+
+    pub fn corners(&self) -> [Vec2; 4] {
+        let [x, y] = self.axes();
+        let x = x * self.half_extents.x;
+        let y = y * self.half_extents.y;
+        [
+            self.center - x - y,
+            self.center + x - y,
+            self.center + x + y,
+            self.center - x + y,
+        ]
+    }
+
+    /// The smallest axis aligned rect that contains the whole box.
+    pub fn bounding_box(&self) -> Rect {
+        let [x, y] = self.axes();
+        let extents = (x * self.half_extents.x).abs() + (y * self.half_extents.y).abs();
+        let min = self.center - extents;
+        Rect::new(min.x, min.y, extents.x * 2.0, extents.y * 2.0)
+    }
+
+    /// Rotates the box around the origin, rather than around its own center.
+    pub fn rotated(&self, angle: f32) -> Self {
+        Self::new(
+            Vec2::from_angle(angle).rotate(self.center),
+            self.half_extents,
+            self.angle + angle,
+        )
+    }
+
+    pub fn lerp(&self, other: &OrientedBox, t: f32) -> Self {
+        Self::new(
+            self.center.lerp(other.center, t),
+            self.half_extents.lerp(other.half_extents, t),
+            self.angle + (other.angle - self.angle) * t,
+        )
+    }
+
+    pub fn overlaps(&self, other: &OrientedBox, offset: Vec2, other_offset: Vec2) -> bool {
+        let corners = self.corners().map(|corner| corner + offset);
+        let other_corners = other.corners().map(|corner| corner + other_offset);
+
+        self.axes()
+            .into_iter()
+            .chain(other.axes())
+            .all(|axis| projections_overlap(&corners, &other_corners, axis))
+    }
+}
+
+/// Projects both sets of points onto the axis, and checks whether the resulting ranges overlap.
+pub(crate) fn projections_overlap(a: &[Vec2], b: &[Vec2], axis: Vec2) -> bool {
+    let (a_min, a_max) = project(a, axis);
+    let (b_min, b_max) = project(b, axis);
+    a_min <= b_max && b_min <= a_max
+}
+
+pub(crate) fn project(points: &[Vec2], axis: Vec2) -> (f32, f32) {
+    points
+        .iter()
+        .map(|point| point.dot(axis))
+        .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), value| {
+            (min.min(value), max.max(value))
+        })
+}
@@ -0,0 +1,319 @@
+use super::shapes::edges;
+use super::{Hitbox, HitboxShape, Layers};
+use bevy_reflect::Reflect;
+use ggez::graphics::Rect;
+use glam::Vec2;
+
+/// A line starting at `origin`, going `length` pixels along `direction`.
+///
+/// Rays only hit hitboxes on one of the layers in their mask.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+    pub origin: Vec2,
+    /// Always a unit vector, or zero for a ray that can only hit things at its origin.
+    pub direction: Vec2,
+    pub length: f32,
+    pub mask: Layers,
+}
+
+/// Where a ray, or a hitbox cast along a ray, first hit something.
+#[derive(Debug, Default, Clone, Copy, Reflect, PartialEq)]
+pub struct RayHit {
+    /// Which hitbox of the frame was hit. Always 0 for singular hitboxes.
+    pub index: usize,
+    /// How far along the ray the hit was.
+    pub distance: f32,
+    /// Unit vector pointing out of the surface that was hit, back towards the ray.
+    ///
+    /// Zero if the ray started inside the hitbox.
+    #[reflect(ignore)]
+    pub normal: Vec2,
+    /// Where along the ray the hit was, in world space.
+    #[reflect(ignore)]
+    pub point: Vec2,
+}
+
+impl Ray {
+    pub fn new(origin: Vec2, direction: Vec2, length: f32) -> Self {
+        Self {
+            origin,
+            direction: direction.normalize_or_zero(),
+            length,
+            mask: Layers::ALL,
+        }
+    }
+
+    /// A ray from one point to another.
+    pub fn between(from: Vec2, to: Vec2) -> Self {
+        Self::new(from, to - from, from.distance(to))
+    }
+
+    pub const fn with_mask(mut self, mask: Layers) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    /// The point `distance` pixels along the ray.
+    pub fn at(&self, distance: f32) -> Vec2 {
+        self.origin + self.direction * distance
+    }
+
+    pub fn end(&self) -> Vec2 {
+        self.at(self.length)
+    }
+
+    pub fn bounding_box(&self) -> Rect {
+        let (origin, end) = (self.origin, self.end());
+        let min = origin.min(end);
+        let max = origin.max(end);
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+}
+
+impl RayHit {
+    pub(crate) fn with_index(self, index: usize) -> Self {
+        Self { index, ..self }
+    }
+
+    /// Picks whichever hit is closer to the start of the ray.
+    pub(crate) fn nearest(a: Option<RayHit>, b: Option<RayHit>) -> Option<RayHit> {
+        match (a, b) {
+            (Some(a), Some(b)) if b.distance < a.distance => Some(b),
+            (Some(a), _) => Some(a),
+            (None, b) => b,
+        }
+    }
+}
+
+/// Finds where the ray first hits the hitbox at the given offset, ignoring its filter.
+pub(crate) fn hitbox_raycast(hitbox: &Hitbox, ray: &Ray, offset: Vec2) -> Option<RayHit> {
+    let hit = match &hitbox.shape {
+        HitboxShape::Circle(circle) => ray_circle(ray, circle.center + offset, circle.radius),
+        // A capsule without any length is just a circle
+        HitboxShape::Capsule(capsule) if capsule.start == capsule.end => {
+            ray_circle(ray, capsule.start + offset, capsule.radius)
+        }
+        // A capsule is a box along its length, with a circle capping each end
+        HitboxShape::Capsule(capsule) => {
+            let (start, end) = (capsule.start + offset, capsule.end + offset);
+            let side = (end - start).perp().normalize_or_zero() * capsule.radius;
+            [
+                ray_circle(ray, start, capsule.radius),
+                ray_circle(ray, end, capsule.radius),
+                ray_polygon(ray, &[start + side, end + side, end - side, start - side]),
+            ]
+            .into_iter()
+            .fold(None, RayHit::nearest)
+        }
+        _ => {
+            let (core, _) = hitbox.core();
+            let core: Vec<Vec2> = core.into_iter().map(|point| point + offset).collect();
+            ray_polygon(ray, &core)
+        }
+    }?;
+    (hit.distance <= ray.length).then_some(hit)
+}
+
+fn ray_circle(ray: &Ray, center: Vec2, radius: f32) -> Option<RayHit> {
+    let to_origin = ray.origin - center;
+    let c = to_origin.length_squared() - radius * radius;
+    if c <= 0.0 {
+        return Some(inside(ray));
+    }
+    // Solves |origin + direction * distance - center| = radius for the nearest distance
+    let b = to_origin.dot(ray.direction);
+    let discriminant = b * b - c;
+    if b > 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    let distance = -b - discriminant.sqrt();
+    let point = ray.at(distance);
+    Some(RayHit {
+        distance,
+        normal: (point - center).normalize_or_zero(),
+        point,
+        ..Default::default()
+    })
+}
+
+/// Clips the ray against each edge of a convex polygon, keeping track of where it enters and leaves.
+fn ray_polygon(ray: &Ray, points: &[Vec2]) -> Option<RayHit> {
+    if points.len() < 3 {
+        return None;
+    }
+    let centroid = points.iter().sum::<Vec2>() / points.len() as f32;
+
+    let mut enter = 0.0;
+    let mut exit = ray.length;
+    let mut normal = Vec2::ZERO;
+    // Polygons with every point in the same place don't have any edges to hit
+    let mut clipped = false;
+    for (start, end) in edges(points) {
+        let Some(mut outward) = (end - start).perp().try_normalize() else {
+            continue;
+        };
+        clipped = true;
+        // Polygons can be wound either way, so make sure the normal points away from the middle
+        if outward.dot(start - centroid) < 0.0 {
+            outward = -outward;
+        }
+        let distance = outward.dot(start - ray.origin);
+        let speed = outward.dot(ray.direction);
+        if speed == 0.0 {
+            if distance < 0.0 {
+                return None;
+            }
+            continue;
+        }
+        let t = distance / speed;
+        if speed < 0.0 {
+            if t > enter {
+                enter = t;
+                normal = outward;
+            }
+        } else {
+            exit = exit.min(t);
+        }
+        if enter > exit {
+            return None;
+        }
+    }
+    if !clipped {
+        return None;
+    }
+
+    Some(RayHit {
+        distance: enter,
+        normal,
+        point: ray.at(enter),
+        ..Default::default()
+    })
+}
+
+fn inside(ray: &Ray) -> RayHit {
+    RayHit {
+        point: ray.origin,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::HitboxType;
+    use super::*;
+
+    fn wall() -> Hitbox {
+        Hitbox::new(Rect::new(10.0, -5.0, 10.0, 10.0))
+    }
+
+    #[test]
+    fn rays_hit_the_near_side() {
+        let ray = Ray::new(Vec2::ZERO, Vec2::X, 50.0);
+        let hit = wall().raycast(&ray, Vec2::ZERO).unwrap();
+        assert_eq!(hit.distance, 10.0);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+        assert_eq!(hit.point, Vec2::new(10.0, 0.0));
+
+        let hit = Hitbox::circle(Vec2::new(20.0, 0.0), 5.0)
+            .raycast(&ray, Vec2::ZERO)
+            .unwrap();
+        assert_eq!(hit.distance, 15.0);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+
+        let hit = Hitbox::capsule(Vec2::new(20.0, -10.0), Vec2::new(20.0, 10.0), 2.0)
+            .raycast(&ray, Vec2::ZERO)
+            .unwrap();
+        assert_eq!(hit.distance, 18.0);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+    }
+
+    #[test]
+    fn rays_stop_at_their_length() {
+        assert!(wall()
+            .raycast(&Ray::new(Vec2::ZERO, Vec2::X, 9.9), Vec2::ZERO)
+            .is_none());
+        assert!(wall()
+            .raycast(&Ray::new(Vec2::ZERO, Vec2::X, 10.0), Vec2::ZERO)
+            .is_some());
+        assert!(wall()
+            .raycast(&Ray::new(Vec2::ZERO, Vec2::NEG_X, 50.0), Vec2::ZERO)
+            .is_none());
+    }
+
+    #[test]
+    fn rays_starting_inside_hit_straight_away() {
+        let ray = Ray::new(Vec2::new(15.0, 0.0), Vec2::X, 50.0);
+        let hit = wall().raycast(&ray, Vec2::ZERO).unwrap();
+        assert_eq!(hit.distance, 0.0);
+        assert_eq!(hit.normal, Vec2::ZERO);
+
+        // Rays without a direction can only hit what they start in.
+        let still = Ray::new(Vec2::new(15.0, 0.0), Vec2::ZERO, 50.0);
+        assert!(wall().raycast(&still, Vec2::ZERO).is_some());
+        let still = Ray::new(Vec2::new(5.0, 0.0), Vec2::ZERO, 50.0);
+        assert!(wall().raycast(&still, Vec2::ZERO).is_none());
+    }
+
+    #[test]
+    fn rotated_boxes_are_hit_on_their_slanted_side() {
+        let diamond = Hitbox::oriented(
+            Vec2::new(20.0, 0.0),
+            Vec2::splat(5.0),
+            0.25 * std::f32::consts::PI,
+        );
+        let hit = diamond
+            .raycast(&Ray::new(Vec2::ZERO, Vec2::X, 50.0), Vec2::ZERO)
+            .unwrap();
+        assert!(
+            (hit.distance - (20.0 - 50.0f32.sqrt())).abs() < 0.001,
+            "{hit:?}"
+        );
+        assert!(hit.normal.x < 0.0 && hit.normal.y != 0.0, "{hit:?}");
+    }
+
+    #[test]
+    fn degenerate_shapes() {
+        let ray = Ray::new(Vec2::ZERO, Vec2::X, 50.0);
+        // A capsule without length is a circle.
+        let hit = Hitbox::capsule(Vec2::new(20.0, 0.0), Vec2::new(20.0, 0.0), 2.0)
+            .raycast(&ray, Vec2::ZERO)
+            .unwrap();
+        assert_eq!(hit.distance, 18.0);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+
+        // A polygon with every point in one place has no edges to hit.
+        let point = Vec2::new(20.0, 0.0);
+        assert!(Hitbox::polygon(&[point, point, point])
+            .raycast(&ray, Vec2::ZERO)
+            .is_none());
+    }
+
+    #[test]
+    fn shape_casts_stop_where_the_shape_touches() {
+        let ball = Hitbox::circle(Vec2::ZERO, 2.0);
+        let ray = Ray::new(Vec2::ZERO, Vec2::X, 50.0);
+        let target = wall();
+        let hit = ball
+            .shape_cast(&ray, HitboxType::Singular(&target), Vec2::ZERO)
+            .unwrap();
+        assert!((hit.distance - 8.0).abs() < 0.01, "{hit:?}");
+        assert!(hit.point.distance(Vec2::new(8.0, 0.0)) < 0.01, "{hit:?}");
+        assert!(hit.normal.distance(Vec2::NEG_X) < 0.01, "{hit:?}");
+    }
+
+    #[test]
+    fn nearest_hits_win() {
+        let near = RayHit {
+            distance: 1.0,
+            ..Default::default()
+        };
+        let far = RayHit {
+            distance: 2.0,
+            index: 1,
+            ..Default::default()
+        };
+        assert_eq!(RayHit::nearest(Some(far), Some(near)), Some(near));
+        assert_eq!(RayHit::nearest(Some(near), Some(far)), Some(near));
+        assert_eq!(RayHit::nearest(None, Some(far)), Some(far));
+    }
+}
@@ -0,0 +1,397 @@
+use super::{max, min};
+use bevy_reflect::Reflect;
+use ggez::graphics::Rect;
+use glam::Vec2;
+use std::f32::consts::PI;
+
+/// How many points are used to draw round edges.
+pub(crate) static ROUND_SEGMENTS: usize = 24;
+
+#[derive(Debug, Default, Clone, Copy, Reflect, PartialEq)]
+pub struct Circle {
+    #[reflect(ignore)]
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+/// A line segment with a radius, like a pill. Useful for swept attacks like sword arcs.
+#[derive(Debug, Default, Clone, Copy, Reflect, PartialEq)]
+pub struct Capsule {
+    #[reflect(ignore)]
+    pub start: Vec2,
+    #[reflect(ignore)]
+    pub end: Vec2,
+    pub radius: f32,
+}
+
+/// The most points a [`ConvexPolygon`] can have. Polygons are stored inline, so that hitboxes can be used in statics.
+pub const MAX_POLYGON_POINTS: usize = 8;
+
+/// A convex polygon. The points can be wound either way, but must not form any dents,
+/// since overlap tests assume that the shape is convex.
+#[derive(Debug, Default, Clone, Copy, Reflect, PartialEq)]
+pub struct ConvexPolygon {
+    #[reflect(ignore)]
+    points: [Vec2; MAX_POLYGON_POINTS],
+    len: usize,
+}
+
+impl Circle {
+    pub const fn new(center: Vec2, radius: f32) -> Self {
+        Self { center, radius }
+    }
+
+    pub const fn bounding_box(&self) -> Rect {
+        Rect {
+            x: self.center.x - self.radius,
+            y: self.center.y - self.radius,
+            w: self.radius * 2.0,
+            h: self.radius * 2.0,
+        }
+    }
+
+    pub fn outline(&self) -> Vec<Vec2> {
+        arc(self.center, self.radius, 0.0, 2.0 * PI)
+    }
+}
+
+impl Capsule {
+    pub const fn new(start: Vec2, end: Vec2, radius: f32) -> Self {
+        Self { start, end, radius }
+    }
+
+    pub const fn bounding_box(&self) -> Rect {
+        let x = min(self.start.x, self.end.x) - self.radius;
+        let y = min(self.start.y, self.end.y) - self.radius;
+        Rect {
+            x,
+            y,
+            w: max(self.start.x, self.end.x) + self.radius - x,
+            h: max(self.start.y, self.end.y) + self.radius - y,
+        }
+    }
+
+    pub fn outline(&self) -> Vec<Vec2> {
+        let angle = Vec2::X.angle_between(self.end - self.start);
+        let angle = if angle.is_nan() { 0.0 } else { angle };
+        let mut outline = arc(self.end, self.radius, angle - PI / 2.0, PI);
+        outline.extend(arc(self.start, self.radius, angle + PI / 2.0, PI));
+        outline
+    }
+}
+
+impl ConvexPolygon {
+    /// Panics if there are more than [`MAX_POLYGON_POINTS`] points.
+    pub const fn new(points: &[Vec2]) -> Self {
+        assert!(
+            points.len() <= MAX_POLYGON_POINTS,
+            "Too many points for a ConvexPolygon"
+        );
+        let mut stored = [Vec2::ZERO; MAX_POLYGON_POINTS];
+        let mut i = 0;
+        while i < points.len() {
+            stored[i] = points[i];
+            i += 1;
+        }
+        Self {
+            points: stored,
+            len: points.len(),
+        }
+    }
+
+    pub fn points(&self) -> &[Vec2] {
+        &self.points[..self.len]
+    }
+
+    pub const fn points_mut(&mut self) -> &mut [Vec2] {
+        self.points.split_at_mut(self.len).0
+    }
+
+    pub fn bounding_box(&self) -> Rect {
+        if self.len == 0 {
+            return Rect::zero();
+        }
+        let min = self
+            .points()
+            .iter()
+            .fold(Vec2::splat(f32::INFINITY), |a, b| a.min(*b));
+        let max = self
+            .points()
+            .iter()
+            .fold(Vec2::splat(f32::NEG_INFINITY), |a, b| a.max(*b));
+        Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+    }
+}
+
+/// Points along an arc, starting at `start` radians and sweeping clockwise on screen.
+fn arc(center: Vec2, radius: f32, start: f32, sweep: f32) -> Vec<Vec2> {
+    let segments = ((ROUND_SEGMENTS as f32 * sweep / (2.0 * PI)).ceil() as usize).max(1);
+    (0..=segments)
+        .map(|i| center + Vec2::from_angle(start + sweep * i as f32 / segments as f32) * radius)
+        .collect()
+}
+
+/// Shortest distance between two convex shapes, each given as the points of their outline.
+///
+/// One point is treated as a point, two points as a line segment, and anything more as a polygon.
+/// Returns 0 if the shapes overlap.
+///
+/// Every shape can be described as one of these with a radius around it,
+/// so two shapes overlap if the distance is no more than their radii added together.
+pub(crate) fn convex_distance(a: &[Vec2], b: &[Vec2]) -> f32 {
+    closest_points(a, b).map_or(0.0, |(a, b)| a.distance(b))
+}
+
+/// The closest pair of points between two convex shapes, laid out like in [`convex_distance`].
+///
+/// Returns `None` if the shapes overlap, since there's no single closest pair.
+pub(crate) fn closest_points(a: &[Vec2], b: &[Vec2]) -> Option<(Vec2, Vec2)> {
+    if b.iter().any(|&point| contains_point(a, point))
+        || a.iter().any(|&point| contains_point(b, point))
+    {
+        return None;
+    }
+
+    let mut closest: Option<(Vec2, Vec2)> = None;
+    for (a_start, a_end) in edges(a) {
+        for (b_start, b_end) in edges(b) {
+            let pair = segment_closest_points(a_start, a_end, b_start, b_end)?;
+            if closest.is_none_or(|(a, b)| pair.0.distance_squared(pair.1) < a.distance_squared(b))
+            {
+                closest = Some(pair);
+            }
+        }
+    }
+    closest
+}
+
+/// The axes worth checking when finding how far two overlapping shapes need to move to separate.
+pub(crate) fn separating_axes(points: &[Vec2]) -> Vec<Vec2> {
+    match points.len() {
+        0 | 1 => Vec::new(),
+        2 => match (points[1] - points[0]).try_normalize() {
+            Some(direction) => vec![direction, direction.perp()],
+            None => Vec::new(),
+        },
+        _ => edges(points)
+            .filter_map(|(start, end)| (end - start).perp().try_normalize())
+            .collect(),
+    }
+}
+
+/// The smallest convex polygon containing every point.
+pub(crate) fn convex_hull(points: &[Vec2]) -> Vec<Vec2> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    // Andrew's monotone chain, building the lower then upper half of the hull
+    let mut hull: Vec<Vec2> = Vec::with_capacity(points.len() * 2);
+    for pass in 0..2 {
+        let start = hull.len();
+        for &point in points.iter() {
+            while hull.len() >= start + 2 {
+                let (a, b) = (hull[hull.len() - 2], hull[hull.len() - 1]);
+                if (b - a).perp_dot(point - a) > 0.0 {
+                    break;
+                }
+                hull.pop();
+            }
+            hull.push(point);
+        }
+        hull.pop();
+        if pass == 0 {
+            points.reverse();
+        }
+    }
+    hull
+}
+
+/// Every edge of the shape. Points and segments have a single edge, which may be zero length.
+pub(crate) fn edges(points: &[Vec2]) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+    let count = match points.len() {
+        0 => 0,
+        1 | 2 => 1,
+        len => len,
+    };
+    (0..count).map(move |i| (points[i], points[(i + 1) % points.len()]))
+}
+
+/// True if the point is inside the polygon. Points and segments have no inside, so this is always false for them.
+fn contains_point(polygon: &[Vec2], point: Vec2) -> bool {
+    if polygon.len() < 3 {
+        return false;
+    }
+    let mut sign = 0.0;
+    for (start, end) in edges(polygon) {
+        let cross = (end - start).perp_dot(point - start);
+        if cross == 0.0 {
+            continue;
+        }
+        if sign == 0.0 {
+            sign = cross.signum();
+        } else if cross.signum() != sign {
+            return false;
+        }
+    }
+    true
+}
+
+pub(crate) fn closest_point_on_segment(point: Vec2, start: Vec2, end: Vec2) -> Vec2 {
+    let line = end - start;
+    let length_squared = line.length_squared();
+    if length_squared == 0.0 {
+        return start;
+    }
+    let t = ((point - start).dot(line) / length_squared).clamp(0.0, 1.0);
+    start + line * t
+}
+
+/// The closest pair of points between two line segments, or `None` if they cross.
+fn segment_closest_points(
+    a_start: Vec2,
+    a_end: Vec2,
+    b_start: Vec2,
+    b_end: Vec2,
+) -> Option<(Vec2, Vec2)> {
+    if segments_intersect(a_start, a_end, b_start, b_end) {
+        return None;
+    }
+    [
+        (a_start, closest_point_on_segment(a_start, b_start, b_end)),
+        (a_end, closest_point_on_segment(a_end, b_start, b_end)),
+        (closest_point_on_segment(b_start, a_start, a_end), b_start),
+        (closest_point_on_segment(b_end, a_start, a_end), b_end),
+    ]
+    .into_iter()
+    .min_by(|(a1, b1), (a2, b2)| {
+        a1.distance_squared(*b1)
+            .total_cmp(&a2.distance_squared(*b2))
+    })
+}
+
+fn segments_intersect(a_start: Vec2, a_end: Vec2, b_start: Vec2, b_end: Vec2) -> bool {
+    let a = a_end - a_start;
+    let b = b_end - b_start;
+    let denominator = a.perp_dot(b);
+    // Parallel segments that touch are caught by the endpoint distance checks instead
+    if denominator == 0.0 {
+        return false;
+    }
+    let t = (b_start - a_start).perp_dot(b) / denominator;
+    let u = (b_start - a_start).perp_dot(a) / denominator;
+    (0.0..=1.0).contains(&t) && (0.0..=1.0).contains(&u)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Hitbox;
+    use super::*;
+
+    #[test]
+    fn hulls_drop_inner_duplicate_and_straight_points() {
+        let square = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(0.0, 10.0),
+        ];
+        let mut points = square.to_vec();
+        points.extend([
+            Vec2::new(5.0, 5.0),
+            Vec2::new(10.0, 10.0),
+            Vec2::new(5.0, 0.0),
+        ]);
+        let hull = convex_hull(&points);
+        assert_eq!(hull.len(), 4, "{hull:?}");
+        assert!(
+            square.iter().all(|corner| hull.contains(corner)),
+            "{hull:?}"
+        );
+    }
+
+    #[test]
+    fn hulls_of_lines_and_points_are_their_ends() {
+        let line = [
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(5.0, 0.0),
+        ];
+        assert_eq!(
+            convex_hull(&line),
+            vec![Vec2::new(0.0, 0.0), Vec2::new(10.0, 0.0)]
+        );
+        assert_eq!(convex_hull(&[Vec2::ONE, Vec2::ONE]), vec![Vec2::ONE]);
+        assert_eq!(convex_hull(&[]), Vec::<Vec2>::new());
+    }
+
+    #[test]
+    fn distances_between_cores() {
+        let point = [Vec2::new(0.0, 5.0)];
+        let segment = [Vec2::new(-10.0, 0.0), Vec2::new(10.0, 0.0)];
+        let square = [
+            Vec2::new(-1.0, -1.0),
+            Vec2::new(1.0, -1.0),
+            Vec2::new(1.0, 1.0),
+            Vec2::new(-1.0, 1.0),
+        ];
+        assert_eq!(convex_distance(&point, &segment), 5.0);
+        assert_eq!(convex_distance(&segment, &point), 5.0);
+        assert_eq!(convex_distance(&point, &square), 4.0);
+        // Crossing or inside each other is no distance at all.
+        assert_eq!(convex_distance(&segment, &square), 0.0);
+        assert_eq!(convex_distance(&[Vec2::ZERO], &square), 0.0);
+        // A segment without length is a point.
+        assert_eq!(convex_distance(&[point[0], point[0]], &segment), 5.0);
+    }
+
+    #[test]
+    fn round_shapes_collide_when_their_edges_touch() {
+        let circle = Hitbox::circle(Vec2::ZERO, 5.0);
+        assert!(circle.colliding_single(&circle, Vec2::ZERO, Vec2::new(10.0, 0.0)));
+        assert!(!circle.colliding_single(&circle, Vec2::ZERO, Vec2::new(10.1, 0.0)));
+
+        let capsule = Hitbox::capsule(Vec2::ZERO, Vec2::new(20.0, 0.0), 2.0);
+        let small = Hitbox::circle(Vec2::ZERO, 3.0);
+        assert!(capsule.colliding_single(&small, Vec2::ZERO, Vec2::new(10.0, 5.0)));
+        assert!(!capsule.colliding_single(&small, Vec2::ZERO, Vec2::new(10.0, 5.5)));
+        // Past the end, the capsule is round rather than square.
+        assert!(!capsule.colliding_single(&small, Vec2::ZERO, Vec2::new(24.0, 4.0)));
+    }
+
+    #[test]
+    fn polygons_collide_with_every_shape() {
+        let triangle = Hitbox::polygon(&[
+            Vec2::new(0.0, 0.0),
+            Vec2::new(10.0, 0.0),
+            Vec2::new(0.0, 10.0),
+        ]);
+        let circle = Hitbox::circle(Vec2::ZERO, 1.0);
+        let square = Hitbox::new(Rect::new(0.0, 0.0, 2.0, 2.0));
+        // Just past the slanted edge, but inside the bounding box.
+        assert!(!triangle.colliding_single(&circle, Vec2::ZERO, Vec2::splat(6.0)));
+        assert!(triangle.colliding_single(&circle, Vec2::ZERO, Vec2::splat(5.5)));
+        assert!(!triangle.colliding_single(&square, Vec2::ZERO, Vec2::splat(5.5)));
+        assert!(triangle.colliding_single(&square, Vec2::ZERO, Vec2::splat(4.0)));
+    }
+
+    #[test]
+    fn degenerate_shapes_act_like_points_and_lines() {
+        let square = Hitbox::new(Rect::new(0.0, 0.0, 10.0, 10.0));
+        let point = Hitbox::circle(Vec2::ZERO, 0.0);
+        assert!(point.colliding_single(&square, Vec2::splat(5.0), Vec2::ZERO));
+        assert!(!point.colliding_single(&square, Vec2::splat(11.0), Vec2::ZERO));
+
+        let stub = Hitbox::capsule(Vec2::ZERO, Vec2::ZERO, 2.0);
+        let circle = Hitbox::circle(Vec2::ZERO, 2.0);
+        assert!(stub.colliding_single(&circle, Vec2::ZERO, Vec2::new(4.0, 0.0)));
+        assert!(!stub.colliding_single(&circle, Vec2::ZERO, Vec2::new(4.1, 0.0)));
+
+        let flat = Hitbox::polygon(&[Vec2::ZERO, Vec2::new(10.0, 0.0), Vec2::new(5.0, 0.0)]);
+        assert!(flat.colliding_single(&square, Vec2::new(0.0, 10.0), Vec2::ZERO));
+        assert!(!flat.colliding_single(&square, Vec2::new(0.0, 11.0), Vec2::ZERO));
+    }
+}
@@ -0,0 +1,284 @@
+use super::{Hitbox, HitboxShape};
+use bevy_reflect::Reflect;
+use ggez::graphics::Rect;
+use glam::Vec2;
+
+/// How many times a sweep halves the gap between a miss and a hit, once it's found roughly where the hitboxes meet.
+static REFINE_STEPS: usize = 8;
+
+/// When a moving hitbox first touches another, found by sweeping it from one position to another.
+///
+/// Returned by the `sweep` family of functions, which catch hits that a fast hitbox would otherwise skip straight over.
+#[derive(Debug, Default, Clone, Copy, Reflect, PartialEq)]
+pub struct Sweep {
+    /// Which hitbox of the first frame touched. Always 0 for singular hitboxes.
+    pub index: usize,
+    /// Which hitbox of the other frame touched. Always 0 for singular hitboxes.
+    pub other_index: usize,
+    /// How far along the movement the hitboxes first touched, from 0 at the start to 1 at the end.
+    pub time: f32,
+    /// Unit vector pointing from the other hitbox towards the moving one, like [`Contact::normal`](super::Contact::normal).
+    ///
+    /// Zero if they were already overlapping at the start of the movement.
+    #[reflect(ignore)]
+    pub normal: Vec2,
+}
+
+impl Sweep {
+    /// Where the moving hitbox's offset was when it first touched.
+    pub fn position(&self, from: Vec2, to: Vec2) -> Vec2 {
+        from.lerp(to, self.time)
+    }
+
+    pub(crate) fn with_indices(self, index: usize, other_index: usize) -> Self {
+        Self {
+            index,
+            other_index,
+            ..self
+        }
+    }
+
+    /// Picks whichever sweep hit first.
+    pub(crate) fn earliest(a: Option<Sweep>, b: Option<Sweep>) -> Option<Sweep> {
+        match (a, b) {
+            (Some(a), Some(b)) if b.time < a.time => Some(b),
+            (Some(a), _) => Some(a),
+            (None, b) => b,
+        }
+    }
+}
+
+/// Swept AABB: when `rect` first touches `other` while moving by `motion`, if it does at all.
+pub fn swept_aabb(rect: Rect, motion: Vec2, other: Rect) -> Option<Sweep> {
+    if rect.overlaps(&other) {
+        return Some(Sweep::default());
+    }
+
+    let mut entry = f32::NEG_INFINITY;
+    let mut exit = f32::INFINITY;
+    let mut normal = Vec2::ZERO;
+    let axes = [
+        (rect.x, rect.w, other.x, other.w, motion.x, Vec2::X),
+        (rect.y, rect.h, other.y, other.h, motion.y, Vec2::Y),
+    ];
+    for (start, size, other_start, other_size, motion, axis) in axes {
+        if motion == 0.0 {
+            if start + size < other_start || start > other_start + other_size {
+                return None;
+            }
+            continue;
+        }
+        let (near, far) = if motion > 0.0 {
+            (
+                other_start - (start + size),
+                other_start + other_size - start,
+            )
+        } else {
+            (
+                other_start + other_size - start,
+                other_start - (start + size),
+            )
+        };
+        let (near, far) = (near / motion, far / motion);
+        if near > entry {
+            entry = near;
+            normal = axis * -motion.signum();
+        }
+        exit = exit.min(far);
+    }
+
+    if entry > exit || !(0.0..=1.0).contains(&entry) {
+        return None;
+    }
+    Some(Sweep {
+        time: entry,
+        normal,
+        ..Default::default()
+    })
+}
+
+/// The smallest axis aligned rect covering the rect at both offsets.
+pub fn swept_bounding_box(rect: Rect, from: Vec2, to: Vec2) -> Rect {
+    let min = Vec2::new(rect.x, rect.y) + from.min(to);
+    let max = Vec2::new(rect.x + rect.w, rect.y + rect.h) + from.max(to);
+    Rect::new(min.x, min.y, max.x - min.x, max.y - min.y)
+}
+
+/// Finds when a hitbox moving from `from` to `to` first touches the other hitbox.
+///
+/// Pairs of axis aligned boxes are solved exactly with [`swept_aabb`]. Every other pair is stepped along
+/// the movement in steps no longer than half of either hitbox, so that neither can be skipped over,
+/// then narrowed down to where they first touched.
+pub(crate) fn hitbox_sweep(
+    hitbox: &Hitbox,
+    other: &Hitbox,
+    from: Vec2,
+    to: Vec2,
+    other_offset: Vec2,
+) -> Option<Sweep> {
+    let offset_box = |b: Rect, o: Vec2| Rect::new(b.x + o.x, b.y + o.y, b.w, b.h);
+    let broad = swept_aabb(
+        offset_box(hitbox.rect, from),
+        to - from,
+        offset_box(other.rect, other_offset),
+    )?;
+    if let (HitboxShape::Rect, HitboxShape::Rect) = (&hitbox.shape, &other.shape) {
+        return Some(broad);
+    }
+
+    let colliding = |time: f32| hitbox.colliding_single(other, from.lerp(to, time), other_offset);
+    let smallest = hitbox
+        .rect
+        .w
+        .min(hitbox.rect.h)
+        .min(other.rect.w)
+        .min(other.rect.h);
+    let length = from.distance(to);
+    let step = if length > 0.0 {
+        (smallest / 2.0).max(1.0) / length
+    } else {
+        1.0
+    };
+
+    let mut missed = None;
+    let mut time = broad.time;
+    let hit = loop {
+        if colliding(time) {
+            break time;
+        }
+        if time >= 1.0 {
+            return None;
+        }
+        missed = Some(time);
+        time = (time + step).min(1.0);
+    };
+
+    let Some(mut missed) = missed else {
+        let normal = if hit == 0.0 { Vec2::ZERO } else { broad.normal };
+        return Some(Sweep {
+            time: hit,
+            normal,
+            ..Default::default()
+        });
+    };
+    let mut hit = hit;
+    for _ in 0..REFINE_STEPS {
+        let middle = (missed + hit) / 2.0;
+        if colliding(middle) {
+            hit = middle;
+        } else {
+            missed = middle;
+        }
+    }
+
+    let normal = hitbox
+        .contact_single(other, from.lerp(to, hit), other_offset)
+        .map_or(broad.normal, |contact| contact.normal);
+    Some(Sweep {
+        time: hit,
+        normal,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn square(x: f32, y: f32, size: f32) -> Rect {
+        Rect::new(x, y, size, size)
+    }
+
+    #[test]
+    fn boxes_stop_where_they_first_touch() {
+        let sweep = swept_aabb(
+            square(0.0, 0.0, 10.0),
+            Vec2::new(20.0, 0.0),
+            square(25.0, 0.0, 10.0),
+        )
+        .unwrap();
+        assert_eq!(sweep.time, 0.75);
+        assert_eq!(sweep.normal, Vec2::NEG_X);
+        assert_eq!(
+            sweep.position(Vec2::ZERO, Vec2::new(20.0, 0.0)),
+            Vec2::new(15.0, 0.0)
+        );
+
+        // Coming down from above instead.
+        let sweep = swept_aabb(
+            square(25.0, -30.0, 10.0),
+            Vec2::new(0.0, 40.0),
+            square(25.0, 0.0, 10.0),
+        )
+        .unwrap();
+        assert_eq!(sweep.time, 0.5);
+        assert_eq!(sweep.normal, Vec2::NEG_Y);
+    }
+
+    #[test]
+    fn boxes_that_never_reach_are_missed() {
+        let rect = square(0.0, 0.0, 10.0);
+        let other = square(25.0, 0.0, 10.0);
+        // Too short, going the wrong way, and passing by to the side.
+        assert_eq!(swept_aabb(rect, Vec2::new(10.0, 0.0), other), None);
+        assert_eq!(swept_aabb(rect, Vec2::new(-20.0, 0.0), other), None);
+        assert_eq!(
+            swept_aabb(square(0.0, 20.0, 10.0), Vec2::new(40.0, 0.0), other),
+            None
+        );
+    }
+
+    #[test]
+    fn sweeps_without_motion_only_hit_what_they_start_in() {
+        let rect = square(0.0, 0.0, 10.0);
+        assert_eq!(swept_aabb(rect, Vec2::ZERO, square(20.0, 0.0, 10.0)), None);
+        // Touching counts as overlapping.
+        let sweep = swept_aabb(rect, Vec2::ZERO, square(10.0, 0.0, 10.0)).unwrap();
+        assert_eq!(sweep.time, 0.0);
+        assert_eq!(sweep.normal, Vec2::ZERO);
+
+        let circle = Hitbox::circle(Vec2::ZERO, 5.0);
+        assert!(circle
+            .sweep_single(&circle, Vec2::ZERO, Vec2::ZERO, Vec2::new(20.0, 0.0))
+            .is_none());
+        let sweep = circle
+            .sweep_single(&circle, Vec2::ZERO, Vec2::ZERO, Vec2::new(5.0, 0.0))
+            .unwrap();
+        assert_eq!(sweep.time, 0.0);
+        assert_eq!(sweep.normal, Vec2::ZERO);
+    }
+
+    #[test]
+    fn round_shapes_are_narrowed_down_to_first_touch() {
+        let circle = Hitbox::circle(Vec2::ZERO, 5.0);
+        let sweep = circle
+            .sweep_single(
+                &circle,
+                Vec2::ZERO,
+                Vec2::new(100.0, 0.0),
+                Vec2::new(50.0, 0.0),
+            )
+            .unwrap();
+        // The centers end up 10 apart, 40 pixels along.
+        assert!((sweep.time - 0.4).abs() < 0.001, "{sweep:?}");
+        assert!(sweep.normal.distance(Vec2::NEG_X) < 0.01, "{sweep:?}");
+    }
+
+    #[test]
+    fn fast_hitboxes_dont_skip_thin_walls() {
+        let wall = Hitbox::new(Rect::new(50.0, -10.0, 1.0, 20.0));
+        let bullet = Hitbox::new(square(0.0, 0.0, 2.0));
+        let sweep = bullet
+            .sweep_single(&wall, Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::ZERO)
+            .unwrap();
+        assert_eq!(sweep.time, 0.48);
+        assert_eq!(sweep.normal, Vec2::NEG_X);
+
+        // Rotated, so it has to be stepped along rather than solved exactly.
+        let bullet = Hitbox::oriented(Vec2::ZERO, Vec2::ONE, 0.5);
+        let sweep = bullet
+            .sweep_single(&wall, Vec2::ZERO, Vec2::new(100.0, 0.0), Vec2::ZERO)
+            .unwrap();
+        assert!(sweep.time > 0.45 && sweep.time < 0.5, "{sweep:?}");
+    }
+}
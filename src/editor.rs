@@ -0,0 +1,455 @@
+use crate::assets::hitboxes::HitboxAnimationData;
+use crate::camera::Camera;
+use crate::collision::{Hitbox, HitboxAnimation};
+use crate::level::Level;
+use ggez::graphics::{Canvas, Color, DrawParam, Mesh, Quad, Text};
+use ggez::input::keyboard::{KeyCode, KeyMods};
+use ggez::input::mouse::MouseButton;
+use ggez::Context;
+use glam::Vec2;
+
+/// Where hitbox files are looked for in the resources folder.
+static HITBOX_FOLDER: &str = "/hitboxes";
+
+pub(crate) static TOGGLE_KEY: KeyCode = KeyCode::F1;
+static NEXT_FILE_KEY: KeyCode = KeyCode::Tab;
+static PREVIOUS_KEYFRAME_KEY: KeyCode = KeyCode::Left;
+static NEXT_KEYFRAME_KEY: KeyCode = KeyCode::Right;
+static LONGER_INTERVAL_KEY: KeyCode = KeyCode::Up;
+static SHORTER_INTERVAL_KEY: KeyCode = KeyCode::Down;
+static MORE_TWINE_KEY: KeyCode = KeyCode::Equals;
+static LESS_TWINE_KEY: KeyCode = KeyCode::Minus;
+static PLAY_KEY: KeyCode = KeyCode::Space;
+static SCRUB_BACK_KEY: KeyCode = KeyCode::Comma;
+static SCRUB_FORWARD_KEY: KeyCode = KeyCode::Period;
+/// Saves while control is held.
+static SAVE_KEY: KeyCode = KeyCode::S;
+
+static TWINE_STEP: f32 = 0.1;
+/// Length of each arm of the cross drawn at the pivot.
+static PIVOT_SIZE: f32 = 12.0;
+
+static HELP: &str = "left/right: keyframe   up/down: interval   -/=: twine\n\
+    space: play   ,/.: scrub   tab: next file   ctrl+s: save\n\
+    left drag: move hitbox   right drag: resize hitbox";
+
+/// A debug build tool for authoring the hitbox files in `resources/hitboxes`, see [`HitboxAnimationData`].
+///
+/// While it's open, the level is paused, and the current file is shown one keyframe at a time around a pivot
+/// in the middle of the screen, which stands in for whatever the hitboxes are attached to.
+/// Hitboxes can be dragged around and resized with the mouse, and the file can be played back
+/// the same way it would be in game.
+#[derive(Debug, Default)]
+pub struct HitboxEditor {
+    pub open: bool,
+    /// Every file in [`HITBOX_FOLDER`], found the first time the editor is opened.
+    files: Vec<String>,
+    /// Index into [`HitboxEditor::files`] of the file being edited.
+    file: usize,
+    /// Missing if the file couldn't be loaded.
+    data: Option<HitboxAnimationData>,
+    /// True if there are edits that haven't been saved yet.
+    unsaved: bool,
+    keyframe: usize,
+    /// Index of the hitbox in the current keyframe that the mouse is working on.
+    selected: Option<usize>,
+    drag: Option<Drag>,
+    /// While previewing, the hitboxes can't be edited.
+    preview: Option<Preview>,
+    /// Shown under the status, like why a file couldn't be loaded or saved.
+    message: String,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Drag {
+    /// Moves the hitbox along with the mouse, from where the mouse was last frame.
+    Move {
+        from: Vec2,
+    },
+    Resize,
+}
+
+/// Plays the file back the way it would be in game.
+#[derive(Debug)]
+struct Preview {
+    /// Files without intervals are hitbox frame strings, which are played one keyframe per frame, like the sword swing.
+    animation: Option<HitboxAnimation>,
+    step: usize,
+    /// How many frames it takes to play the whole file.
+    duration: usize,
+    playing: bool,
+}
+
+impl HitboxEditor {
+    /// Handles opening and closing the editor, and all of its controls while it's open.
+    pub fn update(level: &mut Level, ctx: &mut Context) {
+        let editor = &mut level.editor;
+
+        if cfg!(debug_assertions) && ctx.keyboard.is_key_just_pressed(TOGGLE_KEY) {
+            editor.open = !editor.open;
+            if editor.open && editor.files.is_empty() {
+                editor.find_files(ctx);
+            }
+            return;
+        }
+        if !editor.open {
+            return;
+        }
+
+        let keyboard = &ctx.keyboard;
+        if keyboard.is_key_just_pressed(NEXT_FILE_KEY) && !editor.files.is_empty() {
+            editor.file = (editor.file + 1) % editor.files.len();
+            editor.load(ctx);
+            return;
+        }
+        if keyboard.is_mod_active(KeyMods::CTRL) && keyboard.is_key_just_pressed(SAVE_KEY) {
+            editor.save();
+            return;
+        }
+        if keyboard.is_key_just_pressed(PLAY_KEY) {
+            if let Some(preview) = editor.preview() {
+                preview.playing = !preview.playing;
+            }
+        } else if keyboard.is_key_just_pressed(SCRUB_BACK_KEY) {
+            editor.scrub(-1);
+        } else if keyboard.is_key_just_pressed(SCRUB_FORWARD_KEY) {
+            editor.scrub(1);
+        }
+
+        let Some(data) = editor.data.as_mut() else {
+            return;
+        };
+        let frames = data.frames.len();
+        if keyboard.is_key_just_pressed(PREVIOUS_KEYFRAME_KEY) {
+            editor.keyframe = (editor.keyframe + frames - 1) % frames;
+            editor.selected = None;
+            editor.preview = None;
+        } else if keyboard.is_key_just_pressed(NEXT_KEYFRAME_KEY) {
+            editor.keyframe = (editor.keyframe + 1) % frames;
+            editor.selected = None;
+            editor.preview = None;
+        } else if let Some(interval) = data.intervals.get_mut(editor.keyframe) {
+            if keyboard.is_key_just_pressed(LONGER_INTERVAL_KEY) {
+                *interval += 1;
+                editor.edited();
+            } else if keyboard.is_key_just_pressed(SHORTER_INTERVAL_KEY) {
+                *interval = interval.saturating_sub(1);
+                editor.edited();
+            }
+        }
+
+        let Some(data) = editor.data.as_mut() else {
+            return;
+        };
+        if keyboard.is_key_just_pressed(MORE_TWINE_KEY) {
+            data.twine = (data.twine + TWINE_STEP).clamp(0.0, 1.0);
+            editor.edited();
+        } else if keyboard.is_key_just_pressed(LESS_TWINE_KEY) {
+            data.twine = (data.twine - TWINE_STEP).clamp(0.0, 1.0);
+            editor.edited();
+        }
+
+        match editor.preview.as_mut() {
+            Some(preview) if preview.playing => preview.seek(preview.step + 1),
+            Some(_) => {}
+            None => editor.drag_hitboxes(ctx),
+        }
+    }
+
+    /// Draws the editor over the top of everything, if it's open.
+    pub fn draw(level: &Level, ctx: &mut Context, canvas: &mut Canvas) {
+        let editor = &level.editor;
+        if !editor.open {
+            return;
+        }
+
+        let (width, height) = ctx.gfx.drawable_size();
+        let pivot = Vec2::new(width, height) / 2.0;
+        // Hitboxes are drawn at the same depth, so the backdrop only covers the ones drawn before it
+        canvas.draw(
+            &Quad,
+            DrawParam::new()
+                .scale(Vec2::new(width, height))
+                .color(Color::new(0.0, 0.0, 0.0, 0.8))
+                .z(999),
+        );
+        for arm in [Vec2::X, Vec2::Y] {
+            let line = [pivot - arm * PIVOT_SIZE, pivot + arm * PIVOT_SIZE];
+            canvas.draw(
+                &Mesh::new_line(&ctx.gfx, &line, 2.0, Color::WHITE).unwrap(),
+                DrawParam::new().z(999),
+            );
+        }
+
+        if let Some(data) = &editor.data {
+            match &editor.preview {
+                Some(Preview {
+                    animation: Some(animation),
+                    ..
+                }) => animation.draw(&mut ctx.gfx, canvas, &Camera::SCREEN, pivot),
+                Some(preview) => editor.draw_keyframe(ctx, canvas, data, preview.step, pivot),
+                None => {
+                    // The previous keyframe is shown faintly behind, to line the current one up against
+                    if editor.keyframe > 0 {
+                        for hitbox in &data.frames[editor.keyframe - 1].hitboxes {
+                            hitbox
+                                .hitbox()
+                                .draw(
+                                    &mut ctx.gfx,
+                                    canvas,
+                                    &Camera::SCREEN,
+                                    pivot,
+                                    Some(Color::new(1.0, 1.0, 1.0, 0.2)),
+                                )
+                                .unwrap();
+                        }
+                    }
+                    editor.draw_keyframe(ctx, canvas, data, editor.keyframe, pivot);
+                }
+            }
+        }
+
+        canvas.draw(
+            &Text::new(editor.status()),
+            DrawParam::new()
+                .dest(Vec2::splat(20.0))
+                .color(Color::WHITE)
+                .z(999),
+        );
+    }
+
+    fn draw_keyframe(
+        &self,
+        ctx: &mut Context,
+        canvas: &mut Canvas,
+        data: &HitboxAnimationData,
+        keyframe: usize,
+        pivot: Vec2,
+    ) {
+        let frame = &data.frames[keyframe];
+        for (index, hitbox) in frame.hitboxes.iter().enumerate() {
+            // Hitboxes that can't hurt anything are drawn in grey
+            let color = if self.preview.is_none() && self.selected == Some(index) {
+                Color::YELLOW
+            } else if frame.attack.is_some_and(|attack| attack.active) {
+                Color::GREEN
+            } else {
+                Color::new(0.5, 0.5, 0.5, 1.0)
+            };
+            hitbox
+                .hitbox()
+                .draw(&mut ctx.gfx, canvas, &Camera::SCREEN, pivot, Some(color))
+                .unwrap();
+        }
+    }
+
+    fn status(&self) -> String {
+        let Some(path) = self.files.get(self.file) else {
+            return format!("Hitbox editor\n{}", self.message);
+        };
+        let mut status = format!(
+            "Hitbox editor: {path}{}\n",
+            if self.unsaved { " (unsaved)" } else { "" }
+        );
+        if let Some(data) = &self.data {
+            status += &format!("keyframe {}/{}", self.keyframe + 1, data.frames.len());
+            if let Some(interval) = data.intervals.get(self.keyframe) {
+                status += &format!("   interval {interval}");
+            }
+            if !data.intervals.is_empty() {
+                status += &format!("   twine {:.1}", data.twine);
+            }
+            status += "\n";
+        }
+        if let Some(preview) = &self.preview {
+            status += &format!(
+                "preview frame {}/{}{}\n",
+                preview.step + 1,
+                preview.duration,
+                if preview.playing { " (playing)" } else { "" }
+            );
+        }
+        format!("{status}{HELP}\n{}", self.message)
+    }
+
+    /// Moves or resizes hitboxes of the current keyframe with the mouse.
+    fn drag_hitboxes(&mut self, ctx: &Context) {
+        let Some(data) = self.data.as_mut() else {
+            return;
+        };
+        let (width, height) = ctx.gfx.drawable_size();
+        let pivot = Vec2::new(width, height) / 2.0;
+        let mouse = Vec2::from(ctx.mouse.position());
+        let hitboxes = &mut data.frames[self.keyframe].hitboxes;
+
+        let pressed = [MouseButton::Left, MouseButton::Right]
+            .into_iter()
+            .find(|&button| ctx.mouse.button_just_pressed(button));
+        if let Some(button) = pressed {
+            // Hitboxes drawn last are on top, so they're picked first
+            let cursor = Hitbox::circle(mouse, 1.0);
+            self.selected = hitboxes
+                .iter()
+                .rposition(|hitbox| hitbox.hitbox().colliding_single(&cursor, pivot, Vec2::ZERO));
+            self.drag = self.selected.map(|_| match button {
+                MouseButton::Left => Drag::Move { from: mouse },
+                _ => Drag::Resize,
+            });
+        }
+
+        if !ctx.mouse.button_pressed(MouseButton::Left)
+            && !ctx.mouse.button_pressed(MouseButton::Right)
+        {
+            self.drag = None;
+        }
+        let (Some(drag), Some(hitbox)) = (
+            self.drag,
+            self.selected.and_then(|index| hitboxes.get_mut(index)),
+        ) else {
+            return;
+        };
+        match drag {
+            Drag::Move { from } => {
+                hitbox.translate(mouse - from);
+                self.drag = Some(Drag::Move { from: mouse });
+            }
+            Drag::Resize => hitbox.resize_to(mouse - pivot),
+        }
+        if Vec2::from(ctx.mouse.delta()) != Vec2::ZERO {
+            self.edited();
+        }
+    }
+
+    /// The current preview, starting a new one from the beginning if there isn't one.
+    fn preview(&mut self) -> Option<&mut Preview> {
+        if self.preview.is_none() {
+            let data = self.data.as_ref()?;
+            let path = &self.files[self.file];
+            self.preview = if data.intervals.is_empty() {
+                Some(Preview {
+                    animation: None,
+                    step: 0,
+                    duration: data.frames.len(),
+                    playing: false,
+                })
+            } else {
+                match data.animation(path) {
+                    Ok(mut animation) => {
+                        // Played facing the same way as the keyframes are drawn
+                        animation.set_direction(data.direction);
+                        Some(Preview::new(animation))
+                    }
+                    Err(error) => {
+                        self.message = error.to_string();
+                        None
+                    }
+                }
+            };
+        }
+        self.preview.as_mut()
+    }
+
+    /// Steps the preview back or forward by some frames, pausing it.
+    fn scrub(&mut self, frames: isize) {
+        if let Some(preview) = self.preview() {
+            preview.playing = false;
+            preview.seek(
+                (preview.step as isize + frames).rem_euclid(preview.duration as isize) as usize,
+            );
+        }
+    }
+
+    /// Call after changing the data, since the preview was made from the old data.
+    fn edited(&mut self) {
+        self.unsaved = true;
+        self.preview = None;
+    }
+
+    fn find_files(&mut self, ctx: &Context) {
+        match ctx.fs.read_dir(HITBOX_FOLDER) {
+            Ok(paths) => {
+                self.files = paths
+                    .filter(|path| {
+                        path.extension()
+                            .is_some_and(|extension| extension == "json")
+                    })
+                    .map(|path| path.to_string_lossy().replace('\\', "/"))
+                    .collect();
+                self.files.sort();
+            }
+            Err(error) => self.message = error.to_string(),
+        }
+        if self.files.is_empty() {
+            self.message = format!("no hitbox files found in {HITBOX_FOLDER}");
+        } else {
+            self.load(ctx);
+        }
+    }
+
+    /// Loads the current file, throwing away any unsaved edits to the last one.
+    fn load(&mut self, ctx: &Context) {
+        self.keyframe = 0;
+        self.selected = None;
+        self.drag = None;
+        self.preview = None;
+        self.unsaved = false;
+        match HitboxAnimationData::load(ctx, &self.files[self.file]) {
+            Ok(data) => {
+                self.data = Some(data);
+                self.message.clear();
+            }
+            Err(error) => {
+                self.data = None;
+                self.message = error.to_string();
+            }
+        }
+    }
+
+    fn save(&mut self) {
+        let Some(data) = &self.data else {
+            return;
+        };
+        let path = &self.files[self.file];
+        match data.save(path) {
+            Ok(()) => {
+                self.unsaved = false;
+                self.message = format!("saved {path}, restart to see the changes in game");
+            }
+            Err(error) => self.message = error.to_string(),
+        }
+    }
+}
+
+impl Preview {
+    fn new(mut animation: HitboxAnimation) -> Self {
+        // Count how many frames it takes for the animation to loop back around
+        animation.loop_animations(true);
+        animation.reset();
+        let mut duration = 1;
+        while !animation.update_animation() {
+            duration += 1;
+        }
+
+        let mut preview = Self {
+            animation: Some(animation),
+            step: 0,
+            duration,
+            playing: false,
+        };
+        preview.seek(0);
+        preview
+    }
+
+    /// Jumps to a frame of the preview, wrapping around past the end.
+    ///
+    /// Animations can only be played forwards, so they're replayed from the start up to the frame.
+    fn seek(&mut self, step: usize) {
+        self.step = step % self.duration;
+        if let Some(animation) = self.animation.as_mut() {
+            animation.reset();
+            for _ in 0..=self.step {
+                animation.update_animation();
+            }
+        }
+    }
+}
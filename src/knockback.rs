@@ -0,0 +1,107 @@
+use crate::npc::DamageTransfer;
+use crate::Direction;
+use bevy_reflect::Reflect;
+use glam::Vec2;
+
+/// How fast something with a mass of 1.0 gets sent flying by a hit with a weight of 1.0.
+pub(crate) static KNOCKBACK_SPEED: f32 = 8.0;
+
+/// The fraction of knockback velocity that's kept each frame.
+pub(crate) static KNOCKBACK_FRICTION: f32 = 0.85;
+
+/// Once the knockback velocity drops below this, the knockback is over and control is given back.
+pub(crate) static KNOCKBACK_MIN_SPEED: f32 = 0.5;
+
+/// Where a hit came from, used to work out which way the victim gets knocked.
+#[derive(Debug, Default, Reflect, Clone, Copy, PartialEq)]
+pub enum HitOrigin {
+    /// The hit does not knock the victim anywhere.
+    #[default]
+    None,
+    /// The victim is knocked directly away from this point.
+    Position(#[reflect(ignore)] Vec2),
+    /// The victim is knocked in this direction, regardless of where they are.
+    Direction(Direction),
+    /// The victim is knocked along this vector, such as the normal of a [`Contact`](crate::collision::Contact).
+    Normal(#[reflect(ignore)] Vec2),
+}
+
+impl HitOrigin {
+    /// The direction a victim at `victim_position` should be knocked towards.
+    ///
+    /// If the victim is standing right on top of the origin, they're knocked in `fallback`.
+    pub fn direction_from(&self, victim_position: Vec2, fallback: Direction) -> Option<Vec2> {
+        match self {
+            HitOrigin::None => None,
+            HitOrigin::Position(origin) => Some(
+                (victim_position - *origin)
+                    .try_normalize()
+                    .unwrap_or(fallback.to_vec()),
+            ),
+            HitOrigin::Direction(direction) => Some(direction.to_vec()),
+            HitOrigin::Normal(normal) => Some(normal.try_normalize().unwrap_or(fallback.to_vec())),
+        }
+    }
+}
+
+/// Knockback state for anything that can be hit, shared between the protag and enemies.
+///
+/// While [`Knockback::is_active`] is true, the owner should not move or act on its own,
+/// and instead let [`Knockback::update`] move it.
+#[derive(Debug, Reflect, Clone)]
+pub struct Knockback {
+    /// How resistant the owner is to being knocked back. Heavier hits are needed to move heavier things.
+    pub mass: f32,
+    #[reflect(ignore)]
+    velocity: Vec2,
+}
+
+impl Knockback {
+    pub fn new(mass: f32) -> Self {
+        Self {
+            mass,
+            velocity: Vec2::ZERO,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.velocity.length() >= KNOCKBACK_MIN_SPEED
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.velocity
+    }
+
+    /// Starts knocking the owner back, replacing any knockback that's still going.
+    ///
+    /// The impulse is the hit's [`DamageTransfer::weight`] divided by the owner's mass.
+    pub fn apply(&mut self, hit: &DamageTransfer, position: Vec2, facing: Direction) {
+        let Some(direction) = hit.origin.direction_from(position, facing) else {
+            return;
+        };
+        let impulse = hit.weight / self.mass.max(f32::EPSILON);
+        self.velocity = direction * impulse * KNOCKBACK_SPEED;
+    }
+
+    /// Moves the owner along with the knockback and decays it.
+    pub fn update(&mut self, position: &mut Vec2) {
+        if !self.is_active() {
+            self.velocity = Vec2::ZERO;
+            return;
+        }
+        *position += self.velocity;
+        self.velocity *= KNOCKBACK_FRICTION;
+    }
+
+    /// Like [`Knockback::update`], but returns how far the owner should move instead of moving them,
+    /// so that the movement can be checked against walls first.
+    pub fn step(&mut self) -> Vec2 {
+        let mut motion = Vec2::ZERO;
+        self.update(&mut motion);
+        motion
+    }
+
+    pub fn cancel(&mut self) {
+        self.velocity = Vec2::ZERO;
+    }
+}
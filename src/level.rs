@@ -1,17 +1,59 @@
 use std::any::{Any, TypeId};
+use std::collections::HashMap;
 
+use crate::assets::map::{MapData, SpawnData};
 use crate::assets::StaticAssets;
-use crate::npc::{basic_enemy::BasicEnemy, Enemy, EnemyContainer};
+use crate::camera::Camera;
+use crate::collision::{
+    broadphase::offset_rect, sweep, CollisionFilter, Hitbox, HitboxType, Layers, Ray, RayHit,
+    SpatialHash,
+};
+use crate::editor::HitboxEditor;
+use crate::movement::Solids;
+use crate::npc::{EnemyContainer, EnemyHandle};
+use crate::pickup::Pickup;
+use crate::protag::item_select::ItemSelect;
 use crate::protag::Protag;
+use crate::room::{Room, RoomTransition};
+use crate::terrain::Wall;
+use crate::tilemap::{TileKind, Tilemap, TILE_SIZE};
+use crate::trigger::Trigger;
 use bevy_reflect::{GetField, PartialReflect, Reflect, ReflectMut, ReflectRef};
-use ggez::graphics::{Canvas, Color};
-use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Rect};
+use ggez::{Context, GameError, GameResult};
 use glam::Vec2;
 
+/// The map the game starts in.
+static STARTING_MAP: &str = "/maps/field.tmj";
+
 #[derive(Debug, Reflect)]
 pub struct Level {
     pub protag: Protag,
     pub enemies: EnemyContainer,
+    pub pickups: Vec<Pickup>,
+    pub walls: Vec<Wall>,
+    pub tilemap: Tilemap,
+    pub triggers: Vec<Trigger>,
+    pub rooms: Vec<Room>,
+    /// An index into [`Level::rooms`] of the room the protag is in, see [`Room::update`].
+    pub room: usize,
+    /// Set while the screen scrolls to another room, which freezes everything else.
+    pub transition: Option<RoomTransition>,
+    pub camera: Camera,
+    /// Every enemy in the map, which are spawned whenever the protag enters the room they're in.
+    #[reflect(ignore)]
+    pub spawns: Vec<SpawnData>,
+    /// Where every named entrance in the map is, for doors that lead somewhere else in the same map.
+    #[reflect(ignore)]
+    pub entrances: HashMap<String, Vec2>,
+    /// The path of the map the level was built from, see [`Level::enter_map`].
+    pub map: String,
+    /// Broadphase for collisions against enemies, rebuilt at the start of every update.
+    /// The protag is added once they've finished moving for the frame.
+    #[reflect(ignore)]
+    pub colliders: SpatialHash<Collider>,
+    #[reflect(ignore)]
+    pub editor: HitboxEditor,
     #[reflect(ignore)]
     #[reflect(default = "crate::Game::static_assets")]
     pub static_assets: &'static StaticAssets,
@@ -20,31 +62,263 @@ pub struct Level {
 impl Level {
     pub fn initialize_assets(assets: &mut StaticAssets) {}
 
-    pub fn new(ctx: &mut Context, assets: &'static StaticAssets) -> Self {
+    pub fn new(ctx: &mut Context, assets: &'static StaticAssets) -> GameResult<Self> {
         let mut level = Self {
             protag: Protag::new(&assets.protag, ctx),
             enemies: EnemyContainer::new(),
+            pickups: Vec::new(),
+            walls: Vec::new(),
+            tilemap: Tilemap::default(),
+            triggers: Vec::new(),
+            rooms: Vec::new(),
+            room: 0,
+            transition: None,
+            camera: Camera::default(),
+            spawns: Vec::new(),
+            entrances: HashMap::new(),
+            map: String::new(),
+            colliders: SpatialHash::default(),
+            editor: HitboxEditor::default(),
             static_assets: assets,
         };
 
-        EnemyContainer::init(&mut level, ctx);
+        level.change_map(ctx, STARTING_MAP, None)?;
+
+        Ok(level)
+    }
 
-        level
+    /// Loads a map and moves the protag into it. See [`Level::enter_map`].
+    pub fn change_map(
+        &mut self,
+        ctx: &mut Context,
+        path: &str,
+        entrance: Option<&str>,
+    ) -> GameResult {
+        let map = MapData::load(ctx, self.static_assets, path)?;
+        self.enter_map(ctx, map, entrance)
+    }
+
+    /// Replaces everything in the level with what's in the map, and places the protag at the named entrance,
+    /// or the map's start if there isn't one. The enemies in the room they're placed in are spawned fresh.
+    ///
+    /// The protag respawns wherever they entered the map.
+    pub fn enter_map(
+        &mut self,
+        ctx: &mut Context,
+        map: MapData,
+        entrance: Option<&str>,
+    ) -> GameResult {
+        let start = match entrance {
+            None => map.protag_start,
+            Some(name) => *map.entrances.get(name).ok_or_else(|| {
+                GameError::ResourceLoadError(format!(
+                    "{}: there's no entrance named `{name}`",
+                    map.path
+                ))
+            })?,
+        };
+
+        self.tilemap = Tilemap::new(
+            ctx,
+            &self.static_assets.tileset,
+            map.position,
+            TILE_SIZE,
+            map.width,
+            map.tiles,
+        )?;
+        self.walls = map.walls;
+        self.triggers = map.triggers;
+        self.pickups = map.pickups;
+        self.rooms = map.rooms;
+        self.spawns = map.spawns;
+        self.entrances = map.entrances;
+        self.map = map.path;
+        self.protag.place(start);
+
+        Room::enter(self, ctx);
+        Ok(())
     }
 
     pub fn update(&mut self, ctx: &mut Context) -> GameResult {
+        HitboxEditor::update(self, ctx);
+        // Everything is paused while editing hitboxes, including the item select subscreen.
+        if self.editor.open {
+            return Ok(());
+        }
+
+        ItemSelect::update(self, ctx);
+        // The whole level is paused while the item select subscreen is open.
+        if self.protag.item_select.open {
+            return Ok(());
+        }
+
+        Room::update(self);
+        // Everything else is frozen while the screen scrolls to the next room.
+        if self.transition.is_some() {
+            return Ok(());
+        }
+
+        self.index_colliders();
+
         Protag::update(self, ctx);
 
+        Tilemap::update(self, ctx);
+
+        Trigger::update(self, ctx);
+
         EnemyContainer::update(self, ctx)?;
 
+        Pickup::update(self, ctx);
+
+        // Once everything has moved, so the camera follows where the protag is this frame.
+        Camera::update(self, ctx);
+
         Ok(())
     }
 
+    /// Clears [`Level::colliders`] and adds every enemy's hurtbox and attacks.
+    fn index_colliders(&mut self) {
+        self.colliders.clear();
+        for (handle, enemy) in self.enemies.iter() {
+            if let Some((hitbox, offset)) = enemy.get_hitbox() {
+                self.colliders
+                    .insert_hitbox(Collider::Enemy(handle), hitbox, offset);
+            }
+            for attack in enemy.get_attacks() {
+                self.colliders
+                    .insert_hitbox(Collider::Enemy(handle), attack.hitbox, attack.offset);
+            }
+        }
+    }
+
+    /// The first wall, enemy or protag the ray hits, skipping anything in `ignore`.
+    ///
+    /// Enemies are found through [`Level::colliders`], so enemies spawned during this update are missed.
+    pub fn raycast(&self, ray: &Ray, ignore: &[Collider]) -> Option<(Collider, RayHit)> {
+        self.first_hit(
+            ray.bounding_box(),
+            CollisionFilter::new(Layers::ALL, ray.mask),
+            ignore,
+            |hitbox, offset| hitbox.raycast(ray, offset),
+        )
+    }
+
+    /// Like [`Level::raycast`], but moves a whole hitbox along the ray. See [`Hitbox::shape_cast`].
+    pub fn shape_cast(
+        &self,
+        hitbox: &Hitbox,
+        ray: &Ray,
+        ignore: &[Collider],
+    ) -> Option<(Collider, RayHit)> {
+        self.first_hit(
+            sweep::swept_bounding_box(hitbox.bounding_box(), ray.origin, ray.end()),
+            hitbox.filter(),
+            ignore,
+            |other, offset| hitbox.shape_cast(ray, other, offset),
+        )
+    }
+
+    /// Runs `cast` against everything that could be in `area`, and returns the nearest hit.
+    fn first_hit(
+        &self,
+        area: Rect,
+        filter: CollisionFilter,
+        ignore: &[Collider],
+        cast: impl Fn(HitboxType, Vec2) -> Option<RayHit>,
+    ) -> Option<(Collider, RayHit)> {
+        let cast = &cast;
+        let walls = self.walls.iter().enumerate().filter_map(|(index, wall)| {
+            cast(HitboxType::Singular(&wall.hitbox), wall.position)
+                .map(|hit| (Collider::Wall(index), hit))
+        });
+        // Ledges can be seen and shot over, so only solid tiles get in the way.
+        let tiles = self
+            .tilemap
+            .colliders_in(area)
+            .filter(|(_, collider)| collider.kind == TileKind::Solid)
+            .filter_map(|(index, collider)| {
+                cast(HitboxType::Singular(&collider.hitbox), collider.position)
+                    .map(|hit| (Collider::Tile(index), hit))
+            });
+        let enemies = self
+            .colliders
+            .query(area, filter)
+            .into_iter()
+            .filter_map(Collider::enemy)
+            .filter_map(|handle| {
+                let (hitbox, offset) = self.enemies.get(handle)?.get_hitbox()?;
+                cast(hitbox, offset).map(|hit| (Collider::Enemy(handle), hit))
+            });
+        let protag = cast(
+            HitboxType::Singular(&self.protag.hurtbox),
+            self.protag.position,
+        )
+        .map(|hit| (Collider::Protag, hit));
+
+        walls
+            .chain(tiles)
+            .chain(enemies)
+            .chain(protag)
+            .filter(|(collider, _)| !ignore.contains(collider))
+            .min_by(|(_, a), (_, b)| a.distance.total_cmp(&b.distance))
+    }
+
     pub fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        Tilemap::draw(self, ctx, canvas)?;
+
+        Wall::draw(self, ctx, canvas)?;
+
+        Trigger::draw(self, ctx, canvas)?;
+
+        Pickup::draw(self, ctx, canvas)?;
+
         Protag::draw(self, ctx, canvas)?;
 
         EnemyContainer::draw(self, ctx, canvas)?;
 
+        Protag::draw_hud(self, ctx, canvas);
+
+        ItemSelect::draw(self, ctx, canvas);
+
+        HitboxEditor::draw(self, ctx, canvas);
+
         Ok(())
     }
 }
+
+impl Solids for Level {
+    /// Every hitbox on the solid layer near `area`, from the walls and the tilemap.
+    ///
+    /// Walls always block, but ledge tiles only block one way, see [`TileKind::blocks`].
+    /// Tiles are looked up by the rows `area` covers, see [`Tilemap::colliders_in`].
+    fn solids(&self, area: Rect, motion: Vec2) -> impl Iterator<Item = (&Hitbox, Vec2)> {
+        self.walls
+            .iter()
+            .filter(|wall| wall.hitbox.filter().layers.intersects(Layers::SOLID))
+            .filter(move |wall| {
+                offset_rect(wall.hitbox.bounding_box(), wall.position).overlaps(&area)
+            })
+            .map(|wall| (&wall.hitbox, wall.position))
+            .chain(self.tilemap.solids(area, motion))
+    }
+}
+
+/// Something in the level that can be collided with, like an entry in [`Level::colliders`] or a [`Level::raycast`] hit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub enum Collider {
+    Protag,
+    Enemy(EnemyHandle),
+    /// An index into [`Level::walls`]. Walls aren't added to [`Level::colliders`], since bombs can remove them partway through an update.
+    Wall(usize),
+    /// An index into [`Tilemap::colliders`].
+    Tile(usize),
+}
+
+impl Collider {
+    pub fn enemy(self) -> Option<EnemyHandle> {
+        match self {
+            Collider::Enemy(handle) => Some(handle),
+            Collider::Protag | Collider::Wall(_) | Collider::Tile(_) => None,
+        }
+    }
+}
@@ -12,14 +12,28 @@ use ggez::Context;
 use glam::Vec2;
 use level::Level;
 use protag::Protag;
+use serde::{Deserialize, Serialize};
 
 pub mod assets;
+pub mod attack;
+pub mod camera;
 pub mod collision;
+pub mod editor;
 pub mod get;
+pub mod knockback;
 pub mod level;
+pub mod movement;
 pub mod npc;
+pub mod pickup;
 pub mod protag;
+pub mod room;
 pub mod sprite;
+pub mod terrain;
+pub mod tilemap;
+pub mod trigger;
+
+/// Where resources are loaded from. Debug tools like the [`HitboxEditor`](editor::HitboxEditor) also save back into it.
+pub(crate) static RESOURCES_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/resources");
 
 fn main() {
     let (mut ctx, event) = ggez::ContextBuilder::new("linklike", "jarten")
@@ -30,10 +44,12 @@ fn main() {
                 .min_dimensions(1280.0, 720.0),
         )
         .window_setup(WindowSetup::default().title("linklike debug build"))
+        .add_resource_path(RESOURCES_DIR)
         .build()
         .expect("could not build :(");
 
-    let mut static_assets: StaticAssets = StaticAssets::new();
+    let mut static_assets: StaticAssets =
+        StaticAssets::new(&ctx).unwrap_or_else(|error| panic!("could not load assets: {error}"));
     Level::initialize_assets(&mut static_assets);
 
     let leak: &'static StaticAssets = Box::leak::<'static>(Box::new(static_assets));
@@ -58,7 +74,8 @@ static STATIC_ASSETS_REF: OnceLock<&'static StaticAssets> = OnceLock::new();
 impl Game {
     fn new(ctx: &mut ggez::Context, static_assets: &'static StaticAssets) -> Self {
         Self {
-            level: Level::new(ctx, &static_assets),
+            level: Level::new(ctx, &static_assets)
+                .unwrap_or_else(|error| panic!("could not load the level: {error}")),
             static_assets,
         }
     }
@@ -90,7 +107,8 @@ impl EventHandler for Game {
     }
 }
 
-#[derive(Debug, Clone, Copy, Reflect, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Reflect, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum Direction {
     Right = 0,
     Up = 1,
@@ -0,0 +1,259 @@
+use crate::collision::broadphase::offset_rect;
+use crate::collision::sweep::swept_bounding_box;
+use crate::collision::{Hitbox, Sweep};
+use bevy_reflect::Reflect;
+use ggez::graphics::Rect;
+use glam::{BVec2, Vec2};
+
+/// How far movers are kept away from solids after running into them, so that they aren't
+/// left touching the solid, which would count as overlapping it on the next move.
+static SKIN: f32 = 0.01;
+
+/// Whatever has hitboxes that nothing can move through, which in the game is the [`Level`](crate::level::Level).
+pub trait Solids {
+    /// Every solid hitbox that stops something moving along `motion`, along with its offset.
+    ///
+    /// Only solids that could overlap `area`, in world space, are needed. Ones further away may be left out, but don't have to be.
+    fn solids(&self, area: Rect, motion: Vec2) -> impl Iterator<Item = (&Hitbox, Vec2)>;
+
+    /// The first solid the hitbox runs into while moving from `from` to `to`, and when it hit.
+    fn sweep_solids(&self, hitbox: &Hitbox, from: Vec2, to: Vec2) -> Option<Sweep> {
+        self.solids(
+            swept_bounding_box(hitbox.bounding_box(), from, to),
+            to - from,
+        )
+        .filter_map(|(solid, offset)| hitbox.sweep_single(solid, from, to, offset))
+        .min_by(|a, b| a.time.total_cmp(&b.time))
+    }
+
+    /// Whether the hitbox is overlapping any solid that would stop it moving along `motion`.
+    fn overlaps_solid(&self, hitbox: &Hitbox, position: Vec2, motion: Vec2) -> bool {
+        self.solids(offset_rect(hitbox.bounding_box(), position), motion)
+            .any(|(solid, offset)| hitbox.colliding_single(solid, position, offset))
+    }
+}
+
+/// How something moves through the level, without overlapping any [solid](Solids::solids) hitboxes.
+///
+/// Movement is resolved one axis at a time, so running into a wall diagonally keeps sliding along it.
+/// Shared by the protag and enemies, since anything with a hitbox and a position can use it.
+#[derive(Debug, Clone, Copy, Reflect, PartialEq)]
+pub struct Movement {
+    /// How far the mover can be nudged sideways around a corner it only just clipped, like in LttP.
+    /// Nudging only happens when moving straight along one axis. Zero turns it off.
+    pub corner_nudge: f32,
+}
+
+/// Where a mover ended up after [`Movement::resolve`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Moved {
+    pub position: Vec2,
+    /// Which axes were stopped short by a solid.
+    pub blocked: BVec2,
+}
+
+impl Movement {
+    pub const fn new(corner_nudge: f32) -> Self {
+        Self { corner_nudge }
+    }
+
+    /// Moves the hitbox from `position` by `motion`, stopping against solids.
+    ///
+    /// If the hitbox already started inside a solid, it's pushed back out first.
+    pub fn resolve(
+        &self,
+        level: &impl Solids,
+        hitbox: &Hitbox,
+        position: Vec2,
+        motion: Vec2,
+    ) -> Moved {
+        let mut position = push_out(level, hitbox, position, motion);
+        let mut blocked = BVec2::FALSE;
+
+        for (axis, step) in [(0, Vec2::new(motion.x, 0.0)), (1, Vec2::new(0.0, motion.y))] {
+            if step == Vec2::ZERO {
+                continue;
+            }
+            let (moved, hit) = move_along(level, hitbox, position, step);
+            position = moved;
+            blocked.set(axis, hit);
+        }
+
+        if self.corner_nudge > 0.0 {
+            if blocked.x && motion.y == 0.0 {
+                position = self.nudge(level, hitbox, position, Vec2::new(motion.x, 0.0), Vec2::Y);
+            } else if blocked.y && motion.x == 0.0 {
+                position = self.nudge(level, hitbox, position, Vec2::new(0.0, motion.y), Vec2::X);
+            }
+        }
+
+        Moved { position, blocked }
+    }
+
+    /// Slides the hitbox sideways towards the nearest gap it could fit through,
+    /// if there's one within [`Movement::corner_nudge`] of where it got stuck.
+    fn nudge(
+        &self,
+        level: &impl Solids,
+        hitbox: &Hitbox,
+        position: Vec2,
+        step: Vec2,
+        across: Vec2,
+    ) -> Vec2 {
+        let mut nearest: Option<Vec2> = None;
+        for side in [across, -across] {
+            let mut distance = 1.0;
+            while distance <= self.corner_nudge {
+                let shifted = position + side * distance;
+                if level.overlaps_solid(hitbox, shifted, step) {
+                    break;
+                }
+                if !level.overlaps_solid(hitbox, shifted + step, step) {
+                    if nearest.is_none_or(|nearest| distance < nearest.length()) {
+                        nearest = Some(side * distance);
+                    }
+                    break;
+                }
+                distance += 1.0;
+            }
+        }
+
+        // Nudge at the same speed the mover was trying to go, rather than snapping through the corner
+        let Some(nudge) = nearest else {
+            return position;
+        };
+        move_along(
+            level,
+            hitbox,
+            position,
+            nudge.clamp_length_max(step.length()),
+        )
+        .0
+    }
+}
+
+/// Moves in a straight line until the first solid in the way. Returns the new position, and whether anything was hit.
+fn move_along(level: &impl Solids, hitbox: &Hitbox, position: Vec2, step: Vec2) -> (Vec2, bool) {
+    let to = position + step;
+    match level.sweep_solids(hitbox, position, to) {
+        None => (to, false),
+        Some(sweep) => {
+            let distance = (step.length() * sweep.time - SKIN).max(0.0);
+            (position + step.normalize_or_zero() * distance, true)
+        }
+    }
+}
+
+/// Pushes the hitbox out of every solid it's overlapping that would stop it moving along `motion`.
+fn push_out(level: &impl Solids, hitbox: &Hitbox, mut position: Vec2, motion: Vec2) -> Vec2 {
+    for (solid, offset) in level.solids(offset_rect(hitbox.bounding_box(), position), motion) {
+        if let Some(contact) = hitbox.contact_single(solid, position, offset) {
+            position += contact.penetration + contact.normal * SKIN;
+        }
+    }
+    position
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Solid hitboxes on their own, without a level around them.
+    struct Walls(Vec<(Hitbox, Vec2)>);
+
+    impl Solids for Walls {
+        fn solids(&self, _area: Rect, _motion: Vec2) -> impl Iterator<Item = (&Hitbox, Vec2)> {
+            self.0.iter().map(|(hitbox, offset)| (hitbox, *offset))
+        }
+    }
+
+    fn walls(rects: &[Rect]) -> Walls {
+        Walls(
+            rects
+                .iter()
+                .map(|rect| (Hitbox::new(*rect), Vec2::ZERO))
+                .collect(),
+        )
+    }
+
+    /// A 10x10 box centered on its position.
+    fn mover() -> Hitbox {
+        Hitbox::point_size(Vec2::ZERO, 10.0)
+    }
+
+    fn close(a: Vec2, b: Vec2) -> bool {
+        a.distance(b) < 0.001
+    }
+
+    #[test]
+    fn walls_stop_movers_short() {
+        let level = walls(&[Rect::new(20.0, -50.0, 10.0, 100.0)]);
+        let moved = Movement::new(0.0).resolve(&level, &mover(), Vec2::ZERO, Vec2::new(20.0, 0.0));
+        assert!(
+            close(moved.position, Vec2::new(15.0 - SKIN, 0.0)),
+            "{moved:?}"
+        );
+        assert_eq!(moved.blocked, BVec2::new(true, false));
+        // Stopped just short, so the next move isn't already overlapping.
+        assert!(!level.overlaps_solid(&mover(), moved.position, Vec2::X));
+    }
+
+    #[test]
+    fn diagonal_moves_slide_along_walls() {
+        let level = walls(&[Rect::new(20.0, -50.0, 10.0, 100.0)]);
+        let moved = Movement::new(0.0).resolve(&level, &mover(), Vec2::ZERO, Vec2::new(20.0, 10.0));
+        assert!(
+            close(moved.position, Vec2::new(15.0 - SKIN, 10.0)),
+            "{moved:?}"
+        );
+        assert_eq!(moved.blocked, BVec2::new(true, false));
+    }
+
+    #[test]
+    fn movers_without_motion_stay_put() {
+        let level = walls(&[Rect::new(20.0, -50.0, 10.0, 100.0)]);
+        let moved = Movement::new(4.0).resolve(&level, &mover(), Vec2::ZERO, Vec2::ZERO);
+        assert_eq!(moved.position, Vec2::ZERO);
+        assert_eq!(moved.blocked, BVec2::FALSE);
+    }
+
+    #[test]
+    fn movers_inside_walls_are_pushed_out() {
+        let level = walls(&[Rect::new(20.0, -50.0, 10.0, 100.0)]);
+        let moved = Movement::new(0.0).resolve(&level, &mover(), Vec2::new(18.0, 0.0), Vec2::ZERO);
+        assert!(
+            close(moved.position, Vec2::new(15.0 - SKIN, 0.0)),
+            "{moved:?}"
+        );
+    }
+
+    #[test]
+    fn clipped_corners_nudge_movers_around() {
+        // Hangs down 1 pixel into the mover's way.
+        let level = walls(&[Rect::new(10.0, -20.0, 10.0, 16.0)]);
+        let start = Vec2::new(3.0, 0.0);
+        let moved = Movement::new(4.0).resolve(&level, &mover(), start, Vec2::new(5.0, 0.0));
+        assert!(moved.position.x < 5.0, "{moved:?}");
+        assert!((moved.position.y - 2.0).abs() < 0.001, "{moved:?}");
+        let next =
+            Movement::new(4.0).resolve(&level, &mover(), moved.position, Vec2::new(5.0, 0.0));
+        assert!(!next.blocked.x, "{next:?}");
+
+        // Without nudging, or with too little of it, the mover just stops.
+        let moved = Movement::new(0.0).resolve(&level, &mover(), start, Vec2::new(5.0, 0.0));
+        assert_eq!(moved.position.y, 0.0);
+        let moved = Movement::new(1.0).resolve(&level, &mover(), start, Vec2::new(5.0, 0.0));
+        assert_eq!(moved.position.y, 0.0);
+    }
+
+    #[test]
+    fn diagonal_moves_are_never_nudged() {
+        let level = walls(&[Rect::new(10.0, -20.0, 10.0, 16.0)]);
+        let moved =
+            Movement::new(4.0).resolve(&level, &mover(), Vec2::new(3.0, 0.0), Vec2::new(5.0, -1.0));
+        assert!(
+            close(moved.position, Vec2::new(5.0 - SKIN, -1.0)),
+            "{moved:?}"
+        );
+    }
+}
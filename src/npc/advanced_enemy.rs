@@ -1,45 +1,43 @@
-use crate::collision::{
-    Hitbox, HitboxFrame, HitboxFrameRef, HitboxFrameString, HitboxFrameStringRef,
-    StaticHitboxFrameString,
-};
+use crate::assets::hitboxes::HitboxAnimationData;
+use crate::assets::StaticAssets;
+use crate::attack::{self, Element, Hit, HitHistory};
+use crate::collision::{CollisionFilter, Hitbox, HitboxAnimation, HitboxType};
+use crate::knockback::{HitOrigin, Knockback};
+use crate::level::Collider;
+use crate::pickup::{Pickup, PickupKind};
 use crate::Direction;
 use bevy_reflect::Reflect;
-use ggez::graphics::{Canvas, Color, GraphicsContext, Rect};
-use ggez::GameError;
+use ggez::graphics::Color;
 use glam::Vec2;
 
-use super::Enemy;
+use super::{
+    DamageTransfer, Enemy, EnemyAttack, EnemyCommands, EnemyHandle, EnemyStats, ENEMY_MOVEMENT,
+};
 
 #[derive(Debug)]
 pub struct AdvancedEnemyData {
     wide_swing: HitboxAnimation,
+    contact_damage: DamageTransfer,
+    mass: f32,
 }
 
 impl AdvancedEnemyData {
-    pub fn new() -> Self {
-        static HITBOX_DATA: HitboxFrameStringRef = HitboxFrameStringRef::new(&[
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(50.0, 50.0), 40.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(55.0, -20.0), 40.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(-80.0, 00.0), 40.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(00.0, 100.0), 40.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(150.0, 75.0), 40.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(165.0, -150.0), 60.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(20.0, -200.0), 60.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(-300.0, -100.0), 80.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(-350.0, 200.0), 80.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(00.0, 400.0), 80.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(350.0, 300.0), 80.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(200.0, -50.0), 80.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(00.0, -100.0), 40.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(00.0, 00.0), 00.0)]),
-            HitboxFrameRef::down(&[Hitbox::point_size(Vec2::new(50.0, 50.0), 0.0)]),
-        ]);
-        Self {
-            wide_swing: HitboxAnimation::new(
-                HITBOX_DATA,
-                vec![4, 5, 5, 5, 5, 5, 6, 6, 4, 5, 7, 45, 10],
-            ),
-        }
+    pub fn new(ctx: &ggez::Context) -> ggez::GameResult<Self> {
+        let mut wide_swing = HitboxAnimationData::load_animation(ctx, "/hitboxes/wide_swing.json")?;
+        wide_swing.set_filter(CollisionFilter::ATTACK);
+        // The swing moves hundreds of pixels between keyframes, so it needs sweeping to not skip over the protag.
+        wide_swing.sweep_between_frames(true);
+        Ok(Self {
+            wide_swing,
+            mass: 4.0,
+            contact_damage: DamageTransfer {
+                damage: 4.0,
+                weight: 2.0,
+                origin: HitOrigin::None,
+                stun: 0,
+                element: Element::Physical,
+            },
+        })
     }
 }
 
@@ -47,161 +45,87 @@ impl AdvancedEnemyData {
 pub struct AdvancedEnemy {
     hurtbox: Hitbox,
     wide_swing: HitboxAnimation,
+    /// Who the current swing has hit, cleared whenever the swing starts over.
+    hits: HitHistory,
+    contact_damage: DamageTransfer,
+    knockback: Knockback,
+    stats: EnemyStats,
     #[reflect(ignore)]
     position: Vec2,
 }
 
-/// Machiene that turns hitboxes into lerped hitboxes
-#[derive(Debug, Reflect, Clone)]
-pub struct HitboxAnimation {
-    pub lerped_hitboxes: HitboxFrame,
-    pub hitboxes: [HitboxFrameString; 4],
-    /// The amount of frames between each keyframe.
-    intervals: Vec<usize>,
-
-    frame_of_current_interval: usize,
-    current_interval: usize,
-    loops: bool,
-    active: bool,
-    twine: f32,
-    direction: Direction,
+impl AdvancedEnemy {
+    /// Whether the swing can hit anything, which it can't while the enemy is stunned or dead.
+    fn swinging(&self) -> bool {
+        self.wide_swing.is_active() && self.stats.stunned == 0 && self.stats.health > 0
+    }
 }
 
-impl HitboxAnimation {
-    pub fn new(hitboxes: HitboxFrameStringRef, intervals: Vec<usize>) -> Self {
+impl Enemy for AdvancedEnemy {
+    fn new(assets: &StaticAssets) -> Self {
         Self {
-            lerped_hitboxes: HitboxFrame::new(&[], Direction::Down),
-            hitboxes: [
-                hitboxes.to_direction(Direction::Right),
-                hitboxes.to_direction(Direction::Up),
-                hitboxes.to_direction(Direction::Left),
-                hitboxes.to_direction(Direction::Down),
-            ],
-            intervals,
-            frame_of_current_interval: 0,
-            current_interval: 0,
-            loops: true,
-            active: true,
-            twine: 1.0,
-            direction: Direction::Down,
-        }
-    }
-
-    pub fn loop_animations(&mut self, value: bool) {
-        self.loops = value;
-    }
-
-    pub fn set_active(&mut self, value: bool) {
-        self.active = value;
-    }
-
-    pub fn reset(&mut self) {
-        self.active = true;
-        self.current_interval = 0;
-        self.frame_of_current_interval = 0;
-    }
-
-    pub fn reset_current_interval(&mut self) {
-        self.frame_of_current_interval = 0;
-    }
-
-    pub fn update_animation(&mut self) -> bool {
-        if !self.active {
-            return false;
-        }
-
-        let updated = if self.frame_of_current_interval == 0 {
-            if self.current_interval >= self.intervals.len() - 1 {
-                if self.loops {
-                    self.current_interval = 0;
-                } else {
-                    self.active = false;
-                }
-                true
-            } else {
-                self.current_interval += 1;
-                self.frame_of_current_interval = self.intervals[self.current_interval];
-                false
-            }
-        } else {
-            self.frame_of_current_interval -= 1;
-            false
-        };
-
-        self.lerped_hitboxes = self.lerped_hitboxes();
-
-        updated
-    }
-
-    pub fn current_frame(&self, direction: Direction) -> HitboxFrameRef {
-        self.hitboxes[direction as usize].0[self.current_interval].borrow()
-    }
-
-    pub fn next_frame(&self, direction: Direction) -> Option<HitboxFrameRef> {
-        let string = &self.hitboxes[direction as usize];
-
-        if self.current_interval + 1 >= string.0.len() {
-            return None;
+            hurtbox: Hitbox::point_size(Vec2::ZERO, 50.0).with_filter(CollisionFilter::BODY),
+            wide_swing: assets.advanced_enemy.wide_swing.clone(),
+            hits: HitHistory::default(),
+            contact_damage: assets.advanced_enemy.contact_damage.clone(),
+            knockback: Knockback::new(assets.advanced_enemy.mass),
+            stats: EnemyStats {
+                health: 40,
+                damage: assets.advanced_enemy.contact_damage.damage as i32,
+                iframes: 0,
+                stunned: 0,
+            },
+            position: Vec2::ZERO,
         }
-
-        Some(string.0[self.current_interval + 1].borrow())
     }
 
-    pub fn lerped_hitboxes(&self) -> HitboxFrame {
-        let current_frame = self.current_frame(self.direction);
-
-        let t = (self.intervals[self.current_interval] - self.frame_of_current_interval) as f32
-            / (self.intervals[self.current_interval] as f32 + 1.0);
-
-        let new_frame: HitboxFrame = if let Some(next_frame) = self.next_frame(self.direction) {
-            HitboxFrame::new(
-                &current_frame
-                    .0
-                    .iter()
-                    .zip(next_frame.0.iter())
-                    .map(|(current, next)| current.twine_lerp(next, t, self.twine))
-                    .collect::<Vec<Hitbox>>(),
-                self.direction,
-            )
-        } else {
-            current_frame.to_owned()
-        };
-
-        new_frame
-    }
-
-    pub fn draw(&self, gfx: &mut GraphicsContext, canvas: &mut Canvas, offset: Vec2) {
-        self.lerped_hitboxes
-            .borrow()
-            .draw(gfx, canvas, offset, Color::GREEN)
-            .unwrap();
-    }
-}
-
-impl Enemy for AdvancedEnemy {
-    fn create(level: &mut crate::level::Level, ctx: &mut ggez::Context) -> ggez::GameResult
+    fn create(
+        level: &mut crate::level::Level,
+        ctx: &mut ggez::Context,
+    ) -> ggez::GameResult<EnemyHandle>
     where
         Self: Sized,
     {
-        match level.enemies.advanced_enemy.replace(Self {
-            hurtbox: Hitbox::point_size(Vec2::ZERO, 50.0),
-            wide_swing: level.static_assets.advanced_enemy.wide_swing.clone(),
-            position: Vec2::ZERO,
-        }) {
-            Some(some) => Err(GameError::CustomError("Enemy already exists".to_string())),
-            None => Ok(()),
-        }
+        Ok(level
+            .enemies
+            .insert(Box::new(Self::new(level.static_assets))))
     }
 
     fn update(
         &mut self,
         level: &mut crate::level::Level,
         ctx: &mut ggez::Context,
+        commands: &mut EnemyCommands,
     ) -> ggez::GameResult {
+        if self.stats.health <= 0 {
+            commands.drop_item(Pickup::new(PickupKind::Rupee { amount: 5 }, self.position));
+            commands.despawn();
+            return Ok(());
+        }
+        if self.stats.iframes > 0 {
+            self.stats.iframes -= 1;
+        }
+        if self.knockback.is_active() {
+            let motion = self.knockback.step();
+            self.position = ENEMY_MOVEMENT
+                .resolve(level, &self.hurtbox, self.position, motion)
+                .position;
+            return Ok(());
+        }
+        if self.stats.stunned > 0 {
+            self.stats.stunned -= 1;
+            return Ok(());
+        }
         let direction = (level.protag.position - self.position).normalize();
-        self.position += direction;
+        self.position = ENEMY_MOVEMENT
+            .resolve(level, &self.hurtbox, self.position, direction)
+            .position;
         if self.wide_swing.update_animation() {
-            self.wide_swing.direction = Direction::from(direction)
+            // Aim the swing straight at the protag, keeping the direction around for knockback
+            self.wide_swing.set_direction(Direction::from(direction));
+            self.wide_swing
+                .set_angle(Some(Vec2::X.angle_between(direction)));
+            self.hits.clear();
         }
         Ok(())
     }
@@ -212,9 +136,84 @@ impl Enemy for AdvancedEnemy {
         ctx: &mut ggez::Context,
         canvas: &mut ggez::graphics::Canvas,
     ) -> ggez::GameResult {
-        self.wide_swing.draw(&mut ctx.gfx, canvas, self.position);
-        self.hurtbox
-            .draw(&mut ctx.gfx, canvas, self.position, Some(Color::RED));
+        self.wide_swing
+            .draw(&mut ctx.gfx, canvas, &level.camera, self.position);
+        self.hurtbox.draw(
+            &mut ctx.gfx,
+            canvas,
+            &level.camera,
+            self.position,
+            Some(Color::RED),
+        )?;
         Ok(())
     }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
+    }
+
+    fn get_hitbox(&self) -> Option<(HitboxType<'_, '_, '_>, Vec2)> {
+        Some((HitboxType::Singular(&self.hurtbox), self.position))
+    }
+
+    fn get_attacks(&self) -> Vec<EnemyAttack<'_>> {
+        if !self.swinging() {
+            return Vec::new();
+        }
+        let frame = self.wide_swing.lerped_hitboxes.borrow();
+        if !frame.attack().is_some_and(|attack| attack.active) {
+            return Vec::new();
+        }
+        vec![EnemyAttack {
+            hitbox: HitboxType::Compound(frame),
+            offset: self.position,
+        }]
+    }
+
+    fn resolve_attacks(
+        &mut self,
+        target: Collider,
+        hurtbox: HitboxType,
+        target_offset: Vec2,
+    ) -> Option<Hit> {
+        if !self.swinging() {
+            return None;
+        }
+        attack::resolve_hit(
+            self.wide_swing.lerped_hitboxes.borrow(),
+            self.position,
+            target,
+            hurtbox,
+            target_offset,
+            &mut self.hits,
+        )
+    }
+
+    fn contact_damage(&self) -> Option<DamageTransfer> {
+        (self.stats.health > 0).then(|| {
+            self.contact_damage
+                .clone()
+                .with_origin(HitOrigin::Position(self.position))
+        })
+    }
+
+    fn on_hit(&mut self, stats: DamageTransfer) -> bool {
+        // Same as the basic enemy, stuns alone don't give iframes so the sword can follow up.
+        if stats.damage <= 0.0 {
+            self.stats.stunned = self.stats.stunned.max(stats.stun);
+            self.knockback
+                .apply(&stats, self.position, self.wide_swing.direction());
+            return stats.stun > 0;
+        }
+        if self.stats.iframes == 0 {
+            self.stats.health -= stats.damage as i32;
+            self.stats.iframes = 30;
+            self.stats.stunned = self.stats.stunned.max(stats.stun);
+            self.knockback
+                .apply(&stats, self.position, self.wide_swing.direction());
+            true
+        } else {
+            false
+        }
+    }
 }
@@ -1,19 +1,35 @@
-use super::{DamageTransfer, Enemy, EnemyStats, REMOVE_ENEMY_WORKAROUND};
-use crate::collision::{Hitbox, HitboxType};
+use super::{DamageTransfer, Enemy, EnemyCommands, EnemyHandle, EnemyStats, ENEMY_MOVEMENT};
+use crate::assets::StaticAssets;
+use crate::attack::Element;
+use crate::collision::{CollisionFilter, Hitbox, HitboxType, Layers, Ray};
 use crate::get::Get;
+use crate::knockback::{HitOrigin, Knockback};
 use crate::level::Level;
+use crate::pickup::{Pickup, PickupKind};
 use crate::Direction;
 use bevy_reflect::{GetField, Reflect};
 use ggez::graphics::{Canvas, Color, Rect};
-use ggez::{Context, GameError, GameResult};
+use ggez::{Context, GameResult};
 use glam::Vec2;
 
+#[derive(Debug)]
+pub struct BasicEnemyData {
+    mass: f32,
+}
+
+impl Default for BasicEnemyData {
+    fn default() -> Self {
+        Self { mass: 1.0 }
+    }
+}
+
 #[derive(Debug, Reflect)]
 pub struct BasicEnemy {
     #[reflect(ignore)]
     pub position: glam::Vec2,
     pub hurtbox: Hitbox,
     pub stats: EnemyStats,
+    pub knockback: Knockback,
     pub speed: f32,
 }
 
@@ -22,74 +38,123 @@ pub struct OverheadAttack {
 }
 
 impl Enemy for BasicEnemy {
-    fn create(level: &mut Level, ctx: &mut Context) -> GameResult
-    where
-        Self: Sized,
-    {
-        let mut filtered = [
-            &mut level.enemies.basic_enemy,
-            &mut level.enemies.basic_enemy2,
-            &mut level.enemies.basic_enemy3,
-        ]
-        .into_iter()
-        .filter(|value| value.is_none());
-        let next = filtered.next().unwrap();
-
-        let count = filtered.count() as f32;
-        match next.replace(BasicEnemy {
-            position: Vec2::new((100.0 * count) + 100.0, 50.0),
-            hurtbox: Hitbox::point_size(Vec2::ZERO, 30.0),
+    fn new(assets: &StaticAssets) -> Self {
+        BasicEnemy {
+            position: Vec2::ZERO,
+            hurtbox: Hitbox::point_size(Vec2::ZERO, 30.0).with_filter(CollisionFilter::BODY),
             stats: EnemyStats {
                 health: 20,
                 damage: 7,
                 iframes: 0,
+                stunned: 0,
             },
-            speed: 1.0 + (count / 3.0),
-        }) {
-            Some(some) => Err(GameError::CustomError("Enemy already exists".to_string())),
-            None => Ok(()),
+            knockback: Knockback::new(assets.basic_enemy.mass),
+            speed: 1.0,
         }
     }
 
-    fn update(&mut self, level: &mut Level, ctx: &mut Context) -> GameResult {
+    fn create(level: &mut Level, ctx: &mut Context) -> GameResult<EnemyHandle>
+    where
+        Self: Sized,
+    {
+        let count = level.enemies.iter_as::<BasicEnemy>().count() as f32;
+        Ok(level.enemies.insert(Box::new(BasicEnemy {
+            position: Vec2::new((100.0 * count) + 100.0, 50.0),
+            speed: 1.0 + (count / 3.0),
+            ..BasicEnemy::new(level.static_assets)
+        })))
+    }
+
+    fn update(
+        &mut self,
+        level: &mut Level,
+        ctx: &mut Context,
+        commands: &mut EnemyCommands,
+    ) -> GameResult {
         if self.stats.health <= 0 {
-            return GameResult::Err(GameError::CustomError(REMOVE_ENEMY_WORKAROUND.to_string()));
+            commands.drop_item(Pickup::new(PickupKind::Rupee { amount: 1 }, self.position));
+            commands.despawn();
+            return Ok(());
         }
-        let distance = level.protag.position - self.position;
-        self.position += distance.normalize() * self.speed * (distance.length() / 48.0);
         if self.stats.iframes > 0 {
             self.stats.iframes -= 1;
         }
+        if self.knockback.is_active() {
+            let motion = self.knockback.step();
+            self.position = ENEMY_MOVEMENT
+                .resolve(level, &self.hurtbox, self.position, motion)
+                .position;
+            return Ok(());
+        }
+        if self.stats.stunned > 0 {
+            self.stats.stunned -= 1;
+            return Ok(());
+        }
+        // Only chase the protag while there's no wall in the way.
+        let sight = Ray::between(self.position, level.protag.position).with_mask(Layers::SOLID);
+        if level.raycast(&sight, &[]).is_some() {
+            return Ok(());
+        }
+        let distance = level.protag.position - self.position;
+        let motion = distance.normalize() * self.speed * (distance.length() / 48.0);
+        self.position = ENEMY_MOVEMENT
+            .resolve(level, &self.hurtbox, self.position, motion)
+            .position;
         Ok(())
     }
 
     fn draw(&mut self, level: &Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
-        self.hurtbox.draw(&mut ctx.gfx, canvas, self.position, {
-            if self.stats.health > 0 {
-                if self.stats.iframes > 0 {
-                    Some(Color::CYAN)
+        self.hurtbox
+            .draw(&mut ctx.gfx, canvas, &level.camera, self.position, {
+                if self.stats.health > 0 {
+                    if self.stats.iframes > 0 {
+                        Some(Color::CYAN)
+                    } else if self.stats.stunned > 0 {
+                        Some(Color::YELLOW)
+                    } else {
+                        Some(Color {
+                            r: 0.0,
+                            g: 0.5,
+                            b: 0.5,
+                            a: 1.0,
+                        })
+                    }
                 } else {
-                    Some(Color {
-                        r: 0.0,
-                        g: 0.5,
-                        b: 0.5,
-                        a: 1.0,
-                    })
+                    Some(Color::WHITE)
                 }
-            } else {
-                Some(Color::WHITE)
-            }
-        })
+            })
+    }
+
+    fn set_position(&mut self, position: Vec2) {
+        self.position = position;
     }
 
     fn get_hitbox(&self) -> Option<(crate::collision::HitboxType, Vec2)> {
         Some((HitboxType::Singular(&self.hurtbox), self.position))
     }
 
+    fn contact_damage(&self) -> Option<DamageTransfer> {
+        (self.stats.health > 0).then_some(DamageTransfer {
+            damage: self.stats.damage as f32,
+            weight: 1.0,
+            origin: HitOrigin::Position(self.position),
+            stun: 0,
+            element: Element::Physical,
+        })
+    }
+
     fn on_hit(&mut self, stats: DamageTransfer) -> bool {
+        // Stuns don't hurt, so they shouldn't give iframes either, otherwise the sword couldn't follow up.
+        if stats.damage <= 0.0 {
+            self.stats.stunned = self.stats.stunned.max(stats.stun);
+            self.knockback.apply(&stats, self.position, Direction::Down);
+            return stats.stun > 0;
+        }
         if self.stats.iframes == 0 {
             self.stats.health -= stats.damage as i32;
             self.stats.iframes = 30;
+            self.stats.stunned = self.stats.stunned.max(stats.stun);
+            self.knockback.apply(&stats, self.position, Direction::Down);
             true
         } else {
             false
@@ -0,0 +1,105 @@
+use ggez::audio::{SoundSource, Source};
+use ggez::{Context, GameResult};
+
+use super::{Enemy, EnemyHandle};
+use crate::level::Level;
+use crate::pickup::Pickup;
+
+/// Something an enemy wants to happen to itself or the level, once every enemy has finished updating.
+///
+/// Pushed through [`EnemyCommands`] during [`Enemy::update`].
+pub enum EnemyCommand {
+    /// Removes the enemy from the level.
+    Despawn,
+    /// Inserts a new enemy into the level. It will start updating on the next frame.
+    SpawnChild(Box<dyn Enemy>),
+    /// Places a pickup into the level.
+    DropItem(Pickup),
+    /// Plays the sound file at the given path, relative to the resources folder.
+    PlaySound(&'static str),
+    /// Replaces the enemy with a different one, keeping its [`EnemyHandle`].
+    TransformInto(Box<dyn Enemy>),
+}
+
+/// Command queue handed to [`Enemy::update`].
+///
+/// Commands are recorded against the enemy currently being updated, and applied
+/// by [`EnemyContainer::update`](super::EnemyContainer::update) after every enemy has been updated.
+#[derive(Default)]
+pub struct EnemyCommands {
+    current: Option<EnemyHandle>,
+    queue: Vec<(EnemyHandle, EnemyCommand)>,
+}
+
+impl EnemyCommands {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The handle of the enemy that is currently being updated.
+    pub fn handle(&self) -> EnemyHandle {
+        self.current
+            .expect("EnemyCommands used outside of an enemy update")
+    }
+
+    pub fn push(&mut self, command: EnemyCommand) {
+        let handle = self.handle();
+        self.queue.push((handle, command));
+    }
+
+    pub fn despawn(&mut self) {
+        self.push(EnemyCommand::Despawn)
+    }
+
+    pub fn spawn_child(&mut self, enemy: impl Enemy) {
+        self.push(EnemyCommand::SpawnChild(Box::new(enemy)))
+    }
+
+    pub fn drop_item(&mut self, pickup: Pickup) {
+        self.push(EnemyCommand::DropItem(pickup))
+    }
+
+    pub fn play_sound(&mut self, path: &'static str) {
+        self.push(EnemyCommand::PlaySound(path))
+    }
+
+    pub fn transform_into(&mut self, enemy: impl Enemy) {
+        self.push(EnemyCommand::TransformInto(Box::new(enemy)))
+    }
+
+    pub(crate) fn set_current(&mut self, handle: Option<EnemyHandle>) {
+        self.current = handle;
+    }
+
+    /// Applies every queued command in the order they were pushed.
+    ///
+    /// [`Despawn`](EnemyCommand::Despawn) and [`TransformInto`](EnemyCommand::TransformInto) do nothing
+    /// if the enemy is already gone, while items and sounds don't need the enemy at all,
+    /// so an enemy can despawn and drop an item in the same update without caring about the order.
+    /// A sound failing to play doesn't stop the other commands. The first error is returned once they're all applied.
+    pub(crate) fn apply(self, level: &mut Level, ctx: &mut Context) -> GameResult {
+        let mut result = Ok(());
+
+        for (handle, command) in self.queue {
+            match command {
+                EnemyCommand::Despawn => {
+                    level.enemies.remove(handle);
+                }
+                EnemyCommand::SpawnChild(enemy) => {
+                    level.enemies.insert(enemy);
+                }
+                EnemyCommand::DropItem(pickup) => level.pickups.push(pickup),
+                EnemyCommand::PlaySound(path) => {
+                    result = result.and(
+                        Source::new(ctx, path).and_then(|mut source| source.play_detached(ctx)),
+                    );
+                }
+                EnemyCommand::TransformInto(enemy) => {
+                    level.enemies.replace(handle, enemy);
+                }
+            }
+        }
+
+        result
+    }
+}
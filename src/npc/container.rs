@@ -0,0 +1,544 @@
+use std::any::Any;
+use std::fmt::{Debug, Formatter};
+
+use bevy_reflect::utility::NonGenericTypeInfoCell;
+use bevy_reflect::{
+    struct_debug, struct_partial_eq, ApplyError, DynamicStruct, FieldIter, FromReflect,
+    GetTypeRegistration, PartialReflect, Reflect, ReflectMut, ReflectOwned, ReflectRef, Struct,
+    StructInfo, TypeInfo, TypePath, TypeRegistration, Typed,
+};
+use ggez::graphics::Canvas;
+use ggez::{Context, GameError, GameResult};
+use glam::Vec2;
+
+use super::advanced_enemy::AdvancedEnemy;
+use super::basic_enemy::BasicEnemy;
+use super::{Enemy, EnemyCommands};
+use crate::assets::StaticAssets;
+use crate::level::Level;
+
+type CreateEnemy = fn(&mut Level, &mut Context) -> GameResult<EnemyHandle>;
+type NewEnemy = fn(&StaticAssets) -> Box<dyn Enemy>;
+
+/// Every enemy that can be spawned by name, like from a map.
+static ENEMY_TYPES: [(&str, CreateEnemy, NewEnemy); 2] = [
+    ("BasicEnemy", BasicEnemy::create, |assets| {
+        Box::new(BasicEnemy::new(assets))
+    }),
+    ("AdvancedEnemy", AdvancedEnemy::create, |assets| {
+        Box::new(AdvancedEnemy::new(assets))
+    }),
+];
+
+/// A stable reference to an enemy stored inside of an [`EnemyContainer`].
+///
+/// Handles stay valid for as long as the enemy is alive. Once the enemy is removed, its slot may be reused,
+/// but the generation will differ, so any old handles pointing to that slot will simply fail to resolve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Reflect)]
+pub struct EnemyHandle {
+    index: u32,
+    generation: u32,
+}
+
+#[derive(Debug)]
+struct EnemySlot {
+    generation: u32,
+    entry: Option<EnemyEntry>,
+}
+
+#[derive(Debug)]
+struct EnemyEntry {
+    /// The id used to look up this enemy through [`bevy_reflect`], such as with [`Get`](crate::get::Get).
+    name: String,
+    /// Only `None` while the enemy is checked out for [`Enemy::update`] or [`Enemy::draw`].
+    enemy: Option<Box<dyn Enemy>>,
+}
+
+/// Holds every enemy in the level, of any type implementing [`Enemy`].
+///
+/// Enemies are addressed with an [`EnemyHandle`], or through reflection by their id.
+/// Ids are derived from the enemy's type name (`basic_enemy`, `basic_enemy2`, `advanced_enemy`, ...),
+/// and are reflected as if they were fields of this struct.
+#[derive(Debug, TypePath)]
+pub struct EnemyContainer {
+    slots: Vec<EnemySlot>,
+    free: Vec<u32>,
+}
+
+impl EnemyContainer {
+    pub fn new() -> Self {
+        Self {
+            slots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+
+    /// Creates an enemy by its type name, like `BasicEnemy`, and moves it to `position`.
+    pub fn spawn(
+        level: &mut Level,
+        ctx: &mut Context,
+        name: &str,
+        position: Vec2,
+    ) -> GameResult<EnemyHandle> {
+        let (_, create, _) = ENEMY_TYPES
+            .iter()
+            .find(|(type_name, _, _)| *type_name == name)
+            .ok_or_else(|| GameError::CustomError(format!("unknown enemy type `{name}`")))?;
+        let handle = create(level, ctx)?;
+        if let Some(enemy) = level.enemies.get_mut(handle) {
+            enemy.set_position(position);
+        }
+        Ok(handle)
+    }
+
+    /// The name of every enemy type [`EnemyContainer::spawn`] can create.
+    pub fn enemy_types() -> impl Iterator<Item = &'static str> {
+        ENEMY_TYPES.iter().map(|(name, _, _)| *name)
+    }
+
+    /// A fresh enemy by its type name, see [`Enemy::new`]. It still has to be [inserted](EnemyContainer::insert).
+    pub fn new_enemy(assets: &StaticAssets, name: &str) -> Option<Box<dyn Enemy>> {
+        ENEMY_TYPES
+            .iter()
+            .find(|(type_name, _, _)| *type_name == name)
+            .map(|(_, _, new)| new(assets))
+    }
+
+    /// Returns the ids of every enemy currently in the container.
+    pub fn enemy_ids(&self) -> Vec<&str> {
+        self.entries()
+            .map(|(_, entry)| entry.name.as_str())
+            .collect()
+    }
+
+    /// Inserts an enemy, returning a handle that can be used to access or remove it later.
+    ///
+    /// Enemies inserted during [`EnemyContainer::update`] will start updating on the next frame.
+    pub fn insert(&mut self, enemy: Box<dyn Enemy>) -> EnemyHandle {
+        let name = self.next_name(enemy.reflect_short_type_path());
+        let entry = EnemyEntry {
+            name,
+            enemy: Some(enemy),
+        };
+
+        if let Some(index) = self.free.pop() {
+            let slot = &mut self.slots[index as usize];
+            slot.entry = Some(entry);
+            EnemyHandle {
+                index,
+                generation: slot.generation,
+            }
+        } else {
+            self.slots.push(EnemySlot {
+                generation: 0,
+                entry: Some(entry),
+            });
+            EnemyHandle {
+                index: self.slots.len() as u32 - 1,
+                generation: 0,
+            }
+        }
+    }
+
+    /// Removes an enemy, returning it if it was still in the container.
+    ///
+    /// If the enemy is currently being updated or drawn, it is still removed, but `None` is returned
+    /// and the enemy is dropped once it is done.
+    pub fn remove(&mut self, handle: EnemyHandle) -> Option<Box<dyn Enemy>> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        let entry = slot.entry.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(handle.index);
+        entry.enemy
+    }
+
+    /// Swaps out the enemy behind the handle for a different one, returning the old enemy.
+    ///
+    /// The handle stays the same, but the id is regenerated to match the new enemy's type.
+    /// Returns `None` if the handle is no longer valid, in which case the new enemy is dropped.
+    pub fn replace(
+        &mut self,
+        handle: EnemyHandle,
+        enemy: Box<dyn Enemy>,
+    ) -> Option<Box<dyn Enemy>> {
+        self.entry(handle)?;
+        let name = self.next_name(enemy.reflect_short_type_path());
+        let entry = self.entry_mut(handle)?;
+        entry.name = name;
+        entry.enemy.replace(enemy)
+    }
+
+    pub fn contains(&self, handle: EnemyHandle) -> bool {
+        self.entry(handle).is_some()
+    }
+
+    pub fn get(&self, handle: EnemyHandle) -> Option<&dyn Enemy> {
+        self.entry(handle)?.enemy.as_deref()
+    }
+
+    pub fn get_mut(&mut self, handle: EnemyHandle) -> Option<&mut (dyn Enemy + 'static)> {
+        self.entry_mut(handle)?.enemy.as_deref_mut()
+    }
+
+    /// Gets the enemy as its concrete type, if the handle points to an enemy of that type.
+    pub fn get_as<T: Enemy>(&self, handle: EnemyHandle) -> Option<&T> {
+        self.get(handle)?.as_any().downcast_ref()
+    }
+
+    pub fn get_as_mut<T: Enemy>(&mut self, handle: EnemyHandle) -> Option<&mut T> {
+        self.get_mut(handle)?.as_any_mut().downcast_mut()
+    }
+
+    /// Returns the handle of the enemy with the given id.
+    pub fn handle_of(&self, id: &str) -> Option<EnemyHandle> {
+        self.entries()
+            .find(|(_, entry)| entry.name == id)
+            .map(|(handle, _)| handle)
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.len() - self.free.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn handles(&self) -> Vec<EnemyHandle> {
+        self.entries().map(|(handle, _)| handle).collect()
+    }
+
+    /// Iterates over every enemy that is not currently checked out.
+    pub fn iter(&self) -> impl Iterator<Item = (EnemyHandle, &dyn Enemy)> {
+        self.entries()
+            .filter_map(|(handle, entry)| Some((handle, entry.enemy.as_deref()?)))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (EnemyHandle, &mut (dyn Enemy + 'static))> {
+        self.slots
+            .iter_mut()
+            .enumerate()
+            .filter_map(|(index, slot)| {
+                let enemy = slot.entry.as_mut()?.enemy.as_deref_mut()?;
+                let handle = EnemyHandle {
+                    index: index as u32,
+                    generation: slot.generation,
+                };
+                Some((handle, enemy))
+            })
+    }
+
+    /// Iterates over every enemy of the concrete type `T`.
+    pub fn iter_as<T: Enemy>(&self) -> impl Iterator<Item = (EnemyHandle, &T)> {
+        self.iter()
+            .filter_map(|(handle, enemy)| Some((handle, enemy.as_any().downcast_ref::<T>()?)))
+    }
+
+    pub fn iter_as_mut<T: Enemy>(&mut self) -> impl Iterator<Item = (EnemyHandle, &mut T)> {
+        self.iter_mut()
+            .filter_map(|(handle, enemy)| Some((handle, enemy.as_any_mut().downcast_mut::<T>()?)))
+    }
+
+    /// Updates every enemy, then applies the [`EnemyCommands`] they queued up.
+    ///
+    /// An enemy failing to update doesn't stop the others, or the commands from being applied.
+    /// The first error, from an update or from applying the commands, is returned once the commands are applied.
+    pub(crate) fn update(level: &mut Level, ctx: &mut Context) -> GameResult {
+        let mut commands = EnemyCommands::new();
+        let mut result = Ok(());
+
+        for handle in level.enemies.handles() {
+            let Some(mut enemy) = level.enemies.check_out(handle) else {
+                continue;
+            };
+
+            commands.set_current(Some(handle));
+            let update = enemy.update(level, ctx, &mut commands);
+
+            level.enemies.check_in(handle, enemy);
+
+            result = result.and(update);
+        }
+        commands.set_current(None);
+
+        let applied = commands.apply(level, ctx);
+        result.and(applied)
+    }
+
+    pub(crate) fn draw(level: &mut Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        for handle in level.enemies.handles() {
+            let Some(mut enemy) = level.enemies.check_out(handle) else {
+                continue;
+            };
+
+            let draw = enemy.draw(level, ctx, canvas);
+
+            level.enemies.check_in(handle, enemy);
+
+            draw?;
+        }
+
+        Ok(())
+    }
+
+    /// Temporarily takes the enemy out of its slot, so that it can be given mutable access to the level.
+    ///
+    /// Must be followed up by [`EnemyContainer::check_in`].
+    fn check_out(&mut self, handle: EnemyHandle) -> Option<Box<dyn Enemy>> {
+        self.entry_mut(handle)?.enemy.take()
+    }
+
+    /// Returns the enemy to its slot. If the enemy was removed while it was checked out, it is dropped instead.
+    fn check_in(&mut self, handle: EnemyHandle, enemy: Box<dyn Enemy>) {
+        if let Some(entry) = self.entry_mut(handle) {
+            entry.enemy = Some(enemy);
+        }
+    }
+
+    fn entry(&self, handle: EnemyHandle) -> Option<&EnemyEntry> {
+        let slot = self.slots.get(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.entry.as_ref()
+    }
+
+    fn entry_mut(&mut self, handle: EnemyHandle) -> Option<&mut EnemyEntry> {
+        let slot = self.slots.get_mut(handle.index as usize)?;
+        if slot.generation != handle.generation {
+            return None;
+        }
+        slot.entry.as_mut()
+    }
+
+    fn entries(&self) -> impl Iterator<Item = (EnemyHandle, &EnemyEntry)> {
+        self.slots.iter().enumerate().filter_map(|(index, slot)| {
+            let handle = EnemyHandle {
+                index: index as u32,
+                generation: slot.generation,
+            };
+            Some((handle, slot.entry.as_ref()?))
+        })
+    }
+
+    /// Entries that can currently be reflected, that is, entries that are not checked out.
+    fn reflected_entries(&self) -> impl Iterator<Item = &EnemyEntry> {
+        self.entries()
+            .map(|(_, entry)| entry)
+            .filter(|entry| entry.enemy.is_some())
+    }
+
+    /// Generates an id from the type name, such as `basic_enemy` for [`BasicEnemy`].
+    ///
+    /// Duplicate ids get a number appended to them, starting from 2.
+    fn next_name(&self, short_type_path: &str) -> String {
+        let mut base = String::new();
+        for (i, char) in short_type_path.chars().enumerate() {
+            if char.is_uppercase() && i != 0 {
+                base.push('_');
+            }
+            base.push(char.to_ascii_lowercase());
+        }
+
+        let taken = |name: &str| self.entries().any(|(_, entry)| entry.name == name);
+
+        if !taken(&base) {
+            return base;
+        }
+        (2..)
+            .map(|count| format!("{base}{count}"))
+            .find(|name| !taken(name))
+            .unwrap()
+    }
+}
+
+// Reflection is implemented by hand so that every enemy shows up as a field named after its id,
+// which keeps lookups like [`Get`](crate::get::Get) working without the container knowing about every enemy type.
+
+impl Struct for EnemyContainer {
+    fn field(&self, name: &str) -> Option<&dyn PartialReflect> {
+        self.reflected_entries()
+            .find(|entry| entry.name == name)
+            .and_then(|entry| entry.enemy.as_deref())
+            .map(PartialReflect::as_partial_reflect)
+    }
+
+    fn field_mut(&mut self, name: &str) -> Option<&mut dyn PartialReflect> {
+        self.slots
+            .iter_mut()
+            .filter_map(|slot| slot.entry.as_mut())
+            .find(|entry| entry.name == name)
+            .and_then(|entry| entry.enemy.as_deref_mut())
+            .map(PartialReflect::as_partial_reflect_mut)
+    }
+
+    fn field_at(&self, index: usize) -> Option<&dyn PartialReflect> {
+        self.reflected_entries()
+            .nth(index)
+            .and_then(|entry| entry.enemy.as_deref())
+            .map(PartialReflect::as_partial_reflect)
+    }
+
+    fn field_at_mut(&mut self, index: usize) -> Option<&mut dyn PartialReflect> {
+        self.slots
+            .iter_mut()
+            .filter_map(|slot| slot.entry.as_mut()?.enemy.as_deref_mut())
+            .nth(index)
+            .map(PartialReflect::as_partial_reflect_mut)
+    }
+
+    fn name_at(&self, index: usize) -> Option<&str> {
+        self.reflected_entries()
+            .nth(index)
+            .map(|entry| entry.name.as_str())
+    }
+
+    fn field_len(&self) -> usize {
+        self.reflected_entries().count()
+    }
+
+    fn iter_fields(&self) -> FieldIter<'_> {
+        FieldIter::new(self)
+    }
+
+    fn clone_dynamic(&self) -> DynamicStruct {
+        let mut dynamic = DynamicStruct::default();
+        dynamic.set_represented_type(self.get_represented_type_info());
+        for entry in self.reflected_entries() {
+            if let Some(enemy) = entry.enemy.as_deref() {
+                dynamic.insert_boxed(entry.name.clone(), enemy.clone_value());
+            }
+        }
+        dynamic
+    }
+}
+
+impl PartialReflect for EnemyContainer {
+    fn get_represented_type_info(&self) -> Option<&'static TypeInfo> {
+        Some(<Self as Typed>::type_info())
+    }
+
+    fn into_partial_reflect(self: Box<Self>) -> Box<dyn PartialReflect> {
+        self
+    }
+
+    fn as_partial_reflect(&self) -> &dyn PartialReflect {
+        self
+    }
+
+    fn as_partial_reflect_mut(&mut self) -> &mut dyn PartialReflect {
+        self
+    }
+
+    fn try_into_reflect(self: Box<Self>) -> Result<Box<dyn Reflect>, Box<dyn PartialReflect>> {
+        Ok(self)
+    }
+
+    fn try_as_reflect(&self) -> Option<&dyn Reflect> {
+        Some(self)
+    }
+
+    fn try_as_reflect_mut(&mut self) -> Option<&mut dyn Reflect> {
+        Some(self)
+    }
+
+    fn try_apply(&mut self, value: &dyn PartialReflect) -> Result<(), ApplyError> {
+        let struct_value = value.reflect_ref().as_struct()?;
+
+        for (i, value) in struct_value.iter_fields().enumerate() {
+            let name = struct_value.name_at(i).unwrap();
+            if let Some(field) = self.field_mut(name) {
+                field.try_apply(value)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn reflect_ref(&self) -> ReflectRef<'_> {
+        ReflectRef::Struct(self)
+    }
+
+    fn reflect_mut(&mut self) -> ReflectMut<'_> {
+        ReflectMut::Struct(self)
+    }
+
+    fn reflect_owned(self: Box<Self>) -> ReflectOwned {
+        ReflectOwned::Struct(self)
+    }
+
+    fn clone_value(&self) -> Box<dyn PartialReflect> {
+        Box::new(self.clone_dynamic())
+    }
+
+    fn reflect_partial_eq(&self, value: &dyn PartialReflect) -> Option<bool> {
+        struct_partial_eq(self, value)
+    }
+
+    fn debug(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "EnemyContainer(")?;
+        struct_debug(self, f)?;
+        write!(f, ")")
+    }
+}
+
+impl Reflect for EnemyContainer {
+    fn into_any(self: Box<Self>) -> Box<dyn Any> {
+        self
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn into_reflect(self: Box<Self>) -> Box<dyn Reflect> {
+        self
+    }
+
+    fn as_reflect(&self) -> &dyn Reflect {
+        self
+    }
+
+    fn as_reflect_mut(&mut self) -> &mut dyn Reflect {
+        self
+    }
+
+    fn set(&mut self, value: Box<dyn Reflect>) -> Result<(), Box<dyn Reflect>> {
+        *self = value.take()?;
+        Ok(())
+    }
+}
+
+impl Typed for EnemyContainer {
+    /// The enemies are only known at runtime, so the type info lists no fields.
+    fn type_info() -> &'static TypeInfo {
+        static CELL: NonGenericTypeInfoCell = NonGenericTypeInfoCell::new();
+        CELL.get_or_set(|| TypeInfo::Struct(StructInfo::new::<Self>(&[])))
+    }
+}
+
+impl FromReflect for EnemyContainer {
+    /// Enemies are stored as trait objects, so there is no way to rebuild them from reflected data.
+    fn from_reflect(_reflect: &dyn PartialReflect) -> Option<Self> {
+        None
+    }
+}
+
+impl GetTypeRegistration for EnemyContainer {
+    fn get_type_registration() -> TypeRegistration {
+        TypeRegistration::of::<Self>()
+    }
+}
+
+impl Debug for dyn Enemy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.debug(f)
+    }
+}
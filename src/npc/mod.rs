@@ -2,24 +2,29 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 use std::ops::{Deref, DerefMut};
 
+use crate::assets::StaticAssets;
+use crate::attack::{Element, Hit};
 use crate::collision::HitboxType;
-use crate::level::Level;
-use advanced_enemy::AdvancedEnemy;
-use basic_enemy::BasicEnemy;
+use crate::knockback::HitOrigin;
+use crate::level::{Collider, Level};
+use crate::movement::Movement;
 use bevy_reflect::erased_serde::Serialize;
 use bevy_reflect::prelude::ReflectDefault;
-use bevy_reflect::{
-    DynamicTyped, FromReflect, GetField, PartialReflect, Reflect, Reflectable, TypeData,
-};
+use bevy_reflect::{FromReflect, GetField, PartialReflect, Reflect, Reflectable, TypeData};
 use ggez::graphics::Canvas;
-use ggez::{Context, GameError, GameResult};
+use ggez::{Context, GameResult};
 use glam::Vec2;
 
-/// If this error message is returned in [`Enemy::update`], then the enemy will be removed
-pub const REMOVE_ENEMY_WORKAROUND: &str = "Workaround for remove enemy";
-
 pub mod advanced_enemy;
 pub mod basic_enemy;
+pub mod commands;
+pub mod container;
+
+pub use commands::{EnemyCommand, EnemyCommands};
+pub use container::{EnemyContainer, EnemyHandle};
+
+/// Enemies are stopped by walls, but aren't nudged around corners like the protag.
+pub(crate) static ENEMY_MOVEMENT: Movement = Movement::new(0.0);
 
 /// Information sent to the player or enemy when they have taken a hit, and need to calculate their new health
 #[derive(Debug, Reflect, Clone)]
@@ -27,6 +32,24 @@ pub struct DamageTransfer {
     pub damage: f32,
     /// for knockback
     pub weight: f32,
+    /// Where the hit came from, which decides the direction of the knockback.
+    pub origin: HitOrigin,
+    /// How many frames the victim is stunned for, unable to move or attack.
+    pub stun: usize,
+    pub element: Element,
+}
+
+impl DamageTransfer {
+    pub fn with_origin(mut self, origin: HitOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+}
+
+/// A hitbox belonging to an enemy that can hurt the protag, see [`Enemy::resolve_attacks`].
+pub struct EnemyAttack<'a> {
+    pub hitbox: HitboxType<'a, 'a, 'a>,
+    pub offset: Vec2,
 }
 
 #[derive(Debug, Reflect, Clone)]
@@ -34,118 +57,66 @@ pub struct EnemyStats {
     pub health: i32,
     pub damage: i32,
     pub iframes: usize,
+    /// Frames left until the enemy can act again.
+    pub stunned: usize,
 }
 
 pub trait Enemy: Reflect {
-    /// The enemy will be initialized and inserted into the level, wherever it belongs.
-    fn create(level: &mut Level, ctx: &mut Context) -> GameResult
+    /// A fresh enemy at the origin, not inserted into any level yet.
+    fn new(assets: &StaticAssets) -> Self
+    where
+        Self: Sized;
+
+    /// The enemy will be initialized and inserted into the level's [`EnemyContainer`].
+    fn create(level: &mut Level, ctx: &mut Context) -> GameResult<EnemyHandle>
     where
         Self: Sized;
 
-    fn update(&mut self, level: &mut Level, ctx: &mut Context) -> GameResult;
+    /// Anything that should happen to the enemy or the level as a result of the update,
+    /// like despawning or dropping an item, is pushed into `commands` rather than returned as an error.
+    fn update(
+        &mut self,
+        level: &mut Level,
+        ctx: &mut Context,
+        commands: &mut EnemyCommands,
+    ) -> GameResult;
 
     fn draw(&mut self, level: &Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult;
 
+    /// Moves the enemy straight to a new position, like where a map placed it.
+    fn set_position(&mut self, position: Vec2);
+
     fn get_hitbox(&self) -> Option<(HitboxType, Vec2)> {
         None
     }
 
-    fn on_hit(&mut self, stats: DamageTransfer) -> bool {
-        false
+    /// Returns every attack that is currently able to hurt the protag.
+    ///
+    /// Attacks that are winding up or have finished should not be returned.
+    fn get_attacks(&self) -> Vec<EnemyAttack<'_>> {
+        Vec::new()
     }
-}
 
-#[derive(Debug, Reflect)]
-pub struct EnemyContainer {
-    #[reflect(ignore)]
-    pub missing_enemy: Option<()>,
-    pub basic_enemy: Option<BasicEnemy>,
-    pub basic_enemy2: Option<BasicEnemy>,
-    pub basic_enemy3: Option<BasicEnemy>,
-    pub advanced_enemy: Option<AdvancedEnemy>,
-}
-
-impl EnemyContainer {
-    pub fn new() -> Self {
-        Self {
-            missing_enemy: None,
-            basic_enemy: None,
-            basic_enemy2: None,
-            basic_enemy3: None,
-            advanced_enemy: None,
-        }
-    }
-
-    pub fn init(level: &mut Level, ctx: &mut Context) {
-        BasicEnemy::create(level, ctx).unwrap();
-        BasicEnemy::create(level, ctx).unwrap();
-        BasicEnemy::create(level, ctx).unwrap();
-        AdvancedEnemy::create(level, ctx).unwrap();
-    }
-
-    pub fn enemy_ids(&self) -> &[&'static str] {
-        self.reflect_type_info().as_struct().unwrap().field_names()
-    }
-
-    pub(crate) fn update(level: &mut Level, ctx: &mut Context) -> GameResult {
-        level.enemies.basic_enemy =
-            Self::update_enemy((&mut level.enemies).basic_enemy.take(), level, ctx).1;
-        level.enemies.basic_enemy2 =
-            Self::update_enemy((&mut level.enemies).basic_enemy2.take(), level, ctx).1;
-        level.enemies.basic_enemy3 =
-            Self::update_enemy((&mut level.enemies).basic_enemy3.take(), level, ctx).1;
-        level.enemies.advanced_enemy =
-            Self::update_enemy((&mut level.enemies).advanced_enemy.take(), level, ctx).1;
-
-        Ok(())
-    }
-
-    pub(crate) fn draw(level: &mut Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
-        level.enemies.basic_enemy =
-            Self::draw_enemy(level.enemies.basic_enemy.take(), level, ctx, canvas);
-        level.enemies.basic_enemy2 =
-            Self::draw_enemy(level.enemies.basic_enemy2.take(), level, ctx, canvas);
-        level.enemies.basic_enemy3 =
-            Self::draw_enemy(level.enemies.basic_enemy3.take(), level, ctx, canvas);
-        level.enemies.advanced_enemy =
-            Self::draw_enemy(level.enemies.advanced_enemy.take(), level, ctx, canvas);
-        Ok(())
+    /// Checks the enemy's attacks against a target through [`attack::resolve_hit`](crate::attack::resolve_hit),
+    /// returning the first that lands. Like the protag's items, enemies keep a [`HitHistory`](crate::attack::HitHistory)
+    /// for each use of an attack, so a target is only hit once per hit group.
+    fn resolve_attacks(
+        &mut self,
+        _target: Collider,
+        _hurtbox: HitboxType,
+        _target_offset: Vec2,
+    ) -> Option<Hit> {
+        None
     }
 
-    fn update_enemy<T: Enemy>(
-        mut enemy: Option<T>,
-        level: &mut Level,
-        ctx: &mut Context,
-    ) -> (GameResult, Option<T>) {
-        (
-            if let Some(some) = &mut enemy {
-                let update = some.update(level, ctx);
-
-                if let Err(ref err) = update {
-                    if let GameError::CustomError(err) = err {
-                        if err == REMOVE_ENEMY_WORKAROUND {
-                            return (Ok(()), None);
-                        }
-                    }
-                }
-
-                update
-            } else {
-                Ok(())
-            },
-            enemy,
-        )
+    /// The damage dealt to the protag for touching the hitbox from [`Enemy::get_hitbox`].
+    ///
+    /// If `None`, then the enemy can be touched safely.
+    fn contact_damage(&self) -> Option<DamageTransfer> {
+        None
     }
 
-    fn draw_enemy<T: Enemy>(
-        mut enemy: Option<T>,
-        level: &mut Level,
-        ctx: &mut Context,
-        canvas: &mut Canvas,
-    ) -> Option<T> {
-        if let Some(some) = &mut enemy {
-            some.draw(level, ctx, canvas).unwrap();
-        }
-        enemy
+    fn on_hit(&mut self, stats: DamageTransfer) -> bool {
+        false
     }
 }
@@ -0,0 +1,127 @@
+use crate::collision::{CollisionFilter, Hitbox};
+use crate::level::Level;
+use crate::protag::items::ItemType;
+use bevy_reflect::{Enum, Reflect};
+use ggez::graphics::{Canvas, Color};
+use ggez::{Context, GameResult};
+use glam::Vec2;
+
+/// One of each kind of pickup, which maps can place by name.
+pub(crate) static PICKUP_KINDS: [PickupKind; 4] = [
+    PickupKind::Rupee { amount: 1 },
+    PickupKind::Arrows { amount: 1 },
+    PickupKind::Bombs { amount: 1 },
+    PickupKind::Item {
+        item: ItemType::None,
+    },
+];
+
+/// Something lying on the floor that the protag can collect by walking over it.
+#[derive(Debug, Reflect, Clone)]
+pub struct Pickup {
+    pub kind: PickupKind,
+    #[reflect(ignore)]
+    pub position: Vec2,
+    pub hitbox: Hitbox,
+}
+
+#[derive(Debug, Reflect, Clone, Copy, PartialEq)]
+pub enum PickupKind {
+    Rupee {
+        amount: u32,
+    },
+    Arrows {
+        amount: u32,
+    },
+    Bombs {
+        amount: u32,
+    },
+    /// Adds an item to the protag's [`Inventory`](crate::protag::inventory::Inventory), if they don't have it yet.
+    Item {
+        item: ItemType,
+    },
+}
+
+impl PickupKind {
+    /// A pickup of one of whatever kind is named, like `Rupee`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        PICKUP_KINDS
+            .iter()
+            .find(|kind| kind.variant_name() == name)
+            .copied()
+    }
+
+    pub fn names() -> impl Iterator<Item = &'static str> {
+        PICKUP_KINDS.iter().map(Enum::variant_name)
+    }
+}
+
+impl Pickup {
+    pub fn new(kind: PickupKind, position: Vec2) -> Self {
+        Self {
+            kind,
+            position,
+            hitbox: Hitbox::point_size(Vec2::ZERO, 20.0).with_filter(CollisionFilter::PICKUP),
+        }
+    }
+
+    /// Gives the contents of the pickup to the protag.
+    pub fn collect(self, level: &mut Level) {
+        let data = &level.static_assets.protag.inventory;
+        match self.kind {
+            PickupKind::Rupee { amount } => level.protag.inventory.rupees += amount,
+            PickupKind::Arrows { amount } => {
+                let inventory = &mut level.protag.inventory;
+                inventory.arrows = (inventory.arrows + amount).min(data.bow.max_arrows);
+            }
+            PickupKind::Bombs { amount } => {
+                let inventory = &mut level.protag.inventory;
+                inventory.bombs = (inventory.bombs + amount).min(data.bomb.max_bombs);
+            }
+            PickupKind::Item { item } => {
+                let inventory = &mut level.protag.inventory;
+                if !inventory.contains(item) {
+                    if let Some(item) = item.create() {
+                        inventory.insert(item);
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn update(level: &mut Level, _ctx: &mut Context) {
+        let protag = &level.protag;
+        let (collected, remaining) = level.pickups.drain(..).partition(|pickup: &Pickup| {
+            pickup
+                .hitbox
+                .colliding_single(&protag.hurtbox, pickup.position, protag.position)
+        });
+        level.pickups = remaining;
+
+        for pickup in collected {
+            pickup.collect(level);
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self.kind {
+            PickupKind::Rupee { .. } => Color::GREEN,
+            PickupKind::Arrows { .. } => Color::from_rgb(160, 100, 40),
+            PickupKind::Bombs { .. } => Color::BLUE,
+            PickupKind::Item { .. } => Color::YELLOW,
+        }
+    }
+
+    pub fn draw(level: &Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        for pickup in level.pickups.iter() {
+            pickup.hitbox.draw(
+                &mut ctx.gfx,
+                canvas,
+                &level.camera,
+                pickup.position,
+                Some(pickup.color()),
+            )?;
+        }
+        Ok(())
+    }
+}
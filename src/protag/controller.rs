@@ -1,62 +1,112 @@
+use crate::camera::HURT_TRAUMA;
 use crate::collision::HitboxType;
 use crate::get::Access;
-use crate::level::Level;
-use crate::npc::basic_enemy::BasicEnemy;
-use crate::npc::Enemy;
+use crate::level::{Collider, Level};
+use crate::movement::Movement;
+use crate::npc::EnemyHandle;
 use crate::Direction;
-use bevy_reflect::{GetField, Reflect};
+use bevy_reflect::Reflect;
 use ggez::input::keyboard::{KeyCode, KeyboardContext};
 use glam::Vec2;
 
-use super::Protag;
-
 #[derive(Debug, Reflect)]
 pub struct ProtagController {
     pub can_move: bool,
     pub can_turn: bool,
-    pub hurt: bool,
 }
 
 pub(crate) static PLAYER_SPEED: f32 = 6.0;
 
+/// The protag gets nudged around corners up to about a third of their width.
+pub(crate) static PLAYER_MOVEMENT: Movement = Movement::new(24.0);
+
 impl ProtagController {
     pub fn update(level: &mut Level, ctx: &mut ggez::Context) {
         let input = get_input_axis(&ctx.keyboard);
+        let speed = level
+            .tilemap
+            .tile_at(level.protag.position)
+            .map_or(PLAYER_SPEED, |tile| PLAYER_SPEED * tile.speed);
 
-        if level.protag.controller.can_turn {
-            level.protag.direction = get_direction(input, level.protag.direction)
-        }
+        let protag = &mut level.protag;
+        // The current item decides whether the protag can move or turn while it's in use.
+        (protag.controller.can_move, protag.controller.can_turn) =
+            protag.inventory.current_mut().map_or((true, true), |item| {
+                if item.active() {
+                    (item.can_move(), item.can_turn())
+                } else {
+                    (true, true)
+                }
+            });
 
-        if level.protag.controller.can_move {
-            level.protag.position += input.normalize_or_zero() * PLAYER_SPEED
-        }
+        let motion = if protag.knockback.is_active() {
+            protag.knockback.step()
+        } else {
+            if protag.controller.can_turn {
+                protag.direction = get_direction(input, protag.direction)
+            }
 
-        if let Some(enemy) = level
-            .enemies
-            .get_field_mut::<Option<BasicEnemy>>("basic_enemy")
-            .unwrap()
-            .as_mut()
-        {
-            Self::handle_enemy_collision(&mut level.protag, enemy).unwrap();
+            if protag.controller.can_move {
+                input.normalize_or_zero() * speed
+            } else {
+                Vec2::ZERO
+            }
         };
+
+        let protag = &level.protag;
+        level.protag.position = PLAYER_MOVEMENT
+            .resolve(level, &protag.hurtbox, protag.position, motion)
+            .position;
+
+        let protag = &level.protag;
+        level.colliders.insert_hitbox(
+            Collider::Protag,
+            HitboxType::Singular(&protag.hurtbox),
+            protag.position,
+        );
+        Self::handle_enemy_collision(level);
     }
 
-    pub(crate) fn handle_enemy_collision(
-        protag: &mut Protag,
-        enemy: &mut impl Enemy,
-    ) -> Result<bool, ()> {
-        let Some((enemy_hitbox, enemy_offset)) = enemy.get_hitbox() else {
-            return Err(());
-        };
-        if protag
-            .hurtbox
-            .colliding(enemy_hitbox, protag.position, enemy_offset)
-        {
-            protag.controller.hurt = true;
-            Ok(true)
-        } else {
-            protag.controller.hurt = false;
-            Ok(false)
+    /// Damages the protag with the first enemy attack or enemy body it's touching, shaking the screen if it lands.
+    ///
+    /// Only enemies whose bounding boxes in [`Level::colliders`] overlap the protag's hurtbox are checked,
+    /// each of them once.
+    pub(crate) fn handle_enemy_collision(level: &mut Level) {
+        let protag = &level.protag;
+        let mut candidates: Vec<EnemyHandle> = level
+            .colliders
+            .query_hitbox(HitboxType::Singular(&protag.hurtbox), protag.position)
+            .into_iter()
+            .filter_map(Collider::enemy)
+            .collect();
+        candidates.dedup();
+
+        let protag = &mut level.protag;
+        for handle in candidates {
+            let Some(enemy) = level.enemies.get_mut(handle) else {
+                continue;
+            };
+            let hit = enemy.resolve_attacks(
+                Collider::Protag,
+                HitboxType::Singular(&protag.hurtbox),
+                protag.position,
+            );
+            if hit.is_some_and(|hit| protag.on_hit(hit.damage, &level.static_assets.protag)) {
+                level.camera.add_trauma(HURT_TRAUMA);
+                return;
+            }
+
+            let (Some((hitbox, offset)), Some(damage)) =
+                (enemy.get_hitbox(), enemy.contact_damage())
+            else {
+                continue;
+            };
+            if protag.hurtbox.colliding(hitbox, protag.position, offset)
+                && protag.on_hit(damage, &level.static_assets.protag)
+            {
+                level.camera.add_trauma(HURT_TRAUMA);
+                return;
+            }
         }
     }
 
@@ -64,7 +114,6 @@ impl ProtagController {
         Self {
             can_move: true,
             can_turn: true,
-            hurt: false,
         }
     }
 }
@@ -1,36 +1,160 @@
-use bevy_reflect::prelude::ReflectDefault;
 use bevy_reflect::Reflect;
 use ggez::graphics::Canvas;
 use ggez::Context;
 
 use crate::level::Level;
+use crate::Game;
 
 use super::items::sword::Sword;
-use super::items::ItemType;
+use super::items::{ItemType, ProtagItem};
 
 #[derive(Debug, Reflect)]
 pub struct Inventory {
-    // #[reflect(ignore)]
-    pub sword: Sword,
-    // #[reflect(ignore)]
+    /// Every item the protag is carrying, starting with just the sword. The rest are found as
+    /// [item pickups](crate::pickup::PickupKind::Item). A slot is left empty while its item is checked out for an update.
+    ///
+    /// Items are trait objects, so they're left out of the inventory's reflection. See [`Inventory::reflect_item`] instead.
+    #[reflect(ignore)]
+    items: Vec<Option<Box<dyn ProtagItem>>>,
     pub current_item: ItemType,
+    pub rupees: u32,
+    pub arrows: u32,
+    pub bombs: u32,
 }
 
 impl Inventory {
     pub fn new() -> Self {
         Self {
+            items: vec![Some(Box::new(Sword::default()))],
             current_item: ItemType::Sword,
-            sword: Sword::default(),
+            rupees: 0,
+            arrows: Game::static_assets().protag.inventory.bow.start_arrows,
+            bombs: Game::static_assets().protag.inventory.bomb.start_bombs,
+        }
+    }
+
+    /// Adds an item to the inventory, replacing any item of the same type.
+    pub fn insert(&mut self, item: Box<dyn ProtagItem>) {
+        let item_type = item.item_type();
+        match self.index_of(item_type) {
+            Some(index) => self.items[index] = Some(item),
+            None => self.items.push(Some(item)),
+        }
+    }
+
+    pub fn contains(&self, item_type: ItemType) -> bool {
+        self.index_of(item_type).is_some()
+    }
+
+    pub fn get(&self, item_type: ItemType) -> Option<&dyn ProtagItem> {
+        self.items().find(|item| item.item_type() == item_type)
+    }
+
+    pub fn get_mut(&mut self, item_type: ItemType) -> Option<&mut dyn ProtagItem> {
+        self.items
+            .iter_mut()
+            .flatten()
+            .find(|item| item.item_type() == item_type)
+            .map(|item| &mut **item)
+    }
+
+    pub fn get_as<T: ProtagItem>(&self) -> Option<&T> {
+        self.items().find_map(|item| item.as_any().downcast_ref())
+    }
+
+    pub fn get_as_mut<T: ProtagItem>(&mut self) -> Option<&mut T> {
+        self.items
+            .iter_mut()
+            .flatten()
+            .find_map(|item| item.as_any_mut().downcast_mut())
+    }
+
+    /// The item the protag currently has out, if any.
+    pub fn current(&self) -> Option<&dyn ProtagItem> {
+        self.get(self.current_item)
+    }
+
+    pub fn current_mut(&mut self) -> Option<&mut dyn ProtagItem> {
+        self.get_mut(self.current_item)
+    }
+
+    /// The item as a [`Reflect`], so that its fields can be looked up by name, such as with [`Get`](crate::get::Get).
+    pub fn reflect_item(&self, item_type: ItemType) -> Option<&dyn Reflect> {
+        self.get(item_type).map(|item| item.as_reflect())
+    }
+
+    pub fn reflect_item_mut(&mut self, item_type: ItemType) -> Option<&mut dyn Reflect> {
+        self.get_mut(item_type).map(|item| item.as_reflect_mut())
+    }
+
+    /// Every item in the inventory, in the order they were added.
+    pub fn items(&self) -> impl Iterator<Item = &dyn ProtagItem> {
+        self.items.iter().flatten().map(|item| &**item)
+    }
+
+    /// Switches the current item, running the unequip and equip hooks of the old and new items.
+    ///
+    /// Returns false without switching if the protag hasn't acquired the item,
+    /// or if the current item is still [active](ProtagItem::active).
+    pub fn equip(level: &mut Level, item_type: ItemType) -> bool {
+        let inventory = &mut level.protag.inventory;
+        let previous = inventory.current_item;
+        if previous == item_type {
+            return true;
+        }
+        if !inventory.contains(item_type)
+            || inventory.current_mut().is_some_and(|item| item.active())
+        {
+            return false;
+        }
+        Self::with_item(level, previous, |item, level| item.on_unequip(level));
+        level.protag.inventory.current_item = item_type;
+        Self::with_item(level, item_type, |item, level| item.on_equip(level));
+        true
+    }
+
+    /// Runs the current item's update, then every item's world update.
+    pub fn update(level: &mut Level, ctx: &mut Context) {
+        for index in 0..level.protag.inventory.items.len() {
+            let Some(mut item) = level.protag.inventory.items[index].take() else {
+                continue;
+            };
+            if item.item_type() == level.protag.inventory.current_item {
+                item.update(level, ctx);
+            }
+            item.update_world(level, ctx);
+            level.protag.inventory.items[index] = Some(item);
         }
     }
 
     pub fn draw(level: &mut Level, ctx: &mut Context, canvas: &mut Canvas) {
-        match level.protag.inventory.current_item {
-            ItemType::None => (),
-            ItemType::Sword => Sword::draw(level, ctx, canvas),
-            ItemType::Boomerang => todo!(),
-            ItemType::Bow => todo!(),
-            ItemType::Bomb => todo!(),
-        };
+        let level: &Level = level;
+        let inventory = &level.protag.inventory;
+        for item in inventory.items() {
+            if item.item_type() == inventory.current_item {
+                item.draw(level, ctx, canvas);
+            }
+            item.draw_world(level, ctx, canvas);
+        }
+    }
+
+    /// Takes the item out of the inventory while `f` runs, so that it can be used alongside the rest of the level.
+    fn with_item<R>(
+        level: &mut Level,
+        item_type: ItemType,
+        f: impl FnOnce(&mut dyn ProtagItem, &mut Level) -> R,
+    ) -> Option<R> {
+        let index = level.protag.inventory.index_of(item_type)?;
+        let mut item = level.protag.inventory.items[index].take()?;
+        let result = f(&mut *item, level);
+        level.protag.inventory.items[index] = Some(item);
+        Some(result)
+    }
+
+    fn index_of(&self, item_type: ItemType) -> Option<usize> {
+        self.items.iter().position(|item| {
+            item.as_ref()
+                .is_some_and(|item| item.item_type() == item_type)
+        })
     }
 }
@@ -0,0 +1,234 @@
+use super::inventory::Inventory;
+use super::items::ItemType;
+use crate::camera::Camera;
+use crate::level::Level;
+use crate::sprite::{Sprite, SpriteData};
+use crate::Game;
+use bevy_reflect::Reflect;
+use ggez::graphics::{Canvas, Color, DrawParam, Quad, Text};
+use ggez::input::keyboard::KeyCode;
+use ggez::Context;
+use glam::Vec2;
+
+/// Every item in the order it's laid out in the subscreen grid, left to right then top to bottom.
+/// Slots for items the protag hasn't acquired yet are left empty.
+pub(crate) static SLOTS: [ItemType; 4] = [
+    ItemType::Sword,
+    ItemType::Boomerang,
+    ItemType::Bow,
+    ItemType::Bomb,
+];
+
+pub(crate) static COLUMNS: usize = 2;
+
+/// Width and height of a single slot in the grid, in pixels.
+static SLOT_SIZE: f32 = 64.0;
+static SLOT_PADDING: f32 = 12.0;
+
+pub(crate) static TOGGLE_KEY: KeyCode = KeyCode::Return;
+pub(crate) static PREVIOUS_KEY: KeyCode = KeyCode::Q;
+pub(crate) static NEXT_KEY: KeyCode = KeyCode::E;
+
+/// Where the current item is shown on the HUD, underneath the health bar.
+static HUD_POSITION: Vec2 = Vec2::new(20.0, 48.0);
+
+/// The LttP style pause menu, where the protag picks which item they're holding.
+///
+/// While it's open, the rest of the level is paused. The current item can also be cycled
+/// without opening the subscreen, using the previous and next keys.
+#[derive(Debug, Reflect)]
+pub struct ItemSelect {
+    pub open: bool,
+    /// Index into [`SLOTS`] of the highlighted slot.
+    pub cursor: usize,
+    icons: Vec<ItemIcon>,
+}
+
+#[derive(Debug, Reflect)]
+struct ItemIcon {
+    item: ItemType,
+    /// Missing if the texture couldn't be loaded, in which case the name of the item is drawn instead.
+    sprite: Option<Sprite>,
+}
+
+impl ItemSelect {
+    pub fn new(ctx: &mut Context) -> Self {
+        let icons = SLOTS
+            .iter()
+            .map(|&item| ItemIcon {
+                item,
+                sprite: icon_data(item).and_then(|data| Sprite::new(data, &ctx.gfx).ok()),
+            })
+            .collect();
+
+        Self {
+            open: false,
+            cursor: 0,
+            icons,
+        }
+    }
+
+    /// Handles opening and closing the subscreen, moving the cursor, and quick cycling.
+    pub fn update(level: &mut Level, ctx: &mut Context) {
+        let keyboard = &ctx.keyboard;
+
+        if keyboard.is_key_just_pressed(TOGGLE_KEY) {
+            let select = &mut level.protag.item_select;
+            select.open = !select.open;
+            if let Some(slot) = slot_of(level.protag.inventory.current_item) {
+                select.cursor = slot;
+            }
+            return;
+        }
+
+        if !level.protag.item_select.open {
+            if keyboard.is_key_just_pressed(PREVIOUS_KEY) {
+                Self::cycle(level, -1);
+            } else if keyboard.is_key_just_pressed(NEXT_KEY) {
+                Self::cycle(level, 1);
+            }
+            return;
+        }
+
+        let step = if keyboard.is_key_just_pressed(KeyCode::A)
+            || keyboard.is_key_just_pressed(KeyCode::Left)
+        {
+            -1
+        } else if keyboard.is_key_just_pressed(KeyCode::D)
+            || keyboard.is_key_just_pressed(KeyCode::Right)
+        {
+            1
+        } else if keyboard.is_key_just_pressed(KeyCode::W)
+            || keyboard.is_key_just_pressed(KeyCode::Up)
+        {
+            -(COLUMNS as isize)
+        } else if keyboard.is_key_just_pressed(KeyCode::S)
+            || keyboard.is_key_just_pressed(KeyCode::Down)
+        {
+            COLUMNS as isize
+        } else {
+            return;
+        };
+
+        let Some(slot) = next_acquired(
+            &level.protag.inventory,
+            level.protag.item_select.cursor,
+            step,
+        ) else {
+            return;
+        };
+        // Like LttP, the item is equipped as soon as the cursor lands on it.
+        if Inventory::equip(level, SLOTS[slot]) {
+            level.protag.item_select.cursor = slot;
+        }
+    }
+
+    /// Switches to the next acquired item in grid order, wrapping around.
+    /// Does nothing if the current item is still active.
+    pub fn cycle(level: &mut Level, step: isize) {
+        let from = slot_of(level.protag.inventory.current_item).unwrap_or(0);
+        if let Some(slot) = next_acquired(&level.protag.inventory, from, step) {
+            if Inventory::equip(level, SLOTS[slot]) {
+                level.protag.item_select.cursor = slot;
+            }
+        }
+    }
+
+    /// Draws the current item on the HUD, and the subscreen over the top of everything if it's open.
+    pub fn draw(level: &Level, ctx: &mut Context, canvas: &mut Canvas) {
+        let select = &level.protag.item_select;
+        let inventory = &level.protag.inventory;
+
+        Self::draw_slot(canvas, HUD_POSITION, SLOT_SIZE, Color::from_rgb(40, 40, 60));
+        if let Some(icon) = select.icon(inventory.current_item) {
+            icon.draw(canvas, HUD_POSITION);
+        }
+
+        if !select.open {
+            return;
+        }
+
+        let (width, height) = ctx.gfx.drawable_size();
+        canvas.draw(
+            &Quad,
+            DrawParam::new()
+                .scale(Vec2::new(width, height))
+                .color(Color::new(0.0, 0.0, 0.0, 0.6)),
+        );
+
+        let rows = SLOTS.len().div_ceil(COLUMNS);
+        let grid_size = Vec2::new(COLUMNS as f32, rows as f32) * (SLOT_SIZE + SLOT_PADDING);
+        let grid_origin = (Vec2::new(width, height) - grid_size) / 2.0;
+
+        for (slot, icon) in select.icons.iter().enumerate() {
+            let position = grid_origin
+                + Vec2::new((slot % COLUMNS) as f32, (slot / COLUMNS) as f32)
+                    * (SLOT_SIZE + SLOT_PADDING);
+
+            if slot == select.cursor {
+                Self::draw_slot(
+                    canvas,
+                    position - Vec2::splat(SLOT_PADDING / 2.0),
+                    SLOT_SIZE + SLOT_PADDING,
+                    Color::YELLOW,
+                );
+            }
+            Self::draw_slot(canvas, position, SLOT_SIZE, Color::from_rgb(40, 40, 60));
+
+            if inventory.contains(icon.item) {
+                icon.draw(canvas, position);
+            }
+        }
+    }
+
+    fn draw_slot(canvas: &mut Canvas, position: Vec2, size: f32, color: Color) {
+        canvas.draw(
+            &Quad,
+            DrawParam::new()
+                .dest(position)
+                .scale(Vec2::splat(size))
+                .color(color),
+        );
+    }
+
+    fn icon(&self, item: ItemType) -> Option<&ItemIcon> {
+        self.icons.iter().find(|icon| icon.item == item)
+    }
+}
+
+impl ItemIcon {
+    fn draw(&self, canvas: &mut Canvas, position: Vec2) {
+        match &self.sprite {
+            Some(sprite) => sprite.draw(canvas, &Camera::SCREEN, position + Vec2::splat(8.0), None),
+            None => canvas.draw(
+                &Text::new(format!("{:?}", self.item)),
+                DrawParam::new()
+                    .dest(position + Vec2::splat(4.0))
+                    .color(Color::WHITE),
+            ),
+        }
+    }
+}
+
+fn icon_data(item: ItemType) -> Option<&'static SpriteData> {
+    let data = &Game::static_assets().protag.inventory;
+    match item {
+        ItemType::None => None,
+        ItemType::Sword => Some(&data.sword.icon),
+        ItemType::Boomerang => Some(&data.boomerang.icon),
+        ItemType::Bow => Some(&data.bow.icon),
+        ItemType::Bomb => Some(&data.bomb.icon),
+    }
+}
+
+fn slot_of(item: ItemType) -> Option<usize> {
+    SLOTS.iter().position(|&slot| slot == item)
+}
+
+/// Steps through the grid from `from` until it lands on an item the protag has acquired.
+fn next_acquired(inventory: &Inventory, from: usize, step: isize) -> Option<usize> {
+    let len = SLOTS.len() as isize;
+    (1..=len)
+        .map(|i| (from as isize + step * i).rem_euclid(len) as usize)
+        .find(|&slot| inventory.contains(SLOTS[slot]))
+}
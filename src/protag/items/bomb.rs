@@ -0,0 +1,235 @@
+use super::{ItemType, ProtagItem};
+use crate::assets::player::BombData;
+use crate::attack::{self, HitHistory};
+use crate::camera::HURT_TRAUMA;
+use crate::collision::{CollisionFilter, Hitbox, HitboxAnimation, HitboxType};
+use crate::level::{Collider, Level};
+use crate::movement::Solids;
+use crate::{Direction, Game};
+use bevy_reflect::prelude::ReflectDefault;
+use bevy_reflect::Reflect;
+use ggez::graphics::{Canvas, Color};
+use ggez::input::keyboard::KeyCode;
+use ggez::Context;
+use glam::Vec2;
+
+/// Where a carried bomb sits relative to the protag, held up above their head.
+static CARRY_OFFSET: Vec2 = Vec2::new(0.0, -60.0);
+
+/// How far in front of the protag bombs are placed, and how far the protag can reach to pick one up.
+static REACH: f32 = 40.0;
+
+/// Holds every bomb the protag has put down. The amount of bombs left is stored in [`Inventory::bombs`](crate::protag::inventory::Inventory).
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Default)]
+pub struct BombBag {
+    pub bombs: Vec<Bomb>,
+    #[reflect(ignore)]
+    pub data: &'static BombData,
+}
+
+impl Default for BombBag {
+    fn default() -> Self {
+        Self {
+            bombs: Vec::new(),
+            data: &Game::static_assets().protag.inventory.bomb,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub struct Bomb {
+    #[reflect(ignore)]
+    pub position: Vec2,
+    pub hitbox: Hitbox,
+    /// Frames left until the bomb explodes. The fuse keeps burning while the bomb is carried or thrown.
+    pub fuse: usize,
+    pub state: BombState,
+    /// Everything that's already been caught in the bomb's blast, so it's only hit once.
+    pub hits: HitHistory,
+}
+
+#[derive(Debug, Clone, Reflect)]
+pub enum BombState {
+    /// Sitting on the ground.
+    Placed,
+    /// Held above the protag's head.
+    Carried,
+    /// Flying through the air, until it lands or hits a wall.
+    Thrown { direction: Direction, frame: usize },
+    /// The fuse has run out. The bomb is removed once the blast animation finishes.
+    Exploding { blast: HitboxAnimation },
+}
+
+impl Bomb {
+    pub fn new(position: Vec2, data: &BombData) -> Self {
+        Self {
+            position,
+            hitbox: Hitbox::point_size(Vec2::ZERO, data.size).with_filter(CollisionFilter::PICKUP),
+            fuse: data.fuse_frames,
+            state: BombState::Placed,
+            hits: HitHistory::default(),
+        }
+    }
+}
+
+impl ProtagItem for BombBag {
+    /// Bombs can't be put away while one is being carried.
+    fn active(&mut self) -> bool {
+        self.bombs
+            .iter()
+            .any(|bomb| matches!(bomb.state, BombState::Carried))
+    }
+
+    fn item_type(&self) -> ItemType {
+        ItemType::Bomb
+    }
+
+    /// Throws the carried bomb, picks up a bomb within reach, or places a new one, in that order.
+    fn update(&mut self, level: &mut Level, ctx: &mut Context) {
+        if !ctx.keyboard.is_key_just_pressed(KeyCode::Space) {
+            return;
+        }
+
+        let protag = &mut level.protag;
+        let reach = protag.position + protag.direction.to_vec() * REACH;
+
+        if let Some(carried) = self
+            .bombs
+            .iter_mut()
+            .find(|bomb| matches!(bomb.state, BombState::Carried))
+        {
+            carried.state = BombState::Thrown {
+                direction: protag.direction,
+                frame: 0,
+            };
+        } else if let Some(placed) = self.bombs.iter_mut().find(|bomb| {
+            matches!(bomb.state, BombState::Placed)
+                && bomb
+                    .hitbox
+                    .colliding_single(&protag.hurtbox, bomb.position, reach)
+        }) {
+            placed.state = BombState::Carried;
+        } else if protag.inventory.bombs > 0 {
+            protag.inventory.bombs -= 1;
+            self.bombs.push(Bomb::new(reach, self.data));
+        }
+    }
+
+    /// Burns every bomb's fuse and runs their explosions, even after the protag has put the bombs away.
+    fn update_world(&mut self, level: &mut Level, _ctx: &mut Context) {
+        let data = self.data;
+
+        for bomb in self.bombs.iter_mut() {
+            if !matches!(bomb.state, BombState::Exploding { .. }) {
+                bomb.fuse = bomb.fuse.saturating_sub(1);
+                if bomb.fuse == 0 {
+                    let mut blast = data.blast.clone();
+                    blast.reset();
+                    bomb.state = BombState::Exploding { blast };
+                }
+            }
+
+            match &mut bomb.state {
+                BombState::Placed => {}
+                BombState::Carried => bomb.position = level.protag.position + CARRY_OFFSET,
+                BombState::Thrown { direction, frame } => {
+                    *frame += 1;
+                    let from = bomb.position;
+                    let to = from + direction.to_vec() * data.throw_speed;
+                    // Bombs land against whatever they're thrown into, rather than inside it.
+                    let sweep = level.sweep_solids(&bomb.hitbox, from, to);
+                    bomb.position = match &sweep {
+                        Some(sweep) => from.lerp(to, sweep.time),
+                        None => to,
+                    };
+                    if *frame >= data.throw_frames || sweep.is_some() {
+                        bomb.state = BombState::Placed;
+                    }
+                }
+                BombState::Exploding { blast } => {
+                    blast.update_animation();
+                    Self::explode(level, bomb.position, blast, &mut bomb.hits);
+                }
+            }
+        }
+
+        self.bombs.retain(|bomb| match &bomb.state {
+            BombState::Exploding { blast, .. } => blast.is_active(),
+            _ => true,
+        });
+    }
+
+    fn draw_world(&self, level: &Level, ctx: &mut Context, canvas: &mut Canvas) {
+        for bomb in self.bombs.iter() {
+            match &bomb.state {
+                BombState::Exploding { blast, .. } => {
+                    blast.draw(&mut ctx.gfx, canvas, &level.camera, bomb.position);
+                }
+                _ => {
+                    // Flash when the bomb is about to go off
+                    let color = if bomb.fuse < self.data.fuse_frames / 3 && (bomb.fuse / 5) % 2 == 0
+                    {
+                        Color::RED
+                    } else {
+                        Color::BLUE
+                    };
+                    bomb.hitbox
+                        .draw(
+                            &mut ctx.gfx,
+                            canvas,
+                            &level.camera,
+                            bomb.position,
+                            Some(color),
+                        )
+                        .unwrap();
+                }
+            }
+        }
+    }
+}
+
+impl BombBag {
+    /// Hurts everything caught in the blast, and blows open any bombable walls.
+    fn explode(level: &mut Level, position: Vec2, blast: &HitboxAnimation, hits: &mut HitHistory) {
+        let frame = blast.lerped_hitboxes.borrow();
+
+        let candidates = level.colliders.query_frame(frame, position);
+        for handle in candidates.into_iter().filter_map(Collider::enemy) {
+            let Some(enemy) = level.enemies.get_mut(handle) else {
+                continue;
+            };
+            let Some((hitbox, enemy_position)) = enemy.get_hitbox() else {
+                continue;
+            };
+            if let Some(hit) = attack::resolve_hit(
+                frame,
+                position,
+                Collider::Enemy(handle),
+                hitbox,
+                enemy_position,
+                hits,
+            ) {
+                enemy.on_hit(hit.damage);
+            }
+        }
+
+        let protag = &mut level.protag;
+        if let Some(hit) = attack::resolve_hit(
+            frame,
+            position,
+            Collider::Protag,
+            HitboxType::Singular(&protag.hurtbox),
+            protag.position,
+            hits,
+        ) {
+            if protag.on_hit(hit.damage, &level.static_assets.protag) {
+                level.camera.add_trauma(HURT_TRAUMA);
+            }
+        }
+
+        level.walls.retain(|wall| {
+            !(wall.bombable && frame.colliding_single(&wall.hitbox, position, wall.position))
+        });
+    }
+}
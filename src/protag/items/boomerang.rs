@@ -0,0 +1,212 @@
+use super::{ItemType, ProtagItem};
+use crate::assets::player::BoomerangData;
+use crate::attack::Element;
+use crate::collision::{CollisionFilter, Hitbox, HitboxType};
+use crate::knockback::HitOrigin;
+use crate::level::{Collider, Level};
+use crate::npc::DamageTransfer;
+use crate::pickup::Pickup;
+use crate::{Direction, Game};
+use bevy_reflect::prelude::ReflectDefault;
+use bevy_reflect::Reflect;
+use ggez::graphics::{Canvas, Color};
+use ggez::input::keyboard::KeyCode;
+use ggez::Context;
+use glam::Vec2;
+
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Default)]
+pub struct Boomerang {
+    pub state: BoomerangState,
+    pub hitbox: Hitbox,
+    /// Pickups that the boomerang has grabbed, which are given to the protag once it's caught.
+    pub carrying: Vec<Pickup>,
+    #[reflect(ignore)]
+    pub data: &'static BoomerangData,
+}
+
+impl Default for Boomerang {
+    fn default() -> Self {
+        let data = &Game::static_assets().protag.inventory.boomerang;
+        Self {
+            state: Default::default(),
+            hitbox: Hitbox::point_size(Vec2::ZERO, data.size)
+                .with_filter(CollisionFilter::PROJECTILE),
+            carrying: Vec::new(),
+            data,
+        }
+    }
+}
+
+#[derive(Debug, Default, Reflect, Clone, PartialEq)]
+pub enum BoomerangState {
+    #[default]
+    Held,
+    /// Flying away from the protag, until it runs out of range or hits something.
+    Outbound {
+        #[reflect(ignore)]
+        position: Vec2,
+        direction: Direction,
+        frame: usize,
+    },
+    /// Homing back towards the protag, until it's caught.
+    Returning {
+        #[reflect(ignore)]
+        position: Vec2,
+    },
+}
+
+impl ProtagItem for Boomerang {
+    fn active(&mut self) -> bool {
+        !matches!(self.state, BoomerangState::Held)
+    }
+
+    /// The protag stops for a moment to throw, but can walk around freely while it's in the air.
+    fn can_move(&mut self) -> bool {
+        match self.state {
+            BoomerangState::Outbound { frame, .. } => frame >= self.data.throw_frames,
+            _ => true,
+        }
+    }
+
+    fn can_turn(&mut self) -> bool {
+        self.can_move()
+    }
+
+    fn item_type(&self) -> ItemType {
+        ItemType::Boomerang
+    }
+
+    fn update(&mut self, level: &mut Level, ctx: &mut Context) {
+        let protag_position = level.protag.position;
+        let data = self.data;
+
+        let position = match &mut self.state {
+            BoomerangState::Held => {
+                if ctx.keyboard.is_key_just_pressed(KeyCode::Space) {
+                    self.state = BoomerangState::Outbound {
+                        position: protag_position,
+                        direction: level.protag.direction,
+                        frame: 0,
+                    };
+                }
+                return;
+            }
+            BoomerangState::Outbound {
+                position,
+                direction,
+                frame,
+            } => {
+                *frame += 1;
+                *position += direction.to_vec() * data.speed;
+                let position = *position;
+                if *frame >= data.range {
+                    self.state = BoomerangState::Returning { position };
+                }
+                position
+            }
+            BoomerangState::Returning { position } => {
+                let to_protag = protag_position - *position;
+                if to_protag.length() <= data.speed {
+                    *position = protag_position;
+                } else {
+                    *position += to_protag.normalize() * data.speed;
+                }
+                *position
+            }
+        };
+
+        if matches!(self.state, BoomerangState::Returning { .. })
+            && self
+                .hitbox
+                .colliding_single(&level.protag.hurtbox, position, level.protag.position)
+        {
+            self.catch(level);
+            return;
+        }
+
+        let (grabbed, remaining) = level.pickups.drain(..).partition(|pickup: &Pickup| {
+            self.hitbox
+                .colliding_single(&pickup.hitbox, position, pickup.position)
+        });
+        level.pickups = remaining;
+        self.carrying.extend(grabbed);
+        for pickup in self.carrying.iter_mut() {
+            pickup.position = position;
+        }
+
+        let mut hit_anything = false;
+        let candidates = level
+            .colliders
+            .query_hitbox(HitboxType::Singular(&self.hitbox), position);
+        for handle in candidates.into_iter().filter_map(Collider::enemy) {
+            let Some(enemy) = level.enemies.get_mut(handle) else {
+                continue;
+            };
+            let Some((hitbox, enemy_position)) = enemy.get_hitbox() else {
+                continue;
+            };
+            if self.hitbox.colliding(hitbox, position, enemy_position) {
+                enemy.on_hit(DamageTransfer {
+                    damage: 0.,
+                    weight: data.weight,
+                    origin: HitOrigin::Position(position),
+                    stun: data.stun,
+                    element: Element::Physical,
+                });
+                hit_anything = true;
+            }
+        }
+
+        if hit_anything {
+            self.state = BoomerangState::Returning { position };
+        }
+    }
+
+    fn draw(&self, level: &Level, ctx: &mut Context, canvas: &mut Canvas) {
+        let Some(position) = self.position() else {
+            return;
+        };
+
+        for pickup in self.carrying.iter() {
+            pickup
+                .hitbox
+                .draw(
+                    &mut ctx.gfx,
+                    canvas,
+                    &level.camera,
+                    pickup.position,
+                    Some(pickup.color()),
+                )
+                .unwrap();
+        }
+        self.hitbox
+            .draw(
+                &mut ctx.gfx,
+                canvas,
+                &level.camera,
+                position,
+                Some(Color::YELLOW),
+            )
+            .unwrap();
+    }
+}
+
+impl Boomerang {
+    /// Puts the boomerang back in the protag's hand, and hands over anything it was carrying.
+    fn catch(&mut self, level: &mut Level) {
+        self.state = BoomerangState::Held;
+        for pickup in std::mem::take(&mut self.carrying) {
+            pickup.collect(level);
+        }
+    }
+
+    pub fn position(&self) -> Option<Vec2> {
+        match self.state {
+            BoomerangState::Held => None,
+            BoomerangState::Outbound { position, .. } | BoomerangState::Returning { position } => {
+                Some(position)
+            }
+        }
+    }
+}
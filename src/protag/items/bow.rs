@@ -0,0 +1,250 @@
+use super::{ItemType, ProtagItem};
+use crate::assets::player::BowData;
+use crate::attack::Element;
+use crate::collision::{sweep, CollisionFilter, Hitbox, Ray, Sweep};
+use crate::knockback::HitOrigin;
+use crate::level::{Collider, Level};
+use crate::movement::Solids;
+use crate::npc::{DamageTransfer, EnemyHandle};
+use crate::{Direction, Game};
+use bevy_reflect::prelude::ReflectDefault;
+use bevy_reflect::Reflect;
+use ggez::graphics::{Canvas, Color, DrawParam, Mesh, Rect};
+use ggez::input::keyboard::KeyCode;
+use ggez::Context;
+use glam::Vec2;
+
+#[derive(Debug, Clone, Reflect)]
+#[reflect(Default)]
+pub struct Bow {
+    pub state: BowState,
+    /// Arrows that have been fired and are still in the level.
+    pub arrows: Vec<Arrow>,
+    #[reflect(ignore)]
+    pub data: &'static BowData,
+}
+
+impl Default for Bow {
+    fn default() -> Self {
+        Self {
+            state: Default::default(),
+            arrows: Vec::new(),
+            data: &Game::static_assets().protag.inventory.bow,
+        }
+    }
+}
+
+#[derive(Debug, Default, Reflect, Clone, PartialEq)]
+pub enum BowState {
+    #[default]
+    Inactive,
+    /// The fire key is being held down. Once it's let go, the arrow is fired if the bow was fully drawn.
+    Drawing { frame: usize, direction: Direction },
+}
+
+#[derive(Debug, Reflect, Clone)]
+pub struct Arrow {
+    #[reflect(ignore)]
+    pub position: Vec2,
+    pub direction: Direction,
+    pub hitbox: Hitbox,
+    pub state: ArrowState,
+}
+
+#[derive(Debug, Reflect, Clone, Copy, PartialEq)]
+pub enum ArrowState {
+    /// In the air, with the amount of frames it has been flying for.
+    Flying(usize),
+    /// Stuck in a wall, with the amount of frames left until it disappears.
+    Stuck(usize),
+}
+
+impl ProtagItem for Bow {
+    fn active(&mut self) -> bool {
+        match self.state {
+            BowState::Inactive => false,
+            BowState::Drawing { .. } => true,
+        }
+    }
+
+    fn can_turn(&mut self) -> bool {
+        !self.active()
+    }
+
+    fn item_type(&self) -> ItemType {
+        ItemType::Bow
+    }
+
+    fn update(&mut self, level: &mut Level, ctx: &mut Context) {
+        match &mut self.state {
+            BowState::Inactive => {
+                if ctx.keyboard.is_key_just_pressed(KeyCode::Space)
+                    && level.protag.inventory.arrows > 0
+                {
+                    self.state = BowState::Drawing {
+                        frame: 0,
+                        direction: level.protag.direction,
+                    }
+                }
+            }
+            BowState::Drawing { frame, direction } => {
+                if ctx.keyboard.is_key_pressed(KeyCode::Space) {
+                    *frame += 1;
+                    return;
+                }
+
+                if *frame >= self.data.draw_frames && level.protag.inventory.arrows > 0 {
+                    level.protag.inventory.arrows -= 1;
+                    let position = level.protag.position + direction.to_vec() * 40.0;
+                    self.arrows.push(Arrow::new(position, *direction));
+                }
+                self.state = BowState::Inactive;
+            }
+        }
+    }
+
+    /// Moves every arrow that's been fired, since arrows keep flying after the protag puts the bow away.
+    fn update_world(&mut self, level: &mut Level, _ctx: &mut Context) {
+        let data = self.data;
+
+        for arrow in self.arrows.iter_mut() {
+            let ArrowState::Flying(frame) = &mut arrow.state else {
+                continue;
+            };
+            *frame += 1;
+            // Arrows are swept, so that they can't fly straight past anything thinner than a frame's worth of movement.
+            let from = arrow.position;
+            let to = from + arrow.direction.to_vec() * data.arrow_speed;
+
+            let wall = level.sweep_solids(&arrow.hitbox, from, to);
+
+            let mut first_enemy: Option<(EnemyHandle, Sweep)> = None;
+            let candidates = level.colliders.query(
+                sweep::swept_bounding_box(arrow.hitbox.bounding_box(), from, to),
+                arrow.hitbox.filter(),
+            );
+            for handle in candidates.into_iter().filter_map(Collider::enemy) {
+                let Some((hitbox, enemy_position)) = level
+                    .enemies
+                    .get(handle)
+                    .and_then(|enemy| enemy.get_hitbox())
+                else {
+                    continue;
+                };
+                let Some(hit) = arrow.hitbox.sweep(hitbox, from, to, enemy_position) else {
+                    continue;
+                };
+                if first_enemy.is_none_or(|(_, first)| hit.time < first.time) {
+                    first_enemy = Some((handle, hit));
+                }
+            }
+
+            match (wall, first_enemy) {
+                // Arrows only ever hit the first enemy in their way.
+                (wall, Some((handle, hit))) if wall.is_none_or(|wall| hit.time < wall.time) => {
+                    arrow.position = hit.position(from, to);
+                    arrow.state = ArrowState::Stuck(0);
+                    if let Some(enemy) = level.enemies.get_mut(handle) {
+                        enemy.on_hit(DamageTransfer {
+                            damage: data.damage,
+                            weight: data.weight,
+                            origin: HitOrigin::Direction(arrow.direction),
+                            stun: 0,
+                            element: Element::Physical,
+                        });
+                    }
+                }
+                (Some(wall), _) => {
+                    arrow.position = wall.position(from, to);
+                    arrow.state = ArrowState::Stuck(data.stuck_frames);
+                }
+                (None, _) => arrow.position = to,
+            }
+        }
+
+        self.arrows.retain_mut(|arrow| match &mut arrow.state {
+            ArrowState::Flying(frame) => *frame < data.arrow_range,
+            ArrowState::Stuck(frames) => {
+                let remaining = *frames > 0;
+                *frames = frames.saturating_sub(1);
+                remaining
+            }
+        });
+    }
+
+    fn draw(&self, level: &Level, ctx: &mut Context, canvas: &mut Canvas) {
+        if let BowState::Drawing { frame, direction } = self.state {
+            let color = if frame >= self.data.draw_frames {
+                Color::YELLOW
+            } else {
+                Color::WHITE
+            };
+            let position = level.protag.position + direction.to_vec() * 40.0;
+            let arrow = Arrow::new(position, direction);
+            arrow
+                .hitbox
+                .draw(&mut ctx.gfx, canvas, &level.camera, position, Some(color))
+                .unwrap();
+
+            if frame < self.data.draw_frames {
+                return;
+            }
+            // Once fully drawn, aim along the arrow's path up to whatever it would hit first
+            let flight = Ray::new(
+                position,
+                direction.to_vec(),
+                self.data.arrow_speed * self.data.arrow_range as f32,
+            );
+            let end = level
+                .shape_cast(&arrow.hitbox, &flight, &[Collider::Protag])
+                .map_or(flight.end(), |(_, hit)| hit.point);
+            if end != position {
+                let camera = &level.camera;
+                let line = [
+                    camera.world_to_screen(position),
+                    camera.world_to_screen(end),
+                ];
+                canvas.draw(
+                    &Mesh::new_line(&ctx.gfx, &line, 2.0 * camera.zoom, Color::YELLOW).unwrap(),
+                    DrawParam::new(),
+                );
+            }
+        }
+    }
+
+    fn draw_world(&self, level: &Level, ctx: &mut Context, canvas: &mut Canvas) {
+        for arrow in self.arrows.iter() {
+            arrow
+                .hitbox
+                .draw(
+                    &mut ctx.gfx,
+                    canvas,
+                    &level.camera,
+                    arrow.position,
+                    Some(Color::WHITE),
+                )
+                .unwrap();
+        }
+    }
+
+    /// Putting the bow away lets go of the arrow without firing it.
+    fn on_unequip(&mut self, _level: &mut Level) {
+        self.state = BowState::Inactive;
+    }
+}
+
+impl Arrow {
+    pub fn new(position: Vec2, direction: Direction) -> Self {
+        // Arrows are long and thin, so they need to be rotated to match the direction they're flying in.
+        let rect = match direction {
+            Direction::Right | Direction::Left => Rect::new(-15.0, -3.0, 30.0, 6.0),
+            Direction::Up | Direction::Down => Rect::new(-3.0, -15.0, 6.0, 30.0),
+        };
+        Self {
+            position,
+            direction,
+            hitbox: Hitbox::new(rect).with_filter(CollisionFilter::PROJECTILE),
+            state: ArrowState::Flying(0),
+        }
+    }
+}
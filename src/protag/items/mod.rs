@@ -1,8 +1,17 @@
+use crate::level::Level;
 use bevy_reflect::Reflect;
+use ggez::graphics::Canvas;
+use ggez::Context;
+use serde::Deserialize;
+use std::fmt::{Debug, Formatter};
 
+pub mod bomb;
+pub mod boomerang;
+pub mod bow;
 pub mod sword;
 
-#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Reflect, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum ItemType {
     #[default]
     None,
@@ -12,8 +21,27 @@ pub enum ItemType {
     Bomb,
 }
 
+impl ItemType {
+    /// A new item of this type, the way the protag gets it when picking it up.
+    pub fn create(self) -> Option<Box<dyn ProtagItem>> {
+        match self {
+            ItemType::None => None,
+            ItemType::Sword => Some(Box::new(sword::Sword::default())),
+            ItemType::Boomerang => Some(Box::new(boomerang::Boomerang::default())),
+            ItemType::Bow => Some(Box::new(bow::Bow::default())),
+            ItemType::Bomb => Some(Box::new(bomb::BombBag::default())),
+        }
+    }
+}
+
 /// Hooks that Items can use to communicate details to the player
-pub trait ProtagItem {
+///
+/// Items are stored in the [`Inventory`](crate::protag::inventory::Inventory) as trait objects,
+/// and are taken out of it while their update hooks run, so they're free to borrow the rest of the level.
+pub trait ProtagItem: Reflect {
+    /// Which slot in the inventory this item fills.
+    fn item_type(&self) -> ItemType;
+
     /// If false, then the item can be switched out and does not require updates to be locked to this item.
     fn active(&mut self) -> bool;
 
@@ -32,4 +60,29 @@ pub trait ProtagItem {
     fn can_turn(&mut self) -> bool {
         true
     }
+
+    /// Runs once per frame while this is the current item.
+    fn update(&mut self, level: &mut Level, ctx: &mut Context);
+
+    /// Runs once per frame whether or not this is the current item,
+    /// for anything the item has left out in the level, like arrows that are still flying.
+    fn update_world(&mut self, _level: &mut Level, _ctx: &mut Context) {}
+
+    /// Draws the item in the protag's hands. Only called while this is the current item.
+    fn draw(&self, _level: &Level, _ctx: &mut Context, _canvas: &mut Canvas) {}
+
+    /// Draws anything the item has left out in the level. Called whether or not this is the current item.
+    fn draw_world(&self, _level: &Level, _ctx: &mut Context, _canvas: &mut Canvas) {}
+
+    /// Called when the protag switches to this item.
+    fn on_equip(&mut self, _level: &mut Level) {}
+
+    /// Called when the protag switches away from this item.
+    fn on_unequip(&mut self, _level: &mut Level) {}
+}
+
+impl Debug for dyn ProtagItem {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.debug(f)
+    }
 }
@@ -1,14 +1,10 @@
-use super::ProtagItem;
-use crate::collision::{
-    Hitbox, HitboxFrameRef, HitboxFrameString, HitboxFrameStringRef, HitboxType,
-};
-use crate::level::Level;
-use crate::npc::advanced_enemy::AdvancedEnemy;
-use crate::npc::basic_enemy::BasicEnemy;
-use crate::npc::{DamageTransfer, Enemy};
+use super::{ItemType, ProtagItem};
+use crate::attack::{self, HitHistory};
+use crate::collision::{Hitbox, HitboxFrameRef, HitboxFrameString, HitboxFrameStringRef};
+use crate::level::{Collider, Level};
 use crate::{Direction, Game};
 use bevy_reflect::prelude::ReflectDefault;
-use bevy_reflect::{GetField, Reflect};
+use bevy_reflect::Reflect;
 use ggez::graphics::DrawParam;
 use ggez::graphics::Quad;
 use ggez::graphics::{Canvas, Color};
@@ -20,6 +16,10 @@ use glam::Vec2;
 #[reflect(Default)]
 pub struct Sword {
     pub state: SwordState,
+    /// Which hitboxes of the current swing frame hit an enemy this frame.
+    pub landed: Vec<usize>,
+    /// Every enemy the current swing has hit, so that each enemy is only hit once per swing.
+    pub hits: HitHistory,
     #[reflect(ignore)]
     pub swing: &'static [HitboxFrameString; 4],
 }
@@ -28,6 +28,8 @@ impl Default for Sword {
     fn default() -> Self {
         Self {
             state: Default::default(),
+            landed: Vec::new(),
+            hits: HitHistory::default(),
             swing: &Game::static_assets().protag.inventory.sword.swing,
         }
     }
@@ -58,15 +60,17 @@ impl ProtagItem for Sword {
     fn can_turn(&mut self) -> bool {
         !self.active()
     }
-}
 
-impl Sword {
-    pub fn update(level: &mut Level, ctx: &mut Context) {
-        let sword = &mut level.protag.inventory.sword;
-        match &mut sword.state {
+    fn item_type(&self) -> ItemType {
+        ItemType::Sword
+    }
+
+    fn update(&mut self, level: &mut Level, ctx: &mut Context) {
+        match &mut self.state {
             SwordState::Inactive => {
                 if ctx.keyboard.is_key_just_pressed(KeyCode::Space) {
-                    sword.state = SwordState::Active {
+                    self.hits.clear();
+                    self.state = SwordState::Active {
                         direction: level.protag.direction,
                         frame: 0,
                     }
@@ -74,95 +78,68 @@ impl Sword {
             }
             SwordState::Active { direction, frame } => {
                 *frame += 1;
-                if *frame >= sword.swing[*direction as usize].len() {
-                    sword.state = SwordState::Inactive;
+                self.landed.clear();
+                if *frame >= self.swing[*direction as usize].len() {
+                    self.state = SwordState::Inactive;
                     return;
                 }
 
-                if let Some(other) = &mut level.enemies.basic_enemy {
-                    if sword.swing[*direction as usize].colliding(
-                        *frame,
-                        HitboxType::Singular(&other.hurtbox),
+                let swing_frame = self.swing[*direction as usize].0[*frame].borrow();
+                let candidates = level
+                    .colliders
+                    .query_frame(swing_frame, level.protag.position);
+                for handle in candidates.into_iter().filter_map(Collider::enemy) {
+                    let Some(enemy) = level.enemies.get_mut(handle) else {
+                        continue;
+                    };
+                    let Some((hitbox, position)) = enemy.get_hitbox() else {
+                        continue;
+                    };
+                    let Some(hit) = attack::resolve_hit(
+                        swing_frame,
                         level.protag.position,
-                        other.position,
-                    ) {
-                        other.on_hit(DamageTransfer {
-                            damage: 5.,
-                            weight: 1.,
-                        });
-                    }
-                };
+                        Collider::Enemy(handle),
+                        hitbox,
+                        position,
+                        &mut self.hits,
+                    ) else {
+                        continue;
+                    };
+
+                    enemy.on_hit(hit.damage);
+                    self.landed
+                        .extend(hit.contacts.iter().map(|contact| contact.index));
+                }
             }
         }
     }
 
-    pub fn draw(level: &Level, ctx: &mut Context, canvas: &mut Canvas) {
-        let sword = &level.protag.inventory.sword;
-        match &sword.state {
+    fn draw(&self, level: &Level, ctx: &mut Context, canvas: &mut Canvas) {
+        match &self.state {
             SwordState::Inactive => {}
             SwordState::Active { direction, frame } => {
-                let color = if let Some(other) = level
-                    .enemies
-                    .get_field::<Option<BasicEnemy>>("basic_enemy")
-                    .unwrap_or(&mut None)
-                {
-                    sword.swing[*direction as usize]
-                        .colliding(
-                            *frame,
-                            other.get_hitbox().unwrap().0,
-                            level.protag.position,
-                            other.get_hitbox().unwrap().1,
-                        )
-                        .then_some(Color::RED)
-                        .unwrap_or(Color::WHITE)
-                } else if let Some(other) = level
-                    .enemies
-                    .get_field::<Option<BasicEnemy>>("basic_enemy2")
-                    .unwrap_or(&mut None)
-                {
-                    sword.swing[*direction as usize]
-                        .colliding(
-                            *frame,
-                            other.get_hitbox().unwrap().0,
-                            level.protag.position,
-                            other.get_hitbox().unwrap().1,
-                        )
-                        .then_some(Color::RED)
-                        .unwrap_or(Color::WHITE)
-                } else if let Some(other) = level
-                    .enemies
-                    .get_field::<Option<BasicEnemy>>("basic_enemy3")
-                    .unwrap_or(&mut None)
-                {
-                    sword.swing[*direction as usize]
-                        .colliding(
-                            *frame,
-                            other.get_hitbox().unwrap().0,
-                            level.protag.position,
-                            other.get_hitbox().unwrap().1,
-                        )
-                        .then_some(Color::RED)
-                        .unwrap_or(Color::WHITE)
-                } else if let Some(other) = level
-                    .enemies
-                    .get_field::<Option<AdvancedEnemy>>("advanced_enemy")
-                    .unwrap_or(&mut None)
-                {
-                    sword.swing[*direction as usize]
-                        .colliding(
-                            *frame,
-                            other.get_hitbox().unwrap().0,
+                let Some(swing_frame) = self.swing[*direction as usize].0.get(*frame) else {
+                    return;
+                };
+                // Hitboxes that landed are drawn in red
+                for (index, hitbox) in swing_frame.borrow().0.iter().enumerate() {
+                    let color = if self.landed.contains(&index) {
+                        Color::RED
+                    } else if level.enemies.is_empty() {
+                        Color::BLACK
+                    } else {
+                        Color::WHITE
+                    };
+                    hitbox
+                        .draw(
+                            &mut ctx.gfx,
+                            canvas,
+                            &level.camera,
                             level.protag.position,
-                            other.get_hitbox().unwrap().1,
+                            Some(color),
                         )
-                        .then_some(Color::RED)
-                        .unwrap_or(Color::WHITE)
-                } else {
-                    Color::BLACK
-                };
-                sword.swing[*direction as usize]
-                    .draw(&mut ctx.gfx, canvas, *frame, level.protag.position, color)
-                    .unwrap()
+                        .unwrap()
+                }
             }
         }
         canvas.draw(
@@ -173,8 +150,13 @@ impl Sword {
                 // .rotation(-Vec2::from(level.protag.direction).angle_between(Vec2::ONE))
                 // .scale(Vec2::from([40., 40.]))
                 .dest(
-                    level.protag.position, // + (level.protag.scale / 2.)
+                    level.camera.world_to_screen(level.protag.position), // + (level.protag.scale / 2.)
                 ),
         );
     }
+
+    /// Putting the sword away cancels the swing.
+    fn on_unequip(&mut self, _level: &mut Level) {
+        self.state = SwordState::Inactive;
+    }
 }
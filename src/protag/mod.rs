@@ -1,66 +1,165 @@
 use crate::assets::player::ProtagData;
-use crate::collision::Hitbox;
+use crate::collision::{CollisionFilter, Hitbox};
+use crate::knockback::Knockback;
 use crate::level::Level;
+use crate::npc::DamageTransfer;
+use crate::room::Room;
 use crate::Direction;
 use bevy_reflect::Reflect;
 use controller::ProtagController;
-use ggez::graphics::{Canvas, Color};
+use ggez::graphics::{Canvas, Color, DrawParam, Quad};
 use ggez::{Context, GameResult};
 use glam::Vec2;
 use inventory::Inventory;
-use items::sword::Sword;
-use items::ItemType;
+use item_select::ItemSelect;
 
 pub mod controller;
 pub mod inventory;
+pub mod item_select;
 pub mod items;
 
 #[derive(Debug, Reflect)]
 pub struct Protag {
     #[reflect(ignore)]
     pub position: glam::Vec2,
+    /// Where the protag last stood on solid ground, which they're sent back to after falling into a hazard.
+    #[reflect(ignore)]
+    pub safe_position: Vec2,
+    /// Where the protag respawns, which is wherever they came into the current map.
+    #[reflect(ignore)]
+    pub start_position: Vec2,
     #[reflect(ignore)]
     pub scale: glam::Vec2,
     pub direction: Direction,
     pub hurtbox: Hitbox,
     pub controller: ProtagController,
     pub inventory: Inventory,
+    pub item_select: ItemSelect,
+    pub stats: ProtagStats,
+    pub knockback: Knockback,
+}
+
+/// Mirrors [`EnemyStats`](crate::npc::EnemyStats), but for the protag.
+#[derive(Debug, Reflect, Clone)]
+pub struct ProtagStats {
+    pub health: i32,
+    pub max_health: i32,
+    pub iframes: usize,
 }
 
 impl Protag {
     pub fn new(init: &ProtagData, ctx: &mut Context) -> Self {
         Self {
-            position: init.start_pos,
+            // Placed by the map, see `Level::enter_map`.
+            position: Vec2::ZERO,
+            safe_position: Vec2::ZERO,
+            start_position: Vec2::ZERO,
             direction: Direction::Down,
             inventory: inventory::Inventory::new(),
+            item_select: ItemSelect::new(ctx),
             controller: ProtagController::new(),
             scale: [80.0, 80.0].into(),
-            hurtbox: Hitbox::point_size(Vec2::ZERO, 80.0),
+            hurtbox: Hitbox::point_size(Vec2::ZERO, 80.0).with_filter(CollisionFilter::BODY),
+            stats: ProtagStats {
+                health: init.max_health,
+                max_health: init.max_health,
+                iframes: 0,
+            },
+            knockback: Knockback::new(init.mass),
         }
     }
 
-    pub fn update(level: &mut Level, ctx: &mut Context) {
-        match level.protag.inventory.current_item {
-            ItemType::None => (),
-            ItemType::Sword => Sword::update(level, ctx),
-            ItemType::Boomerang => todo!(),
-            ItemType::Bow => todo!(),
-            ItemType::Bomb => todo!(),
+    /// Applies damage to the protag, returning true if the hit landed.
+    ///
+    /// Hits are ignored while the protag still has invulnerability frames left over from the last hit.
+    pub fn on_hit(&mut self, stats: DamageTransfer, init: &ProtagData) -> bool {
+        if self.stats.iframes > 0 {
+            return false;
         }
+        self.stats.health -= stats.damage as i32;
+        self.stats.iframes = init.iframes;
+        self.knockback.apply(&stats, self.position, self.direction);
+        true
+    }
+
+    /// Hurts the protag and sends them back to [`Protag::safe_position`], after falling into a pit or deep water.
+    ///
+    /// Unlike [`Protag::on_hit`], iframes don't stop the protag from falling.
+    pub fn fall(&mut self, damage: f32, init: &ProtagData) {
+        self.stats.health -= damage as i32;
+        self.stats.iframes = init.iframes;
+        self.position = self.safe_position;
+        self.knockback.cancel();
+    }
+
+    /// Moves the protag somewhere new, like when they enter a map, which also becomes where they respawn.
+    pub fn place(&mut self, position: Vec2) {
+        self.position = position;
+        self.safe_position = position;
+        self.start_position = position;
+        self.knockback.cancel();
+    }
+
+    /// Sends the protag back to the start with full health.
+    ///
+    /// The start can be in a different room, so this should be followed by [`Room::enter`]
+    /// rather than letting the screen scroll all the way there.
+    pub fn respawn(&mut self, init: &ProtagData) {
+        self.position = self.start_position;
+        self.safe_position = self.start_position;
+        self.stats.health = self.stats.max_health;
+        self.stats.iframes = init.iframes;
+        self.knockback.cancel();
+    }
+
+    pub fn update(level: &mut Level, ctx: &mut Context) {
+        Inventory::update(level, ctx);
 
         controller::ProtagController::update(level, ctx);
+
+        if level.protag.stats.iframes > 0 {
+            level.protag.stats.iframes -= 1;
+        }
+
+        if level.protag.stats.health <= 0 {
+            level.protag.respawn(&level.static_assets.protag);
+            Room::enter(level, ctx);
+        }
     }
 
     pub fn draw(level: &mut Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
         level.protag.hurtbox.draw(
             &mut ctx.gfx,
             canvas,
+            &level.camera,
             level.protag.position,
-            level.protag.controller.hurt.then_some(Color::RED),
+            (level.protag.stats.iframes > 0).then_some(Color::RED),
         )?;
 
         Inventory::draw(level, ctx, canvas);
 
         Ok(())
     }
+
+    /// Draws the health bar in the top left of the screen.
+    pub fn draw_hud(level: &Level, _ctx: &mut Context, canvas: &mut Canvas) {
+        let stats = &level.protag.stats;
+        let max_width = stats.max_health as f32 * 5.0;
+        let width = stats.health.max(0) as f32 * 5.0;
+
+        canvas.draw(
+            &Quad,
+            DrawParam::new()
+                .dest(Vec2::new(20.0, 20.0))
+                .scale(Vec2::new(max_width, 16.0))
+                .color(Color::from_rgb(60, 0, 0)),
+        );
+        canvas.draw(
+            &Quad,
+            DrawParam::new()
+                .dest(Vec2::new(20.0, 20.0))
+                .scale(Vec2::new(width, 16.0))
+                .color(Color::RED),
+        );
+    }
 }
@@ -0,0 +1,162 @@
+use crate::assets::map::SpawnData;
+use crate::level::Level;
+use crate::npc::EnemyContainer;
+use bevy_reflect::Reflect;
+use ggez::graphics::Rect;
+use ggez::Context;
+use glam::Vec2;
+
+/// How many frames the screen takes to scroll from one room to the next.
+pub(crate) static SCROLL_FRAMES: usize = 48;
+
+/// How far past the edge of the next room the protag is walked while the screen scrolls,
+/// so that they end up all the way inside it rather than straddling the edge.
+pub(crate) static ENTER_DISTANCE: f32 = 60.0;
+
+/// A screen of a map, like one level of an LDtk world, a `Room` object in a Tiled map, or a whole Tiled map.
+///
+/// The camera stays inside the room the protag is in. Walking off its edge into one of its neighbours freezes
+/// the level while the screen scrolls over, see [`RoomTransition`]. Enemies only exist in the room the protag is in,
+/// and are spawned fresh from the map every time it's entered.
+#[derive(Debug, Clone, Reflect)]
+pub struct Room {
+    pub name: String,
+    /// What the room covers, in world space.
+    #[reflect(ignore)]
+    pub area: Rect,
+    /// The names of the rooms this one leads into. Rooms that only happen to touch aren't connected.
+    pub neighbours: Vec<String>,
+}
+
+/// The screen scrolling from one room to the next, while the protag walks into it.
+#[derive(Debug, Clone, Reflect)]
+pub struct RoomTransition {
+    /// An index into [`Level::rooms`] of the room being scrolled to.
+    pub to: usize,
+    pub frame: usize,
+    /// Where the [`Camera`](crate::camera::Camera) scrolls from and to.
+    #[reflect(ignore)]
+    pub camera_from: Vec2,
+    #[reflect(ignore)]
+    pub camera_to: Vec2,
+    #[reflect(ignore)]
+    pub protag_from: Vec2,
+    #[reflect(ignore)]
+    pub protag_to: Vec2,
+}
+
+impl Room {
+    pub fn new(name: String, area: Rect, neighbours: Vec<String>) -> Self {
+        Self {
+            name,
+            area,
+            neighbours,
+        }
+    }
+
+    pub fn contains(&self, point: Vec2) -> bool {
+        self.area.contains(point)
+    }
+
+    /// Where the protag ends up after walking into the room from `position`.
+    fn entry_point(&self, position: Vec2) -> Vec2 {
+        let min = Vec2::new(self.area.x, self.area.y) + ENTER_DISTANCE;
+        let max = Vec2::new(self.area.right(), self.area.bottom()) - ENTER_DISTANCE;
+        position.clamp(min, max.max(min))
+    }
+
+    /// Moves the camera to whichever room the protag was placed in, and spawns its enemies, like after entering a map.
+    pub fn enter(level: &mut Level, ctx: &mut Context) {
+        let position = level.protag.position;
+        level.transition = None;
+        level.room = level
+            .rooms
+            .iter()
+            .position(|room| room.contains(position))
+            .unwrap_or(0);
+        if let Some(room) = level.rooms.get(level.room) {
+            let (width, height) = ctx.gfx.drawable_size();
+            level.camera.resize(Vec2::new(width, height));
+            level.camera.snap(position, room.area);
+        }
+
+        level.enemies = EnemyContainer::new();
+        Self::spawn_enemies(level);
+    }
+
+    /// Scrolls to the next room when the protag walks out of the current one.
+    ///
+    /// The rest of the level doesn't update while the screen is scrolling.
+    pub fn update(level: &mut Level) {
+        if let Some(transition) = &mut level.transition {
+            transition.frame += 1;
+            let progress = transition.frame as f32 / SCROLL_FRAMES as f32;
+            level.camera.position = transition.camera_from.lerp(transition.camera_to, progress);
+            level.protag.position = transition.protag_from.lerp(transition.protag_to, progress);
+
+            if transition.frame >= SCROLL_FRAMES {
+                level.room = transition.to;
+                level.protag.safe_position = level.protag.position;
+                level.transition = None;
+                Self::spawn_enemies(level);
+            }
+            return;
+        }
+
+        let Some(room) = level.rooms.get(level.room) else {
+            return;
+        };
+        let position = level.protag.position;
+
+        // Rooms lead into whichever of their neighbours is on the other side of their edge. Off the edge of
+        // every neighbour, like partway through a doorway out of the map, the protag is still in the room they were in.
+        let next = match room.contains(position) {
+            true => None,
+            false => level.rooms.iter().position(|other| {
+                other.contains(position) && room.neighbours.contains(&other.name)
+            }),
+        };
+        let Some(to) = next else {
+            return;
+        };
+
+        let protag_to = level.rooms[to].entry_point(position);
+        level.transition = Some(RoomTransition {
+            to,
+            frame: 0,
+            camera_from: level.camera.position,
+            camera_to: level.camera.clamp(protag_to, level.rooms[to].area),
+            protag_from: position,
+            protag_to,
+        });
+        level.protag.knockback.cancel();
+        level.enemies = EnemyContainer::new();
+    }
+
+    /// Spawns every enemy the map placed in the current room, with their fields set.
+    ///
+    /// The fields were already checked against each enemy when the map loaded, see [`MapData::load`](crate::assets::map::MapData::load).
+    fn spawn_enemies(level: &mut Level) {
+        let Some(room) = level.rooms.get(level.room) else {
+            return;
+        };
+        let spawns: Vec<SpawnData> = level
+            .spawns
+            .iter()
+            .filter(|spawn| room.contains(spawn.position))
+            .cloned()
+            .collect();
+
+        for spawn in spawns {
+            let Some(mut enemy) = EnemyContainer::new_enemy(level.static_assets, &spawn.enemy)
+            else {
+                continue;
+            };
+            for field in spawn.fields.iter() {
+                let _ = field.apply(enemy.as_partial_reflect_mut());
+            }
+            enemy.set_position(spawn.position);
+            level.enemies.insert(enemy);
+        }
+    }
+}
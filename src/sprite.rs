@@ -1,3 +1,4 @@
+use crate::camera::Camera;
 use bevy_reflect::prelude::ReflectDefault;
 use bevy_reflect::Reflect;
 use ggez::graphics::{Canvas, DrawParam, GraphicsContext, Image, Rect, Transform};
@@ -36,26 +37,33 @@ impl Sprite {
         })
     }
 
+    /// Draws the sprite through the camera, which moves it on screen and scales it up by the camera's zoom.
     pub fn draw(
         &self,
         canvas: &mut Canvas,
+        camera: &Camera,
         sprite_world_offset: Vec2,
         draw_param: Option<DrawParam>,
     ) {
         if let Some(image) = self.texture.as_ref() {
             let param = draw_param.unwrap_or(self.data.draw_param);
-            let dest = if let Transform::Values {
+            let (dest, scale) = if let Transform::Values {
                 dest,
                 rotation: _,
-                scale: _,
+                scale,
                 offset: _,
             } = param.transform
             {
-                sprite_world_offset + Vec2::from(dest)
+                (sprite_world_offset + Vec2::from(dest), Vec2::from(scale))
             } else {
-                sprite_world_offset
+                (sprite_world_offset, Vec2::ONE)
             };
-            canvas.draw(image, param.dest(dest));
+            canvas.draw(
+                image,
+                param
+                    .dest(camera.world_to_screen(dest))
+                    .scale(scale * camera.zoom),
+            );
         };
     }
 }
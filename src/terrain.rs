@@ -0,0 +1,59 @@
+use crate::collision::{CollisionFilter, Hitbox};
+use crate::level::Level;
+use bevy_reflect::Reflect;
+use ggez::graphics::{Canvas, Color, Rect};
+use ggez::{Context, GameResult};
+use glam::Vec2;
+
+/// A piece of static level geometry that blocks projectiles.
+#[derive(Debug, Reflect, Clone)]
+pub struct Wall {
+    #[reflect(ignore)]
+    pub position: Vec2,
+    pub hitbox: Hitbox,
+    /// If true, then the wall is cracked, and is removed when caught in a bomb blast.
+    pub bombable: bool,
+}
+
+impl Wall {
+    pub fn new(position: Vec2, hitbox: Hitbox) -> Self {
+        Self {
+            position,
+            hitbox,
+            bombable: false,
+        }
+    }
+
+    /// Creates a wall covering the given rect, in world space.
+    pub fn from_rect(rect: Rect) -> Self {
+        Self::new(
+            Vec2::new(rect.x, rect.y),
+            Hitbox::new(Rect::new(0.0, 0.0, rect.w, rect.h)).with_filter(CollisionFilter::SOLID),
+        )
+    }
+
+    /// Creates a cracked wall covering the given rect, which can be blown open with a bomb.
+    pub fn cracked(rect: Rect) -> Self {
+        Self {
+            bombable: true,
+            ..Self::from_rect(rect)
+        }
+    }
+
+    pub fn draw(level: &Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        for wall in level.walls.iter() {
+            wall.hitbox.draw(
+                &mut ctx.gfx,
+                canvas,
+                &level.camera,
+                wall.position,
+                Some(if wall.bombable {
+                    Color::from_rgb(128, 96, 64)
+                } else {
+                    Color::from_rgb(128, 128, 128)
+                }),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,428 @@
+use crate::assets::tileset::{TileData, TilesetData};
+use crate::attack::Attack;
+use crate::camera::HURT_TRAUMA;
+use crate::collision::broadphase::offset_rect;
+use crate::collision::{CollisionFilter, Hitbox};
+use crate::knockback::HitOrigin;
+use crate::level::Level;
+use crate::sprite::Sprite;
+use crate::{Direction, Game};
+use bevy_reflect::prelude::ReflectDefault;
+use bevy_reflect::Reflect;
+use ggez::graphics::{Canvas, Color, DrawParam, InstanceArray, Rect, Sampler};
+use ggez::{Context, GameResult};
+use glam::Vec2;
+
+/// How big each tile is in the world, in pixels. Maps are scaled up or down to fit.
+pub(crate) static TILE_SIZE: f32 = 40.0;
+
+/// What a tile is, which decides how it collides and what it does to the protag. See [`TileData`].
+#[derive(Debug, Default, Clone, Copy, Reflect, PartialEq, Eq)]
+pub enum TileKind {
+    #[default]
+    Floor,
+    Solid,
+    Pit,
+    /// Slows the protag down, see [`TileData::speed`].
+    ShallowWater,
+    DeepWater,
+    /// Can be jumped down in the direction it faces, but not climbed back up.
+    Ledge(Direction),
+    /// Hurts the protag for as long as they stand on it.
+    DamagingFloor,
+}
+
+impl TileKind {
+    /// Reads a kind from its name in snake case, like `deep_water`. Ledges are named by the way they face, like `ledge_down`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "floor" => TileKind::Floor,
+            "solid" => TileKind::Solid,
+            "pit" => TileKind::Pit,
+            "shallow_water" => TileKind::ShallowWater,
+            "deep_water" => TileKind::DeepWater,
+            "damaging_floor" => TileKind::DamagingFloor,
+            name => TileKind::Ledge(match name.strip_prefix("ledge_")? {
+                "right" => Direction::Right,
+                "up" => Direction::Up,
+                "left" => Direction::Left,
+                "down" => Direction::Down,
+                _ => return None,
+            }),
+        })
+    }
+
+    /// Whether the tile stops something moving along `motion`.
+    ///
+    /// Ledges only block movement against the direction they face, so standing still on one never counts.
+    pub fn blocks(self, motion: Vec2) -> bool {
+        match self {
+            TileKind::Solid => true,
+            TileKind::Ledge(direction) => motion.dot(direction.to_vec()) < 0.0,
+            _ => false,
+        }
+    }
+
+    /// Hazards drop the protag back to the last safe tile they stood on.
+    pub fn is_hazard(self) -> bool {
+        matches!(self, TileKind::Pit | TileKind::DeepWater)
+    }
+}
+
+/// A grid of tiles from a [`TilesetData`], owned by the [`Level`].
+///
+/// Solid and ledge tiles are turned into [`Tilemap::colliders`], which are included in the level's [solids](crate::movement::Solids).
+#[derive(Debug, Reflect)]
+#[reflect(Default)]
+pub struct Tilemap {
+    /// Where the top left corner of the map is, in world space.
+    #[reflect(ignore)]
+    pub position: Vec2,
+    /// How big each tile is in the world, in pixels.
+    pub tile_size: f32,
+    pub width: usize,
+    pub height: usize,
+    /// Tile ids, one row after another. See [`TilesetData::tiles`]. `None` where there's no tile, which acts like floor.
+    pub tiles: Vec<Option<usize>>,
+    /// Built from [`Tilemap::tiles`] by [`Tilemap::build_colliders`], with each run of the same tile in a row
+    /// merged into one hitbox.
+    pub colliders: Vec<TileCollider>,
+    /// Where each row's colliders start in [`Tilemap::colliders`], followed by where the last row's end,
+    /// so that [`Tilemap::colliders_in`] only has to look through the rows it covers.
+    #[reflect(ignore)]
+    rows: Vec<usize>,
+    /// The tileset's atlas. Only missing from the [default](Tilemap::default) map, which draws its colliders instead.
+    sprite: Option<Sprite>,
+    /// The tiles on screen, drawn from the atlas in one go. Refilled every draw, since the camera moves.
+    #[reflect(ignore)]
+    batch: Option<InstanceArray>,
+    #[reflect(ignore)]
+    pub tileset: &'static TilesetData,
+}
+
+/// A solid run of tiles.
+#[derive(Debug, Clone, Reflect)]
+pub struct TileCollider {
+    #[reflect(ignore)]
+    pub position: Vec2,
+    pub hitbox: Hitbox,
+    pub kind: TileKind,
+}
+
+impl Default for Tilemap {
+    fn default() -> Self {
+        Self {
+            position: Vec2::ZERO,
+            tile_size: TILE_SIZE,
+            width: 0,
+            height: 0,
+            tiles: Vec::new(),
+            colliders: Vec::new(),
+            rows: vec![0],
+            sprite: None,
+            batch: None,
+            tileset: &Game::static_assets().tileset,
+        }
+    }
+}
+
+impl Tilemap {
+    /// Creates a map `width` tiles wide, with as many rows as `tiles` fills.
+    ///
+    /// Fails if the tileset's atlas can't be loaded.
+    pub fn new(
+        ctx: &Context,
+        tileset: &'static TilesetData,
+        position: Vec2,
+        tile_size: f32,
+        width: usize,
+        tiles: Vec<Option<usize>>,
+    ) -> GameResult<Self> {
+        let sprite = Sprite::new(&tileset.atlas, &ctx.gfx)?;
+        let batch = InstanceArray::new(&ctx.gfx, sprite.texture.clone());
+        let mut tilemap = Self {
+            position,
+            tile_size,
+            width,
+            height: tiles.len().checked_div(width).unwrap_or(0),
+            tiles,
+            colliders: Vec::new(),
+            rows: Vec::new(),
+            sprite: Some(sprite),
+            batch: Some(batch),
+            tileset,
+        };
+        tilemap.build_colliders();
+        Ok(tilemap)
+    }
+
+    /// The tile under a point in world space, if the point is on the map.
+    pub fn tile_at(&self, point: Vec2) -> Option<&'static TileData> {
+        let cell = ((point - self.position) / self.tile_size).floor();
+        if cell.x < 0.0 || cell.y < 0.0 {
+            return None;
+        }
+        let (column, row) = (cell.x as usize, cell.y as usize);
+        if column >= self.width || row >= self.height {
+            return None;
+        }
+        self.tile(column, row)
+    }
+
+    fn tile(&self, column: usize, row: usize) -> Option<&'static TileData> {
+        self.tiles[row * self.width + column].and_then(|id| self.tileset.tiles.get(id))
+    }
+
+    /// Every collider overlapping `area` in world space, along with its index into [`Tilemap::colliders`].
+    ///
+    /// Colliders never span more than one row, so only the rows `area` covers are looked through.
+    pub fn colliders_in(&self, area: Rect) -> impl Iterator<Item = (usize, &TileCollider)> {
+        let row = |y: f32| {
+            ((y - self.position.y) / self.tile_size)
+                .floor()
+                .clamp(0.0, self.height as f32) as usize
+        };
+        let top = row(area.y);
+        let bottom = (row(area.y + area.h) + 1).min(self.height).max(top);
+        let range = self.rows[top]..self.rows[bottom];
+
+        self.colliders[range.clone()]
+            .iter()
+            .zip(range)
+            .filter(move |(collider, _)| {
+                offset_rect(collider.hitbox.bounding_box(), collider.position).overlaps(&area)
+            })
+            .map(|(collider, index)| (index, collider))
+    }
+
+    /// Every collider overlapping `area` that stops something moving along `motion`, along with its offset.
+    /// See [`TileKind::blocks`].
+    pub fn solids(&self, area: Rect, motion: Vec2) -> impl Iterator<Item = (&Hitbox, Vec2)> {
+        self.colliders_in(area)
+            .filter(move |(_, collider)| collider.kind.blocks(motion))
+            .map(|(_, collider)| (&collider.hitbox, collider.position))
+    }
+
+    /// Rebuilds [`Tilemap::colliders`], which has to be done whenever the tiles change.
+    pub fn build_colliders(&mut self) {
+        self.colliders.clear();
+        self.rows.clear();
+        for row in 0..self.height {
+            self.rows.push(self.colliders.len());
+            let mut column = 0;
+            while column < self.width {
+                let kind = self
+                    .tile(column, row)
+                    .map_or(TileKind::Floor, |tile| tile.kind);
+                let start = column;
+                while column < self.width
+                    && self
+                        .tile(column, row)
+                        .map_or(TileKind::Floor, |tile| tile.kind)
+                        == kind
+                {
+                    column += 1;
+                }
+
+                if matches!(kind, TileKind::Solid | TileKind::Ledge(_)) {
+                    let length = (column - start) as f32 * self.tile_size;
+                    self.colliders.push(TileCollider {
+                        position: self.position
+                            + Vec2::new(start as f32, row as f32) * self.tile_size,
+                        hitbox: Hitbox::new(Rect::new(0.0, 0.0, length, self.tile_size))
+                            .with_filter(CollisionFilter::SOLID),
+                        kind,
+                    });
+                }
+            }
+        }
+        self.rows.push(self.colliders.len());
+    }
+
+    /// Applies whatever tile the protag is standing on.
+    ///
+    /// Walking into a hazard hurts the protag and drops them back where they last stood safely,
+    /// and damaging floors hurt them whenever they don't have iframes.
+    pub fn update(level: &mut Level, _ctx: &mut Context) {
+        let protag = &mut level.protag;
+        let Some(tile) = level.tilemap.tile_at(protag.position) else {
+            return;
+        };
+
+        if tile.kind.is_hazard() {
+            protag.fall(tile.damage, &level.static_assets.protag);
+            level.camera.add_trauma(HURT_TRAUMA);
+            return;
+        }
+        protag.safe_position = protag.position;
+
+        if tile.kind == TileKind::DamagingFloor
+            && protag.on_hit(
+                Attack::new(tile.damage, 0.0).transfer(HitOrigin::None),
+                &level.static_assets.protag,
+            )
+        {
+            level.camera.add_trauma(HURT_TRAUMA);
+        }
+    }
+
+    /// Draws the tiles that are on screen from the atlas, all in one batch.
+    /// Maps without an atlas draw their colliders instead.
+    pub fn draw(level: &mut Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let camera = &level.camera;
+        let tilemap = &mut level.tilemap;
+        let Some((atlas, batch)) = tilemap
+            .sprite
+            .as_ref()
+            .and_then(|sprite| sprite.texture.as_ref())
+            .zip(tilemap.batch.as_mut())
+        else {
+            for collider in tilemap.colliders.iter() {
+                collider.hitbox.draw(
+                    &mut ctx.gfx,
+                    canvas,
+                    camera,
+                    collider.position,
+                    Some(Color::from_rgb(128, 128, 128)),
+                )?;
+            }
+            return Ok(());
+        };
+
+        let source_size = tilemap.tileset.source_size;
+        let columns = ((atlas.width() as f32 / source_size) as usize).max(1);
+        let src_size = Vec2::new(
+            source_size / atlas.width() as f32,
+            source_size / atlas.height() as f32,
+        );
+        let scale = Vec2::splat(tilemap.tile_size / source_size) * camera.zoom;
+
+        // Every tile at least partly on screen, clamped to the map
+        let center = camera.position + camera.shake - tilemap.position;
+        let half = camera.view_size() / 2.0;
+        let size = Vec2::new(tilemap.width as f32, tilemap.height as f32);
+        let min = ((center - half) / tilemap.tile_size)
+            .floor()
+            .clamp(Vec2::ZERO, size);
+        let max = ((center + half) / tilemap.tile_size)
+            .ceil()
+            .clamp(Vec2::ZERO, size);
+
+        batch.clear();
+        for row in min.y as usize..max.y as usize {
+            for column in min.x as usize..max.x as usize {
+                let Some(id) = tilemap.tiles[row * tilemap.width + column] else {
+                    continue;
+                };
+                let cell = Vec2::new(column as f32, row as f32);
+                let src = Rect::new(
+                    (id % columns) as f32 * src_size.x,
+                    (id / columns) as f32 * src_size.y,
+                    src_size.x,
+                    src_size.y,
+                );
+                batch.push(
+                    DrawParam::new()
+                        .src(src)
+                        .dest(camera.world_to_screen(tilemap.position + cell * tilemap.tile_size))
+                        .scale(scale),
+                );
+            }
+        }
+
+        // Tiles are pixel art, so they're scaled up without blurring.
+        canvas.set_sampler(Sampler::nearest_clamp());
+        canvas.draw(batch, DrawParam::new());
+        canvas.set_sampler(Sampler::linear_clamp());
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sprite::SpriteData;
+
+    /// A map of 10 pixel tiles at the origin, where tile 1 is solid and tile 0 is floor.
+    fn tilemap(width: usize, tiles: &[usize]) -> Tilemap {
+        let tileset = Box::leak(Box::new(TilesetData {
+            path: String::new(),
+            atlas: SpriteData::default(),
+            source_size: 10.0,
+            tiles: vec![
+                TileData::new(TileKind::Floor),
+                TileData::new(TileKind::Solid),
+            ],
+        }));
+        let mut tilemap = Tilemap {
+            position: Vec2::ZERO,
+            tile_size: 10.0,
+            width,
+            height: tiles.len() / width,
+            tiles: tiles.iter().map(|&id| Some(id)).collect(),
+            colliders: Vec::new(),
+            rows: Vec::new(),
+            sprite: None,
+            batch: None,
+            tileset,
+        };
+        tilemap.build_colliders();
+        tilemap
+    }
+
+    #[test]
+    fn colliders_are_looked_up_by_area() {
+        #[rustfmt::skip]
+        let tilemap = tilemap(4, &[
+            1, 1, 0, 0,
+            0, 0, 0, 1,
+            1, 0, 1, 1,
+        ]);
+        let indices = |area: Rect| -> Vec<usize> {
+            tilemap.colliders_in(area).map(|(index, _)| index).collect()
+        };
+
+        assert_eq!(tilemap.colliders.len(), 4);
+        assert_eq!(indices(Rect::new(0.0, 0.0, 40.0, 30.0)), [0, 1, 2, 3]);
+        // Only the middle row, and only the right side of it
+        assert_eq!(indices(Rect::new(25.0, 12.0, 10.0, 5.0)), [1]);
+        assert_eq!(indices(Rect::new(12.0, 12.0, 5.0, 5.0)), [] as [usize; 0]);
+        // Rows past either edge are clamped to the map
+        assert_eq!(indices(Rect::new(-5.0, -100.0, 10.0, 200.0)), [0, 2]);
+        assert_eq!(indices(Rect::new(0.0, 100.0, 40.0, 10.0)), [] as [usize; 0]);
+    }
+
+    #[test]
+    fn empty_maps_have_no_colliders() {
+        let tilemap = tilemap(3, &[]);
+        assert_eq!(
+            tilemap
+                .colliders_in(Rect::new(-5.0, -5.0, 20.0, 20.0))
+                .count(),
+            0
+        );
+    }
+
+    #[test]
+    fn kinds_are_named_in_snake_case() {
+        assert_eq!(TileKind::from_name("floor"), Some(TileKind::Floor));
+        assert_eq!(TileKind::from_name("deep_water"), Some(TileKind::DeepWater));
+        assert_eq!(
+            TileKind::from_name("damaging_floor"),
+            Some(TileKind::DamagingFloor)
+        );
+        assert_eq!(
+            TileKind::from_name("ledge_up"),
+            Some(TileKind::Ledge(Direction::Up))
+        );
+    }
+
+    #[test]
+    fn unknown_kinds_have_no_name() {
+        assert_eq!(TileKind::from_name("DeepWater"), None);
+        assert_eq!(TileKind::from_name("ledge"), None);
+        assert_eq!(TileKind::from_name("ledge_sideways"), None);
+        assert_eq!(TileKind::from_name(""), None);
+    }
+}
@@ -0,0 +1,112 @@
+use crate::collision::{CollisionFilter, Hitbox};
+use crate::level::Level;
+use crate::room::Room;
+use bevy_reflect::Reflect;
+use ggez::graphics::{Canvas, Color, Rect};
+use ggez::{Context, GameResult};
+use glam::Vec2;
+
+/// An area of the level that notices the protag walking into it, placed by a map.
+#[derive(Debug, Clone, Reflect)]
+pub struct Trigger {
+    pub name: String,
+    #[reflect(ignore)]
+    pub position: Vec2,
+    pub hitbox: Hitbox,
+    /// Whether the protag is inside.
+    ///
+    /// Starts out true, so that a trigger the protag is placed inside of, like the door they came through,
+    /// doesn't fire until they've stepped out of it.
+    pub occupied: bool,
+    /// Whether the protag walked in this frame.
+    pub entered: bool,
+    /// Doors take the protag somewhere else as soon as they're entered.
+    pub door: Option<Door>,
+}
+
+/// Where a door trigger leads.
+#[derive(Debug, Clone, Reflect, PartialEq)]
+pub struct Door {
+    /// The path of the map the door leads to, or `None` for somewhere else in the same map,
+    /// which the protag is moved to without reloading the map.
+    pub map: Option<String>,
+    /// The name of the entrance the protag comes out of.
+    pub entrance: String,
+}
+
+impl Trigger {
+    /// Creates a trigger covering the given rect, in world space.
+    pub fn new(name: String, area: Rect, door: Option<Door>) -> Self {
+        Self {
+            name,
+            position: Vec2::new(area.x, area.y),
+            hitbox: Hitbox::new(Rect::new(0.0, 0.0, area.w, area.h))
+                .with_filter(CollisionFilter::TRIGGER),
+            occupied: true,
+            entered: false,
+            door,
+        }
+    }
+
+    /// Takes the protag through any door they walked into.
+    ///
+    /// Doors to other maps can only be checked once they're walked through, so if the map or its entrance can't be
+    /// loaded, the problem is printed and the protag stays where they are.
+    pub fn update(level: &mut Level, ctx: &mut Context) {
+        let mut door = None;
+        for trigger in level.triggers.iter_mut() {
+            let inside = trigger.hitbox.colliding_single(
+                &level.protag.hurtbox,
+                trigger.position,
+                level.protag.position,
+            );
+            trigger.entered = inside && !trigger.occupied;
+            trigger.occupied = inside;
+            if trigger.entered && door.is_none() {
+                door = trigger.door.clone();
+            }
+        }
+
+        let Some(door) = door else {
+            return;
+        };
+        match door.map {
+            Some(map) => {
+                if let Err(error) = level.change_map(ctx, &map, Some(&door.entrance)) {
+                    eprintln!("couldn't go through the door to {map}: {error}");
+                }
+            }
+            // Doors within the same map just move the protag, leaving the rest of the map as it is.
+            // Their entrances were already checked when the map loaded, see `MapData::check`.
+            None => {
+                let Some(entrance) = level.entrances.get(&door.entrance).copied() else {
+                    return;
+                };
+                level.protag.place(entrance);
+                // Like after entering a map, the door the protag comes out of waits for them to step out of it.
+                for trigger in level.triggers.iter_mut() {
+                    trigger.occupied = true;
+                }
+                Room::enter(level, ctx);
+            }
+        }
+    }
+
+    /// Only doors are drawn, since other triggers are invisible.
+    pub fn draw(level: &Level, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        for trigger in level
+            .triggers
+            .iter()
+            .filter(|trigger| trigger.door.is_some())
+        {
+            trigger.hitbox.draw(
+                &mut ctx.gfx,
+                canvas,
+                &level.camera,
+                trigger.position,
+                Some(Color::from_rgb(40, 24, 16)),
+            )?;
+        }
+        Ok(())
+    }
+}